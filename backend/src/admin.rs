@@ -0,0 +1,226 @@
+use crate::{
+    client::{Clients, Status},
+    config::Config,
+    game::{GameEvent, ProtocolStateInfo, QueryError},
+    Games,
+};
+use common::Outcome;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+use warp::{
+    http::StatusCode,
+    reject::Reject,
+    reply::{json, Response},
+    Rejection, Reply,
+};
+
+type Result<T> = std::result::Result<T, Rejection>;
+
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+#[derive(Serialize, Debug)]
+pub struct ClientInfo {
+    id: String,
+    user_id: usize,
+    status: Status,
+    connected: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameInfo {
+    id: String,
+    protocol_state: ProtocolStateInfo,
+    turn: u32,
+    turns_left: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameList {
+    games: Vec<GameInfo>,
+    // Games whose task didn't answer in time, which are likely stuck
+    unresponsive: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EndGameRequest {
+    outcome: Outcome,
+}
+
+// Rejects requests that don't carry the configured admin token. Every request is
+// rejected if no token is configured.
+pub async fn check_token(
+    authorization: Option<String>,
+    config: Arc<Config>,
+) -> std::result::Result<(), Rejection> {
+    let token = authorization
+        .as_deref()
+        .and_then(|header| header.strip_prefix("Bearer "));
+    match (&config.admin_token, token) {
+        (Some(expected), Some(token)) if expected.matches(token) => Ok(()),
+        _ => {
+            warn!("rejected unauthorized admin request");
+            Err(warp::reject::custom(Unauthorized))
+        }
+    }
+}
+
+pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply, Rejection> {
+    if err.find::<Unauthorized>().is_some() {
+        Ok(StatusCode::UNAUTHORIZED)
+    } else {
+        Err(err)
+    }
+}
+
+pub async fn list_clients_handler(clients: Clients) -> Result<impl Reply> {
+    let clients: Vec<ClientInfo> = clients
+        .read()
+        .await
+        .iter()
+        .map(|(id, client)| ClientInfo {
+            id: id.clone(),
+            user_id: client.user_id,
+            status: client.status.clone(),
+            connected: client.sender.is_some(),
+        })
+        .collect();
+    Ok(json(&clients))
+}
+
+pub async fn kick_client_handler(id: String, clients: Clients, games: Games) -> Result<impl Reply> {
+    let client = match clients.write().await.remove(&id) {
+        Some(client) => client,
+        None => return Err(warp::reject::not_found()),
+    };
+    info!("admin kicked client {}", id);
+    // The client was removed above, so its connection won't notify the game when it closes
+    if let Status::InGame { uuid, player_num } = &client.status {
+        if let Some(game) = games.read().await.get(uuid) {
+            let _ = game.send(GameEvent::Leave(*player_num));
+        }
+    }
+    if let Some(sender) = &client.sender {
+        let _ = sender.close();
+    }
+    Ok(StatusCode::OK)
+}
+
+pub async fn list_games_handler(games: Games) -> Result<impl Reply> {
+    let handles: Vec<_> = games
+        .read()
+        .await
+        .iter()
+        .map(|(id, handle)| (id.clone(), handle.clone()))
+        .collect();
+    // Ask every game at once, so one stuck game only delays the list by the timeout
+    let replies = join_all(
+        handles
+            .iter()
+            .map(|(_, handle)| handle.query(GameEvent::Details)),
+    )
+    .await;
+    let mut list = GameList {
+        games: vec![],
+        unresponsive: vec![],
+    };
+    for ((id, _), reply) in handles.into_iter().zip(replies) {
+        match reply {
+            Ok(details) => list.games.push(GameInfo {
+                id: details.id,
+                protocol_state: details.protocol_state,
+                turn: details.turn,
+                turns_left: details.turns_left,
+            }),
+            // Games that end before replying are left out
+            Err(QueryError::Ended) => (),
+            Err(QueryError::Unresponsive) => {
+                warn!("game {} didn't answer a details query", id);
+                list.unresponsive.push(id);
+            }
+        }
+    }
+    Ok(json(&list))
+}
+
+pub async fn game_handler(id: String, games: Games) -> Result<Response> {
+    let game = games.read().await.get(&id).cloned();
+    let game = game.ok_or_else(warp::reject::not_found)?;
+    match game.query(GameEvent::Details).await {
+        Ok(details) => Ok(json(&details).into_response()),
+        Err(QueryError::Ended) => Err(warp::reject::not_found()),
+        Err(QueryError::Unresponsive) => {
+            warn!("game {} didn't answer a details query", id);
+            Ok(StatusCode::SERVICE_UNAVAILABLE.into_response())
+        }
+    }
+}
+
+pub async fn end_game_handler(
+    id: String,
+    body: EndGameRequest,
+    games: Games,
+) -> Result<impl Reply> {
+    let game = games.read().await.get(&id).cloned();
+    match game {
+        Some(game) if game.send(GameEvent::ForceEnd(body.outcome)).is_ok() => {
+            info!("admin ended game {} with outcome {:?}", id, body.outcome);
+            Ok(StatusCode::OK)
+        }
+        _ => Err(warp::reject::not_found()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameHandle;
+    use hashbrown::HashMap;
+    use tokio::sync::{mpsc, RwLock};
+
+    fn config(admin_token: Option<&str>) -> Arc<Config> {
+        Arc::new(Config {
+            admin_token: admin_token.map(|t| t.parse().unwrap()),
+            ..Config::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_check_token() {
+        let valid = check_token(Some("Bearer secret".to_string()), config(Some("secret"))).await;
+        assert!(valid.is_ok());
+        let wrong = check_token(Some("Bearer nope".to_string()), config(Some("secret"))).await;
+        assert!(wrong.is_err());
+        let missing = check_token(None, config(Some("secret"))).await;
+        assert!(missing.is_err());
+        let not_bearer = check_token(Some("secret".to_string()), config(Some("secret"))).await;
+        assert!(not_bearer.is_err());
+        let disabled = check_token(Some("Bearer ".to_string()), config(None)).await;
+        assert!(disabled.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_games_reports_unresponsive_games() {
+        let games: Games = Arc::new(RwLock::new(HashMap::new()));
+        // A game whose task is stuck, and one that has already ended
+        let (stuck, _stuck_receiver) = mpsc::unbounded_channel();
+        let (ended, _) = mpsc::unbounded_channel();
+        games.write().await.extend([
+            ("stuck".to_string(), GameHandle(stuck)),
+            ("ended".to_string(), GameHandle(ended)),
+        ]);
+        let response = list_games_handler(games).await.unwrap().into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            list,
+            serde_json::json!({ "games": [], "unresponsive": ["stuck"] })
+        );
+    }
+}
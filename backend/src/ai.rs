@@ -0,0 +1,145 @@
+use crate::client::{SendError, SendMsg};
+use crate::game::{GameEvent, GameHandle};
+use common::ai::{Agent, GreedyAgent, RandomAgent};
+use common::{messages, PlayerNum};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+#[derive(Error, Debug)]
+#[error("Unknown difficulty {0}. Expected easy or hard")]
+pub struct UnknownDifficulty(String);
+
+#[derive(Serialize, Copy, Clone, Debug, Default, PartialEq)]
+pub enum Difficulty {
+    #[default]
+    Easy,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = UnknownDifficulty;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(UnknownDifficulty(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl Difficulty {
+    fn agent(self) -> Box<dyn Agent + Send> {
+        match self {
+            Difficulty::Easy => Box::<RandomAgent>::default(),
+            Difficulty::Hard => Box::new(GreedyAgent),
+        }
+    }
+}
+
+// Takes a seat in a game like a websocket client would. Messages sent to it are forwarded
+// to a task that picks the AI's replies, so the game never waits on the AI's moves.
+#[derive(Debug)]
+pub struct AiPeer(mpsc::UnboundedSender<String>);
+
+impl SendMsg for AiPeer {
+    fn send(&self, msg: &str) -> Result<(), SendError> {
+        self.0.send(msg.to_string()).map_err(|_| SendError)
+    }
+}
+
+// Creates an AI peer. Messages sent to it are queued until its task is spawned with the
+// returned receiver, which can only happen once the game it plays in has a handle.
+pub fn peer() -> (AiPeer, mpsc::UnboundedReceiver<String>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (AiPeer(sender), receiver)
+}
+
+pub fn spawn(
+    receiver: mpsc::UnboundedReceiver<String>,
+    difficulty: Difficulty,
+    player_num: PlayerNum,
+    game: GameHandle,
+) {
+    tokio::task::spawn(run(receiver, difficulty.agent(), player_num, game));
+}
+
+async fn run(
+    mut receiver: mpsc::UnboundedReceiver<String>,
+    mut agent: Box<dyn Agent + Send>,
+    player_num: PlayerNum,
+    game: GameHandle,
+) {
+    // The first state of every game is the hand that may be redrawn
+    let mut redraw_pending = true;
+    while let Some(msg) = receiver.recv().await {
+        if msg == "leave" {
+            break;
+        }
+        let reply = if serde_json::from_str::<messages::GameEnd>(&msg).is_ok() {
+            // Leave it to the player to decide whether to play again
+            redraw_pending = true;
+            "true".to_string()
+        } else if let Ok(state) = serde_json::from_str::<messages::GameState>(&msg) {
+            if redraw_pending {
+                redraw_pending = false;
+                agent.redraw(&state.board, &state.player).to_string()
+            } else {
+                // Searching for a move can take a while, so keep it off the async workers
+                let (returned_agent, input) = match tokio::task::spawn_blocking(move || {
+                    let input = agent.choose(&state.board, &state.player);
+                    (agent, input)
+                })
+                .await
+                {
+                    Ok(result) => result,
+                    Err(err) => {
+                        error!("AI failed to choose an input: {}", err);
+                        break;
+                    }
+                };
+                agent = returned_agent;
+                // If we cannot serialize our own input, panic because that's a bug
+                serde_json::to_string(&input).unwrap()
+            }
+        } else {
+            error!("AI received an unexpected message: {}", msg);
+            continue;
+        };
+        if game
+            .send(GameEvent::Message {
+                player_num,
+                msg: reply,
+            })
+            .is_err()
+        {
+            break;
+        }
+    }
+    info!("AI player {:?} stopped", player_num);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_difficulty() {
+        assert_eq!("easy".parse::<Difficulty>().unwrap(), Difficulty::Easy);
+        assert_eq!("hard".parse::<Difficulty>().unwrap(), Difficulty::Hard);
+        assert!("medium".parse::<Difficulty>().is_err());
+    }
+}
@@ -1,6 +1,9 @@
+use crate::ai::Difficulty;
 use common::PlayerNum;
 use hashbrown::HashMap;
+use serde::Serialize;
 use std::sync::Arc;
+use std::time::Instant;
 use thiserror::Error;
 use tokio::sync::{mpsc, RwLock};
 use warp::ws::Message;
@@ -11,16 +14,32 @@ pub struct SendError;
 
 pub type Clients = Arc<RwLock<HashMap<String, Client>>>;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 pub enum Status {
-    JoiningGame,
-    InGame { uuid: String, player_num: PlayerNum },
+    JoiningGame {
+        // Used to measure how long matchmaking takes
+        #[serde(skip)]
+        since: Instant,
+        // The AI to play against if no opponent is found in time
+        difficulty: Difficulty,
+    },
+    InGame {
+        uuid: String,
+        player_num: PlayerNum,
+    },
     Idle,
 }
 
 #[derive(Debug, Clone)]
 pub struct Sender(pub mpsc::UnboundedSender<Result<Message, warp::Error>>);
 
+impl Sender {
+    // Asks the client's websocket connection to close
+    pub fn close(&self) -> Result<(), SendError> {
+        self.0.send(Ok(Message::close())).map_err(|_| SendError)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Client {
     pub user_id: usize,
@@ -32,6 +51,15 @@ pub trait SendMsg {
     fn send(&self, msg: &str) -> Result<(), SendError>;
 }
 
+// Anything game messages can be sent to, like a player's websocket or an in-process AI
+pub type Peer = Box<dyn SendMsg + Send + Sync>;
+
+impl<T: SendMsg + ?Sized> SendMsg for Box<T> {
+    fn send(&self, msg: &str) -> Result<(), SendError> {
+        (**self).send(msg)
+    }
+}
+
 impl SendMsg for Sender {
     fn send(&self, msg: &str) -> Result<(), SendError> {
         self.0.send(Ok(Message::text(msg))).map_err(|_| SendError)
@@ -0,0 +1,290 @@
+use clap::Parser;
+use common::{DeckRng, DEFAULT_TURNS};
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Failed to parse config file {0}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("The admin token can't be empty. Leave it unset to disable the admin API")]
+pub struct EmptyAdminToken;
+
+// Bearer token required by the admin API. Debug output is redacted so that
+// the token doesn't end up in the logs. Empty tokens are rejected when the config is
+// loaded, since an empty bearer token is easy to send by accident.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct AdminToken(String);
+
+impl AdminToken {
+    // Compares in constant time, so response times don't give away how much of the
+    // token a guess got right
+    pub fn matches(&self, token: &str) -> bool {
+        self.0.as_bytes().ct_eq(token.as_bytes()).into()
+    }
+}
+
+impl TryFrom<String> for AdminToken {
+    type Error = EmptyAdminToken;
+
+    fn try_from(token: String) -> Result<Self, Self::Error> {
+        if token.trim().is_empty() {
+            return Err(EmptyAdminToken);
+        }
+        Ok(AdminToken(token))
+    }
+}
+
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AdminToken(<redacted>)")
+    }
+}
+
+impl std::str::FromStr for AdminToken {
+    type Err = EmptyAdminToken;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AdminToken::try_from(s.to_string())
+    }
+}
+
+// Settings are layered from lowest to highest precedence:
+// built-in defaults, the config file, environment variables and CLI flags.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_address: SocketAddr,
+    // The externally reachable URL of the server, used to build the websocket URLs
+    // handed out on registration. This differs from the bind address when the server
+    // runs behind a reverse proxy, e.g. wss://example.com/tableturf
+    pub public_url: String,
+    pub log_dir: PathBuf,
+    // Origins allowed to make cross-origin requests. Any origin is allowed if empty.
+    pub cors_allowed_origins: Vec<String>,
+    pub turns: u32,
+    // The admin API is disabled if no token is set
+    pub admin_token: Option<AdminToken>,
+    // Seeds the card draws of every game so that games are reproducible, e.g. in tests.
+    // Draws are random if unset.
+    pub rng_seed: Option<u64>,
+    // How long a client waits for an opponent before being matched against an AI.
+    // Clients wait indefinitely if unset.
+    pub ai_fallback_secs: Option<u64>,
+    // Directory of puzzle files served under /puzzles. No puzzles are served if unset.
+    pub puzzle_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 8000)),
+            public_url: "ws://127.0.0.1:8000".to_string(),
+            log_dir: PathBuf::from("./logs"),
+            cors_allowed_origins: vec![],
+            turns: DEFAULT_TURNS,
+            admin_token: None,
+            rng_seed: None,
+            ai_fallback_secs: None,
+            puzzle_dir: None,
+        }
+    }
+}
+
+#[derive(Parser, Debug, Default)]
+#[command(about = "Tableturf web server")]
+pub struct Args {
+    /// Path to a TOML config file
+    #[arg(long, env = "TABLETURF_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address and port to listen on
+    #[arg(long, env = "TABLETURF_BIND_ADDRESS")]
+    pub bind_address: Option<SocketAddr>,
+    /// Public base URL used to build websocket URLs, e.g. wss://example.com/tableturf
+    #[arg(long, env = "TABLETURF_PUBLIC_URL")]
+    pub public_url: Option<String>,
+    /// Directory to write log files to
+    #[arg(long, env = "TABLETURF_LOG_DIR")]
+    pub log_dir: Option<PathBuf>,
+    /// Comma-separated list of origins allowed by CORS. Any origin is allowed if unset.
+    #[arg(long, env = "TABLETURF_CORS_ALLOWED_ORIGINS", value_delimiter = ',')]
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// Number of turns in a match. Must be at least 1.
+    #[arg(long, env = "TABLETURF_TURNS")]
+    pub turns: Option<NonZeroU32>,
+    /// Bearer token for the admin API. The admin API is disabled if unset.
+    #[arg(long, env = "TABLETURF_ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<AdminToken>,
+    /// Seed for card draws, making every game reproducible. Draws are random if unset.
+    #[arg(long, env = "TABLETURF_RNG_SEED")]
+    pub rng_seed: Option<u64>,
+    /// Seconds a client waits for an opponent before playing against an AI instead.
+    /// Clients wait indefinitely if unset.
+    #[arg(long, env = "TABLETURF_AI_FALLBACK_SECS")]
+    pub ai_fallback_secs: Option<u64>,
+    /// Directory of puzzle files to serve. No puzzles are served if unset.
+    #[arg(long, env = "TABLETURF_PUZZLE_DIR")]
+    pub puzzle_dir: Option<PathBuf>,
+}
+
+// Every field is optional so that a config file only needs to contain the settings it overrides
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub bind_address: Option<SocketAddr>,
+    pub public_url: Option<String>,
+    pub log_dir: Option<PathBuf>,
+    pub cors_allowed_origins: Option<Vec<String>>,
+    // Games with no turns would end as soon as they start, so 0 is rejected
+    pub turns: Option<NonZeroU32>,
+    pub admin_token: Option<AdminToken>,
+    pub rng_seed: Option<u64>,
+    pub ai_fallback_secs: Option<u64>,
+    pub puzzle_dir: Option<PathBuf>,
+}
+
+impl FileConfig {
+    pub fn read(path: PathBuf) -> Result<Self, ConfigError> {
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => return Err(ConfigError::Read(path, err)),
+        };
+        toml::from_str(&contents).map_err(|err| ConfigError::Parse(path, err))
+    }
+}
+
+impl Config {
+    // Loads the config from the CLI flags and environment variables of this process,
+    // along with the config file they point to, if any.
+    pub fn load() -> Result<Self, ConfigError> {
+        let args = Args::parse();
+        let file = match args.config.clone() {
+            Some(path) => FileConfig::read(path)?,
+            None => FileConfig::default(),
+        };
+        Ok(Config::layered(file, args))
+    }
+
+    pub fn layered(file: FileConfig, args: Args) -> Self {
+        let default = Config::default();
+        Config {
+            bind_address: args
+                .bind_address
+                .or(file.bind_address)
+                .unwrap_or(default.bind_address),
+            public_url: args
+                .public_url
+                .or(file.public_url)
+                .unwrap_or(default.public_url),
+            log_dir: args.log_dir.or(file.log_dir).unwrap_or(default.log_dir),
+            cors_allowed_origins: args
+                .cors_allowed_origins
+                .or(file.cors_allowed_origins)
+                .unwrap_or(default.cors_allowed_origins),
+            turns: args
+                .turns
+                .or(file.turns)
+                .map_or(default.turns, NonZeroU32::get),
+            admin_token: args.admin_token.or(file.admin_token),
+            rng_seed: args.rng_seed.or(file.rng_seed),
+            ai_fallback_secs: args.ai_fallback_secs.or(file.ai_fallback_secs),
+            puzzle_dir: args.puzzle_dir.or(file.puzzle_dir),
+        }
+    }
+
+    // Gets the RNG a new game draws its cards from
+    pub fn deck_rng(&self) -> DeckRng {
+        match self.rng_seed {
+            Some(seed) => DeckRng::from_seed(seed),
+            None => DeckRng::default(),
+        }
+    }
+
+    // Gets the URL a registered client should open its websocket connection on
+    pub fn websocket_url(&self, id: &str) -> String {
+        format!("{}/ws/{}", self.public_url.trim_end_matches('/'), id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layered_config() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            bind_address = "0.0.0.0:9000"
+            public_url = "wss://example.com/tableturf/"
+            turns = 8
+            "#,
+        )
+        .unwrap();
+        let args = Args::try_parse_from(["tableturf-web", "--turns", "4"]).unwrap();
+        let config = Config::layered(file, args);
+        assert_eq!(config.bind_address, SocketAddr::from(([0, 0, 0, 0], 9000)));
+        assert_eq!(config.log_dir, PathBuf::from("./logs"));
+        assert!(config.cors_allowed_origins.is_empty());
+        assert_eq!(config.turns, 4);
+        assert!(config.admin_token.is_none());
+        assert_eq!(
+            config.websocket_url("abc"),
+            "wss://example.com/tableturf/ws/abc"
+        );
+    }
+
+    #[test]
+    fn test_cors_allowed_origins_flag() {
+        let args = Args::try_parse_from([
+            "tableturf-web",
+            "--cors-allowed-origins",
+            "http://localhost:8080,https://example.com",
+        ])
+        .unwrap();
+        let config = Config::layered(FileConfig::default(), args);
+        assert_eq!(
+            config.cors_allowed_origins,
+            vec!["http://localhost:8080", "https://example.com"]
+        );
+    }
+
+    #[test]
+    fn test_admin_token_redacted() {
+        let file: FileConfig = toml::from_str(r#"admin_token = "secret""#).unwrap();
+        let config = Config::layered(file, Args::default());
+        assert_eq!(config.admin_token, Some(AdminToken("secret".to_string())));
+        assert!(!format!("{:?}", config).contains("secret"));
+    }
+
+    #[test]
+    fn test_empty_admin_token() {
+        assert!(toml::from_str::<FileConfig>(r#"admin_token = """#).is_err());
+        assert!(toml::from_str::<FileConfig>(r#"admin_token = "  ""#).is_err());
+        assert!(Args::try_parse_from(["tableturf-web", "--admin-token", ""]).is_err());
+        let token: AdminToken = "secret".parse().unwrap();
+        assert!(token.matches("secret"));
+        assert!(!token.matches("secre"));
+        assert!(!token.matches(""));
+    }
+
+    #[test]
+    fn test_zero_turns() {
+        assert!(toml::from_str::<FileConfig>("turns = 0").is_err());
+        assert!(Args::try_parse_from(["tableturf-web", "--turns", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_file_setting() {
+        assert!(toml::from_str::<FileConfig>("port = 8000").is_err());
+    }
+}
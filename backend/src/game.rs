@@ -1,49 +1,303 @@
-use crate::client::SendMsg;
-use crate::util;
+use crate::client::{Clients, Peer, SendError, SendMsg, Sender, Status};
+use crate::{metrics, util};
+use common::notation::format_input;
 use common::{
-    messages, DeckRng, DrawRng, GameState, InputError, Outcome, PlayerNum, RawInput, ValidInput,
+    messages, Action, Board, DeckRng, DrawRng, GameState, InputError, Outcome, Player, PlayerNum,
+    RawInput, ValidInput,
 };
 use hashbrown::HashMap;
 use serde::Serialize;
 use serde_json::from_str;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, warn};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tracing::{error, info, warn};
 
-pub type Games = Arc<RwLock<HashMap<String, Game<DeckRng>>>>;
+// How long a game's task gets to answer a query before it's reported as unresponsive
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+// The games map only routes messages to each game's task, so it is never locked
+// while a game processes a message.
+pub type Games = Arc<RwLock<HashMap<String, GameHandle>>>;
+
+#[derive(Debug)]
+pub enum GameEvent {
+    // A message received from one of the game's players
+    Message { player_num: PlayerNum, msg: String },
+    // One of the game's players disconnected, which ends the game
+    Leave(PlayerNum),
+    // A client started watching the game
+    Spectate { id: String, sender: Sender },
+    // A spectator disconnected
+    StopSpectating(String),
+    // Request for a summary of the game, used to list live games
+    Summary(oneshot::Sender<GameSummary>),
+    // Request for the game's full state, for admins
+    Details(oneshot::Sender<GameDetails>),
+    // Ends the game immediately with the given outcome, for admins
+    ForceEnd(Outcome),
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameSummary {
+    pub id: String,
+    pub turns_left: u32,
+    pub spectators: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameDetails {
+    pub id: String,
+    pub player_ids: [String; 2],
+    pub protocol_state: ProtocolStateInfo,
+    // The current turn, starting from 1
+    pub turn: u32,
+    pub turns_left: u32,
+    pub board: Board,
+    pub players: [Player; 2],
+    pub spectators: usize,
+}
+
+// A serializable view of a game's ProtocolState that only shows which
+// players have made their choice for the current phase
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum ProtocolStateInfo {
+    Redraw { chosen: [bool; 2] },
+    InGame { chosen: [bool; 2] },
+    Rematch { chosen: [bool; 2] },
+    End,
+}
+
+impl From<&ProtocolState> for ProtocolStateInfo {
+    fn from(state: &ProtocolState) -> Self {
+        match state {
+            ProtocolState::Redraw([c1, c2]) => ProtocolStateInfo::Redraw {
+                chosen: [c1.is_some(), c2.is_some()],
+            },
+            ProtocolState::InGame([i1, i2]) => ProtocolStateInfo::InGame {
+                chosen: [i1.is_some(), i2.is_some()],
+            },
+            ProtocolState::Rematch([c1, c2]) => ProtocolStateInfo::Rematch {
+                chosen: [c1.is_some(), c2.is_some()],
+            },
+            ProtocolState::End => ProtocolStateInfo::End,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameHandle(pub(crate) mpsc::UnboundedSender<GameEvent>);
+
+impl GameHandle {
+    pub fn send(&self, event: GameEvent) -> Result<(), SendError> {
+        self.0.send(event).map_err(|_| SendError)
+    }
+
+    // Asks the game's task for something through a reply channel, such as its summary
+    pub async fn query<T>(
+        &self,
+        event: impl FnOnce(oneshot::Sender<T>) -> GameEvent,
+    ) -> Result<T, QueryError> {
+        let (reply, response) = oneshot::channel();
+        self.send(event(reply)).map_err(|_| QueryError::Ended)?;
+        match tokio::time::timeout(QUERY_TIMEOUT, response).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(QueryError::Ended),
+            Err(_) => Err(QueryError::Unresponsive),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryError {
+    // The game ended before it could answer
+    Ended,
+    // The game's task didn't answer within QUERY_TIMEOUT
+    Unresponsive,
+}
+
+// A validated input, along with the action it was created from so that it can be
+// revealed to spectators once both players have chosen
+#[derive(Clone, Debug)]
+struct TurnInput {
+    input: ValidInput,
+    action: Action,
+}
 
 #[derive(Clone, Debug)]
 enum ProtocolState {
     // true means that the player wants to redraw their hand, false means they don't
     Redraw([Option<bool>; 2]),
-    InGame([Option<ValidInput>; 2]),
+    InGame([Option<TurnInput>; 2]),
     // true means that the player wants a rematch, false means they don't
     Rematch([Option<bool>; 2]),
     End,
 }
 
+impl ProtocolState {
+    fn name(&self) -> &'static str {
+        match self {
+            ProtocolState::Redraw(_) => "redraw",
+            ProtocolState::InGame(_) => "in_game",
+            ProtocolState::Rematch(_) => "rematch",
+            ProtocolState::End => "end",
+        }
+    }
+}
+
+// Spectators keyed by their connection ID
+#[derive(Default)]
+struct Spectators(HashMap<String, Peer>);
+
+impl Debug for Spectators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.keys()).finish()
+    }
+}
+
 #[derive(Debug)]
 pub struct Game<R: DrawRng + Default + Debug> {
     game_state: GameState<R>,
     // The first element is Player 1's ID and the second is Player 2's ID
     player_ids: [String; 2],
     protocol_state: ProtocolState,
+    // The number of turns each match lasts, including rematches
+    turns: u32,
+    spectators: Spectators,
+    // The cards played on the last turn, so that new spectators can be shown them
+    revealed: Option<[messages::RevealedCard; 2]>,
+    // When the current turn started waiting for the players' inputs
+    turn_started: Instant,
 }
 
 impl<R: DrawRng + Default + Debug> Game<R> {
     pub fn new(game_state: GameState<R>, player_ids: [String; 2]) -> Self {
         Game {
+            turns: game_state.turns_left(),
             game_state,
             player_ids,
             protocol_state: ProtocolState::Redraw([None, None]),
+            spectators: Spectators::default(),
+            revealed: None,
+            turn_started: Instant::now(),
         }
     }
 
+    pub fn turns_left(&self) -> u32 {
+        self.game_state.turns_left()
+    }
+
+    pub fn spectator_count(&self) -> usize {
+        self.spectators.0.len()
+    }
+
+    // Adds a read-only client to the game and sends them the current state
+    pub fn add_spectator(&mut self, id: String, spectator: Peer) {
+        send_message(spectator.as_ref(), self.spectator_state());
+        self.spectators.0.insert(id, spectator);
+    }
+
+    pub fn remove_spectator(&mut self, id: &str) {
+        self.spectators.0.remove(id);
+    }
+
+    // Tells spectators the game is over and stops sending them updates
+    pub fn dismiss_spectators(&mut self) {
+        for (_, spectator) in self.spectators.0.drain() {
+            let _ = util::retry::<(), _, _>(1, || spectator.send("leave"));
+        }
+    }
+
+    fn spectator_state(&self) -> messages::SpectatorState {
+        messages::SpectatorState {
+            board: self.game_state.board().clone(),
+            special: [
+                self.game_state.player(PlayerNum::P1).special,
+                self.game_state.player(PlayerNum::P2).special,
+            ],
+            turns_left: self.game_state.turns_left(),
+            revealed: self.revealed.clone(),
+        }
+    }
+
+    fn broadcast_to_spectators(&self) {
+        if self.spectators.0.is_empty() {
+            return;
+        }
+        let state = self.spectator_state();
+        for spectator in self.spectators.0.values() {
+            send_message(spectator.as_ref(), &state);
+        }
+    }
+
+    pub fn state_name(&self) -> &'static str {
+        self.protocol_state.name()
+    }
+
     pub fn is_over(&self) -> bool {
         matches!(self.protocol_state, ProtocolState::End)
     }
 
+    pub fn details(&self, id: String) -> GameDetails {
+        GameDetails {
+            id,
+            player_ids: self.player_ids.clone(),
+            protocol_state: (&self.protocol_state).into(),
+            // Stays on the last turn once it has been played, while waiting for rematches
+            turn: (self.turns - self.game_state.turns_left() + 1).min(self.turns),
+            turns_left: self.game_state.turns_left(),
+            board: self.game_state.board().clone(),
+            players: [
+                self.game_state.player(PlayerNum::P1).clone(),
+                self.game_state.player(PlayerNum::P2).clone(),
+            ],
+            spectators: self.spectator_count(),
+        }
+    }
+
+    // Ends the game without waiting for the remaining turns or a rematch choice
+    pub fn force_end(&mut self, outcome: Outcome, p1: &impl SendMsg, p2: &impl SendMsg) {
+        self.send_final_outcome(outcome, PlayerNum::P1, p1, p2);
+        self.protocol_state = ProtocolState::End;
+    }
+
+    // Sends each player whether they won, given the game's outcome
+    fn send_final_outcome(
+        &self,
+        outcome: Outcome,
+        player_num: PlayerNum,
+        client: &impl SendMsg,
+        opponent: &impl SendMsg,
+    ) {
+        match (outcome, player_num) {
+            (Outcome::P1Win, PlayerNum::P1) | (Outcome::P2Win, PlayerNum::P2) => {
+                self.send_outcomes(
+                    client,
+                    messages::Outcome::Win,
+                    opponent,
+                    messages::Outcome::Lose,
+                );
+            }
+            (Outcome::P2Win, PlayerNum::P1) | (Outcome::P1Win, PlayerNum::P2) => {
+                self.send_outcomes(
+                    client,
+                    messages::Outcome::Lose,
+                    opponent,
+                    messages::Outcome::Win,
+                );
+            }
+            (Outcome::Draw, _) => {
+                self.send_outcomes(
+                    client,
+                    messages::Outcome::Draw,
+                    opponent,
+                    messages::Outcome::Draw,
+                );
+            }
+        }
+    }
+
     fn send_outcomes(
         &self,
         client: &impl SendMsg,
@@ -62,18 +316,8 @@ impl<R: DrawRng + Default + Debug> Game<R> {
         send_messages(client, client_msg, opponent, opponent_msg);
     }
 
-    // Given a client's ID, gets the opponent's ID for the game they have joined
-    pub fn opponent_id(&self, id: String) -> String {
-        if id == self.player_ids[0] {
-            self.player_ids[1].clone()
-        } else if id == self.player_ids[1] {
-            self.player_ids[0].clone()
-        } else {
-            panic!(
-                "Client with ID {} did not match any of the game's client IDs {:?}",
-                id, self.player_ids
-            );
-        }
+    pub fn player_ids(&self) -> &[String; 2] {
+        &self.player_ids
     }
 
     pub fn handle_message(
@@ -84,7 +328,7 @@ impl<R: DrawRng + Default + Debug> Game<R> {
         opponent: &impl SendMsg,
     ) {
         use ProtocolState::*;
-        self.protocol_state = match self.protocol_state.clone() {
+        let state = match self.protocol_state.clone() {
             Redraw(choices) => {
                 let choice: bool = match from_str(msg) {
                     Ok(choice) => choice,
@@ -107,6 +351,9 @@ impl<R: DrawRng + Default + Debug> Game<R> {
                     Ok(state) => state,
                     Err(err) => {
                         warn!("Invalid game input: {}", err);
+                        metrics::INVALID_INPUTS
+                            .with_label_values(&[metrics::input_error_label(&err)])
+                            .inc();
                         return;
                     }
                 }
@@ -122,7 +369,12 @@ impl<R: DrawRng + Default + Debug> Game<R> {
                 self.process_rematch_choice(client, opponent, choices, player_num, input)
             }
             End => End,
+        };
+        // A turn starts once both players can submit their inputs
+        if matches!(state, InGame([None, None])) {
+            self.turn_started = Instant::now();
         }
+        self.protocol_state = state;
     }
 
     fn process_redraw_choice(
@@ -170,76 +422,50 @@ impl<R: DrawRng + Default + Debug> Game<R> {
         &mut self,
         client: &impl SendMsg,
         opponent: &impl SendMsg,
-        inputs: [Option<ValidInput>; 2],
+        inputs: [Option<TurnInput>; 2],
         player_num: PlayerNum,
         input: RawInput,
     ) -> Result<ProtocolState, InputError> {
-        let validated_input = ValidInput::new(
-            input,
-            self.game_state.board(),
-            self.game_state.player(player_num),
-        )?;
+        let player = self.game_state.player(player_num);
+        info!("{:?} played {}", player_num, format_input(&input, player));
+        let action = input.action.clone();
+        let validated_input = TurnInput {
+            input: ValidInput::new(
+                input,
+                self.game_state.board(),
+                self.game_state.player(player_num),
+            )?,
+            action,
+        };
         let choices = match player_num {
             PlayerNum::P1 => [Some(validated_input), inputs[1].clone()],
             PlayerNum::P2 => [inputs[0].clone(), Some(validated_input)],
         };
         let state = match choices {
             [Some(input1), Some(input2)] => {
-                self.game_state.update(input1, input2);
+                metrics::TURN_DURATION.observe(self.turn_started.elapsed().as_secs_f64());
+                // Cards are replaced during the update, so look up the played cards first
+                self.revealed = Some([
+                    self.reveal(PlayerNum::P1, &input1),
+                    self.reveal(PlayerNum::P2, &input2),
+                ]);
+                self.game_state.update(input1.input, input2.input);
+                self.broadcast_to_spectators();
                 if self.game_state.turns_left() == 0 {
                     let winner = self.game_state.check_winner();
-                    match (winner, player_num) {
-                        (Outcome::P1Win, PlayerNum::P1) => {
-                            self.send_outcomes(
-                                client,
-                                messages::Outcome::Win,
-                                opponent,
-                                messages::Outcome::Lose,
-                            );
-                        }
-                        (Outcome::P2Win, PlayerNum::P1) => {
-                            self.send_outcomes(
-                                client,
-                                messages::Outcome::Lose,
-                                opponent,
-                                messages::Outcome::Win,
-                            );
-                        }
-                        (Outcome::P1Win, PlayerNum::P2) => {
-                            self.send_outcomes(
-                                client,
-                                messages::Outcome::Lose,
-                                opponent,
-                                messages::Outcome::Win,
-                            );
-                        }
-                        (Outcome::P2Win, PlayerNum::P2) => {
-                            self.send_outcomes(
-                                client,
-                                messages::Outcome::Win,
-                                opponent,
-                                messages::Outcome::Lose,
-                            );
-                        }
-                        (Outcome::Draw, _) => {
-                            self.send_outcomes(
-                                client,
-                                messages::Outcome::Draw,
-                                opponent,
-                                messages::Outcome::Draw,
-                            );
-                        }
-                    }
+                    self.send_final_outcome(winner, player_num, client, opponent);
                     //info!("Game end state: {:?}", self.game_state.board());
                     ProtocolState::Rematch([None, None])
                 } else {
                     let client_msg = messages::GameState {
                         board: self.game_state.board().clone(),
                         player: self.game_state.player(player_num).clone(),
+                        turns_left: self.game_state.turns_left(),
                     };
                     let opponent_msg = messages::GameState {
                         board: self.game_state.board().clone(),
                         player: self.game_state.player(other_player(player_num)).clone(),
+                        turns_left: self.game_state.turns_left(),
                     };
                     send_messages(client, client_msg, opponent, opponent_msg);
                     //info!("Game state after update: {:?}", self.game_state.board());
@@ -248,10 +474,21 @@ impl<R: DrawRng + Default + Debug> Game<R> {
             }
             _ => ProtocolState::InGame(choices),
         };
-        info!("Protocol state: {:?}", state);
+        info!("Protocol state: {:?}", ProtocolStateInfo::from(&state));
         Ok(state)
     }
 
+    fn reveal(&self, player_num: PlayerNum, input: &TurnInput) -> messages::RevealedCard {
+        messages::RevealedCard {
+            card: self
+                .game_state
+                .player(player_num)
+                .get_card(input.input.hand_idx())
+                .clone(),
+            action: input.action.clone(),
+        }
+    }
+
     fn process_rematch_choice(
         &mut self,
         client: &impl SendMsg,
@@ -266,25 +503,133 @@ impl<R: DrawRng + Default + Debug> Game<R> {
         };
         match choices {
             [Some(true), Some(true)] => {
-                self.game_state = GameState::default();
+                self.game_state.restart(self.turns);
+                self.revealed = None;
+                self.broadcast_to_spectators();
                 let client_msg = messages::GameState {
                     board: self.game_state.board().clone(),
                     player: self.game_state.player(player_num).clone(),
+                    turns_left: self.game_state.turns_left(),
                 };
                 let opponent_msg = messages::GameState {
                     board: self.game_state.board().clone(),
                     player: self.game_state.player(other_player(player_num)).clone(),
+                    turns_left: self.game_state.turns_left(),
                 };
                 send_messages(client, client_msg, opponent, opponent_msg);
                 ProtocolState::Redraw([None, None])
             }
-            // Let ws module handle removing the game
+            // Let the game's task handle removing the game
             [_, Some(false)] | [Some(false), _] => ProtocolState::End,
             _ => ProtocolState::Rematch(choices),
         }
     }
 }
 
+// Spawns a task that owns the game and processes its players' messages in order.
+// The returned handle must be added to the games map so messages can be routed to it.
+pub fn spawn(
+    uuid: String,
+    game: Game<DeckRng>,
+    senders: [Peer; 2],
+    clients: Clients,
+    games: Games,
+) -> GameHandle {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::task::spawn(run(uuid, game, senders, receiver, clients, games));
+    GameHandle(sender)
+}
+
+async fn run<R: DrawRng + Default + Debug>(
+    uuid: String,
+    mut game: Game<R>,
+    senders: [Peer; 2],
+    mut receiver: mpsc::UnboundedReceiver<GameEvent>,
+    clients: Clients,
+    games: Games,
+) {
+    let [p1_sender, p2_sender] = &senders;
+    let mut state = game.state_name();
+    metrics::GAMES.with_label_values(&[state]).inc();
+    while let Some(event) = receiver.recv().await {
+        match event {
+            GameEvent::Message { player_num, msg } => {
+                info!("Handling message from player");
+                match player_num {
+                    PlayerNum::P1 => game.handle_message(player_num, &msg, p1_sender, p2_sender),
+                    PlayerNum::P2 => game.handle_message(player_num, &msg, p2_sender, p1_sender),
+                }
+                if game.state_name() != state {
+                    metrics::GAMES.with_label_values(&[state]).dec();
+                    state = game.state_name();
+                    metrics::GAMES.with_label_values(&[state]).inc();
+                }
+                if game.is_over() {
+                    break;
+                }
+            }
+            GameEvent::Leave(player_num) => {
+                info!("player {:?} left game {}", player_num, uuid);
+                break;
+            }
+            GameEvent::Spectate { id, sender } => {
+                info!("{} spectating game {}", id, uuid);
+                game.add_spectator(id, Box::new(sender));
+            }
+            GameEvent::StopSpectating(id) => {
+                info!("{} stopped spectating game {}", id, uuid);
+                game.remove_spectator(&id);
+            }
+            GameEvent::Summary(reply) => {
+                let _ = reply.send(GameSummary {
+                    id: uuid.clone(),
+                    turns_left: game.turns_left(),
+                    spectators: game.spectator_count(),
+                });
+            }
+            GameEvent::Details(reply) => {
+                let _ = reply.send(game.details(uuid.clone()));
+            }
+            GameEvent::ForceEnd(outcome) => {
+                info!("game {} force ended with outcome {:?}", uuid, outcome);
+                game.force_end(outcome, p1_sender, p2_sender);
+                break;
+            }
+        }
+    }
+    metrics::GAMES.with_label_values(&[state]).dec();
+    game.dismiss_spectators();
+    end_game(&uuid, game.player_ids(), &senders, &clients, &games).await;
+}
+
+async fn end_game(
+    uuid: &str,
+    player_ids: &[String; 2],
+    senders: &[Peer; 2],
+    clients: &Clients,
+    games: &Games,
+) {
+    let mut clients_map = clients.write().await;
+    for (id, sender) in player_ids.iter().zip(senders) {
+        // Players that already disconnected or were moved on are left alone
+        match clients_map.get_mut(id) {
+            Some(client) if matches!(&client.status, Status::InGame { uuid: u, .. } if u == uuid) =>
+            {
+                info!("client {} changing state to Idle", id);
+                client.status = Status::Idle;
+                // If the message fails to send even after retries, there's not much we can do but proceed
+                let _ = util::retry::<(), _, _>(1, || sender.send("leave"));
+            }
+            _ => (),
+        }
+    }
+    drop(clients_map);
+    if games.write().await.remove(uuid).is_none() {
+        error!("Game with ID {} was already removed", uuid);
+    }
+    info!("game {} ended", uuid);
+}
+
 fn send_redraw_responses<R: DrawRng + Debug>(
     game_state: &mut GameState<R>,
     player_num: PlayerNum,
@@ -294,10 +639,12 @@ fn send_redraw_responses<R: DrawRng + Debug>(
     let client_msg = messages::GameState {
         board: game_state.board().clone(),
         player: game_state.player(player_num).clone(),
+        turns_left: game_state.turns_left(),
     };
     let opponent_msg = messages::GameState {
         board: game_state.board().clone(),
         player: game_state.player(other_player(player_num)).clone(),
+        turns_left: game_state.turns_left(),
     };
     send_messages(client, client_msg, opponent, opponent_msg);
 }
@@ -309,7 +656,7 @@ fn other_player(player_num: PlayerNum) -> PlayerNum {
     }
 }
 
-fn send_message<M: Serialize>(client: &impl SendMsg, message: M) {
+fn send_message<M: Serialize>(client: &(impl SendMsg + ?Sized), message: M) {
     // If the message fails to send even after retries, there's not much we can do but proceed
     let _ = util::retry(1, || client.send(&serde_json::to_string(&message).unwrap()));
 }
@@ -597,4 +944,204 @@ mod tests {
             ProtocolState::Redraw([None, None])
         ));
     }
+
+    #[derive(Clone, Default)]
+    struct RecordingSender(Arc<std::sync::Mutex<Vec<String>>>);
+    impl SendMsg for RecordingSender {
+        fn send(&self, msg: &str) -> Result<(), SendError> {
+            self.0.lock().unwrap().push(msg.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_spectator_updates() {
+        let game_state = GameState::<MockRng>::default();
+        let mut game = Game::new(game_state, ["id1".to_string(), "id2".to_string()]);
+        let spectator = RecordingSender::default();
+        game.add_spectator("spectator".to_string(), Box::new(spectator.clone()));
+        game.handle_message(PlayerNum::P1, "true", &MockSender, &MockSender);
+        game.handle_message(PlayerNum::P2, "false", &MockSender, &MockSender);
+        let pass = "{\"hand_idx\":\"H1\",\"action\":\"Pass\"}";
+        game.handle_message(PlayerNum::P1, pass, &MockSender, &MockSender);
+        game.handle_message(PlayerNum::P2, pass, &MockSender, &MockSender);
+
+        let msgs = spectator.0.lock().unwrap().clone();
+        assert_eq!(msgs.len(), 2);
+        let initial: messages::SpectatorState = serde_json::from_str(&msgs[0]).unwrap();
+        assert_eq!(initial.turns_left, 12);
+        assert!(initial.revealed.is_none());
+        let update: messages::SpectatorState = serde_json::from_str(&msgs[1]).unwrap();
+        assert_eq!(update.turns_left, 11);
+        assert_eq!(update.special, [1, 1]);
+        let revealed = update.revealed.unwrap();
+        assert_eq!(revealed[0].card.name(), "Splattershot");
+        assert!(matches!(revealed[1].action, Action::Pass));
+
+        game.remove_spectator("spectator");
+        game.handle_message(PlayerNum::P1, pass, &MockSender, &MockSender);
+        game.handle_message(PlayerNum::P2, pass, &MockSender, &MockSender);
+        assert_eq!(spectator.0.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_force_end() {
+        let game_state = GameState::<MockRng>::default();
+        let mut game = Game::new(game_state, ["id1".to_string(), "id2".to_string()]);
+        game.handle_message(PlayerNum::P1, "true", &MockSender, &MockSender);
+        let p1 = RecordingSender::default();
+        let p2 = RecordingSender::default();
+        game.force_end(Outcome::P2Win, &p1, &p2);
+        assert!(game.is_over());
+        assert_eq!(
+            game.details("game".to_string()).protocol_state,
+            ProtocolStateInfo::End
+        );
+        let p1_msg: messages::GameEnd = serde_json::from_str(&p1.0.lock().unwrap()[0]).unwrap();
+        assert!(matches!(p1_msg.outcome, messages::Outcome::Lose));
+        let p2_msg: messages::GameEnd = serde_json::from_str(&p2.0.lock().unwrap()[0]).unwrap();
+        assert!(matches!(p2_msg.outcome, messages::Outcome::Win));
+    }
+
+    #[test]
+    fn test_details() {
+        let game_state = GameState::<MockRng>::default();
+        let mut game = Game::new(game_state, ["id1".to_string(), "id2".to_string()]);
+        game.handle_message(PlayerNum::P2, "true", &MockSender, &MockSender);
+        let details = game.details("game".to_string());
+        assert_eq!(
+            details.protocol_state,
+            ProtocolStateInfo::Redraw {
+                chosen: [false, true]
+            }
+        );
+        assert_eq!(details.turn, 1);
+        assert_eq!(details.turns_left, 12);
+
+        // After the last turn, the game is still on it rather than a turn that doesn't exist
+        let mut game = Game::new(
+            GameState::<MockRng>::with_turns(1),
+            ["id1".to_string(), "id2".to_string()],
+        );
+        let pass = "{\"hand_idx\":\"H1\",\"action\":\"Pass\"}";
+        for msg in ["false", pass] {
+            game.handle_message(PlayerNum::P1, msg, &MockSender, &MockSender);
+            game.handle_message(PlayerNum::P2, msg, &MockSender, &MockSender);
+        }
+        let details = game.details("game".to_string());
+        assert!(matches!(
+            details.protocol_state,
+            ProtocolStateInfo::Rematch { .. }
+        ));
+        assert_eq!(details.turn, 1);
+        assert_eq!(details.turns_left, 0);
+    }
+
+    type Receiver = mpsc::UnboundedReceiver<Result<warp::ws::Message, warp::Error>>;
+
+    fn game_clients(uuid: &str) -> (Clients, [Peer; 2], [Receiver; 2]) {
+        let (sender1, receiver1) = mpsc::unbounded_channel();
+        let (sender2, receiver2) = mpsc::unbounded_channel();
+        let senders = [Sender(sender1), Sender(sender2)];
+        let mut clients = HashMap::new();
+        for (id, (player_num, sender)) in ["id1", "id2"].iter().zip(
+            [PlayerNum::P1, PlayerNum::P2]
+                .into_iter()
+                .zip(senders.clone()),
+        ) {
+            clients.insert(
+                id.to_string(),
+                crate::client::Client {
+                    user_id: 0,
+                    status: Status::InGame {
+                        uuid: uuid.to_string(),
+                        player_num,
+                    },
+                    sender: Some(sender),
+                },
+            );
+        }
+        (
+            Arc::new(RwLock::new(clients)),
+            senders.map(|sender| Box::new(sender) as Peer),
+            [receiver1, receiver2],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_game_task_handles_messages() {
+        let (clients, senders, [mut receiver1, mut receiver2]) = game_clients("game");
+        let games: Games = Arc::new(RwLock::new(HashMap::new()));
+        let game = Game::new(
+            GameState::<MockRng>::default(),
+            ["id1".to_string(), "id2".to_string()],
+        );
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(
+            "game".to_string(),
+            game,
+            senders,
+            receiver,
+            clients.clone(),
+            games.clone(),
+        ));
+        games
+            .write()
+            .await
+            .insert("game".to_string(), GameHandle(sender.clone()));
+
+        for player_num in [PlayerNum::P1, PlayerNum::P2] {
+            sender
+                .send(GameEvent::Message {
+                    player_num,
+                    msg: "false".to_string(),
+                })
+                .unwrap();
+        }
+        let msg = receiver1.recv().await.unwrap().unwrap();
+        let game_state: messages::GameState = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+        assert_eq!(game_state.player.player_num(), PlayerNum::P1);
+        let msg = receiver2.recv().await.unwrap().unwrap();
+        let game_state: messages::GameState = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+        assert_eq!(game_state.player.player_num(), PlayerNum::P2);
+        assert!(games.read().await.contains_key("game"));
+
+        sender.send(GameEvent::Leave(PlayerNum::P1)).unwrap();
+        task.await.unwrap();
+        assert!(games.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_game_task_ends_when_player_leaves() {
+        let (clients, senders, [_receiver1, mut receiver2]) = game_clients("game");
+        let games: Games = Arc::new(RwLock::new(HashMap::new()));
+        let game = Game::new(
+            GameState::<MockRng>::default(),
+            ["id1".to_string(), "id2".to_string()],
+        );
+        let (sender, receiver) = mpsc::unbounded_channel();
+        games
+            .write()
+            .await
+            .insert("game".to_string(), GameHandle(sender.clone()));
+        clients.write().await.remove("id1");
+        sender.send(GameEvent::Leave(PlayerNum::P1)).unwrap();
+        run(
+            "game".to_string(),
+            game,
+            senders,
+            receiver,
+            clients.clone(),
+            games.clone(),
+        )
+        .await;
+
+        let msg = receiver2.recv().await.unwrap().unwrap();
+        assert_eq!(msg.to_str().unwrap(), "leave");
+        assert!(matches!(
+            clients.read().await.get("id2").unwrap().status,
+            Status::Idle
+        ));
+        assert!(games.read().await.is_empty());
+    }
 }
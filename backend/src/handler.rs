@@ -1,8 +1,13 @@
 use crate::{
     client::{Client, Clients, Status},
+    config::Config,
+    game::{GameEvent, QueryError},
     ws, Games,
 };
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::warn;
 use uuid::Uuid;
 use warp::{http::StatusCode, reply::json, Rejection, Reply};
 
@@ -18,13 +23,17 @@ pub struct RegisterResponse {
     url: String,
 }
 
-pub async fn register_handler(body: RegisterRequest, clients: Clients) -> Result<impl Reply> {
+pub async fn register_handler(
+    body: RegisterRequest,
+    clients: Clients,
+    config: Arc<Config>,
+) -> Result<impl Reply> {
     let user_id = body.user_id;
     let uuid = Uuid::new_v4().as_simple().to_string();
 
     register_client(uuid.clone(), user_id, clients).await;
     Ok(json(&RegisterResponse {
-        url: format!("ws://127.0.0.1:8000/ws/{}", uuid),
+        url: config.websocket_url(&uuid),
     }))
 }
 
@@ -49,16 +58,55 @@ pub async fn ws_handler(
     id: String,
     clients: Clients,
     games: Games,
+    config: Arc<Config>,
 ) -> Result<impl Reply> {
     let client = clients.read().await.get(&id).cloned();
     match client {
-        Some(c) => {
-            Ok(ws.on_upgrade(move |socket| ws::client_connection(socket, id, clients, c, games)))
-        }
+        Some(c) => Ok(ws.on_upgrade(move |socket| {
+            ws::client_connection(socket, id, clients, c, games, config)
+        })),
+        None => Err(warp::reject::not_found()),
+    }
+}
+
+pub async fn spectate_handler(
+    ws: warp::ws::Ws,
+    game_id: String,
+    games: Games,
+) -> Result<impl Reply> {
+    let game = games.read().await.get(&game_id).cloned();
+    match game {
+        Some(g) => Ok(ws.on_upgrade(move |socket| ws::spectator_connection(socket, game_id, g))),
         None => Err(warp::reject::not_found()),
     }
 }
 
+pub async fn list_games_handler(games: Games) -> Result<impl Reply> {
+    let handles: Vec<_> = games
+        .read()
+        .await
+        .iter()
+        .map(|(id, handle)| (id.clone(), handle.clone()))
+        .collect();
+    // Ask every game's task for its summary at once, without holding the games lock
+    let replies = join_all(
+        handles
+            .iter()
+            .map(|(_, handle)| handle.query(GameEvent::Summary)),
+    )
+    .await;
+    let mut summaries = vec![];
+    for ((id, _), reply) in handles.into_iter().zip(replies) {
+        match reply {
+            Ok(summary) => summaries.push(summary),
+            // Games that end before replying are left out
+            Err(QueryError::Ended) => (),
+            Err(QueryError::Unresponsive) => warn!("game {} didn't answer a summary query", id),
+        }
+    }
+    Ok(json(&summaries))
+}
+
 pub async fn health_handler() -> Result<impl Reply> {
     Ok(StatusCode::OK)
 }
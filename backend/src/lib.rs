@@ -0,0 +1,178 @@
+use crate::client::Clients;
+use crate::config::Config;
+use crate::game::Games;
+use crate::puzzle::Puzzles;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::{
+    http::{header, Method},
+    Filter, Rejection, Reply,
+};
+
+mod admin;
+mod ai;
+pub mod client;
+pub mod config;
+pub mod game;
+mod handler;
+mod metrics;
+pub mod puzzle;
+mod util;
+mod ws;
+
+// Builds every route the server handles. Clients and games are shared between the routes
+// so that the same server state can be inspected from outside, e.g. by tests.
+pub fn routes(
+    config: Arc<Config>,
+    clients: Clients,
+    games: Games,
+    puzzles: Puzzles,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    metrics::register();
+
+    let health_route = warp::path!("health").and_then(handler::health_handler);
+
+    let metrics_route = warp::path!("metrics")
+        .and(warp::get())
+        .and(with_clients(clients.clone()))
+        .and_then(metrics::metrics_handler);
+
+    let register = warp::path("register");
+    let register_routes = register
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_clients(clients.clone()))
+        .and(with_config(config.clone()))
+        .and_then(handler::register_handler)
+        .or(register
+            .and(warp::delete())
+            .and(warp::path::param())
+            .and(with_clients(clients.clone()))
+            .and_then(handler::unregister_handler));
+
+    let ws_route = warp::path("ws")
+        .and(warp::ws())
+        .and(warp::path::param())
+        .and(with_clients(clients.clone()))
+        .and(with_games(games.clone()))
+        .and(with_config(config.clone()))
+        .and_then(handler::ws_handler);
+
+    let cors = warp::cors()
+        .allow_credentials(true)
+        .allow_methods(&[
+            Method::OPTIONS,
+            Method::GET,
+            Method::POST,
+            Method::DELETE,
+            Method::PUT,
+        ])
+        .allow_headers(vec![
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        ])
+        .expose_headers(vec![header::LINK])
+        .max_age(300);
+    let cors = if config.cors_allowed_origins.is_empty() {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(config.cors_allowed_origins.iter().map(String::as_str))
+    };
+
+    let spectate_route = warp::path("spectate")
+        .and(warp::ws())
+        .and(warp::path::param())
+        .and(with_games(games.clone()))
+        .and_then(handler::spectate_handler);
+
+    let games_route = warp::path!("games")
+        .and(warp::get())
+        .and(with_games(games.clone()))
+        .and_then(handler::list_games_handler);
+
+    let puzzle_routes = warp::path!("puzzles")
+        .and(warp::get())
+        .and(with_puzzles(puzzles.clone()))
+        .and_then(puzzle::list_puzzles_handler)
+        .or(warp::path!("puzzles" / String)
+            .and(warp::get())
+            .and(with_puzzles(puzzles.clone()))
+            .and_then(puzzle::puzzle_handler))
+        .or(warp::path!("puzzles" / String / "check")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_puzzles(puzzles))
+            .and_then(puzzle::check_handler));
+
+    let admin_routes = warp::path!("clients")
+        .and(warp::get())
+        .and(with_clients(clients.clone()))
+        .and_then(admin::list_clients_handler)
+        .or(warp::path!("clients" / String)
+            .and(warp::delete())
+            .and(with_clients(clients.clone()))
+            .and(with_games(games.clone()))
+            .and_then(admin::kick_client_handler))
+        .or(warp::path!("games")
+            .and(warp::get())
+            .and(with_games(games.clone()))
+            .and_then(admin::list_games_handler))
+        .or(warp::path!("games" / String)
+            .and(warp::get())
+            .and(with_games(games.clone()))
+            .and_then(admin::game_handler))
+        .or(warp::path!("games" / String / "end")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_games(games.clone()))
+            .and_then(admin::end_game_handler));
+    let admin_route = warp::path("admin")
+        .and(with_admin(config.clone()))
+        .and(admin_routes)
+        .recover(admin::handle_rejection);
+
+    health_route
+        .or(metrics_route)
+        .or(register_routes)
+        .or(ws_route)
+        .or(spectate_route)
+        .or(games_route)
+        .or(puzzle_routes)
+        .or(admin_route)
+        .with(cors)
+}
+
+// Starts the tasks that run alongside the routes, like the AI fallback for matchmaking
+pub fn spawn_background_tasks(config: Arc<Config>, clients: Clients, games: Games) {
+    if let Some(secs) = config.ai_fallback_secs {
+        let wait = Duration::from_secs(secs);
+        tokio::task::spawn(ws::ai_fallback(clients, games, config, wait));
+    }
+}
+
+fn with_clients(clients: Clients) -> impl Filter<Extract = (Clients,), Error = Infallible> + Clone {
+    warp::any().map(move || clients.clone())
+}
+
+fn with_games(games: Games) -> impl Filter<Extract = (Games,), Error = Infallible> + Clone {
+    warp::any().map(move || games.clone())
+}
+
+fn with_puzzles(puzzles: Puzzles) -> impl Filter<Extract = (Puzzles,), Error = Infallible> + Clone {
+    warp::any().map(move || puzzles.clone())
+}
+
+fn with_config(
+    config: Arc<Config>,
+) -> impl Filter<Extract = (Arc<Config>,), Error = Infallible> + Clone {
+    warp::any().map(move || config.clone())
+}
+
+fn with_admin(config: Arc<Config>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(with_config(config))
+        .and_then(admin::check_token)
+        .untuple_one()
+}
@@ -0,0 +1,147 @@
+use crate::client::Clients;
+use common::InputError;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Encoder, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    TextEncoder,
+};
+use tracing::error;
+use warp::{
+    http::{header, Response, StatusCode},
+    Rejection, Reply,
+};
+
+// Wait times range from instant matches to players idling in the queue for minutes
+const WAIT_BUCKETS: &[f64] = &[0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+pub static CONNECTED_CLIENTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "tableturf_connected_clients",
+        "Number of clients with an open websocket connection"
+    )
+    .unwrap()
+});
+
+pub static GAMES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "tableturf_games",
+        "Number of running games in each protocol state",
+        &["state"]
+    )
+    .unwrap()
+});
+
+pub static MATCHMAKING_WAIT: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "tableturf_matchmaking_wait_seconds",
+        "Time a client spends waiting for an opponent",
+        WAIT_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+pub static TURN_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "tableturf_turn_duration_seconds",
+        "Time between the start of a turn and both players submitting their input",
+        WAIT_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+pub static INVALID_INPUTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tableturf_invalid_inputs_total",
+        "Number of game inputs rejected by validation",
+        &["error"]
+    )
+    .unwrap()
+});
+
+pub static SEND_FAILURES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "tableturf_send_failures_total",
+        "Number of messages that failed to send after all retries"
+    )
+    .unwrap()
+});
+
+pub static WEBSOCKET_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tableturf_websocket_errors_total",
+        "Number of websocket errors",
+        &["direction"]
+    )
+    .unwrap()
+});
+
+// Registers every metric up front so that all of them are reported from the first scrape
+pub fn register() {
+    Lazy::force(&CONNECTED_CLIENTS);
+    Lazy::force(&GAMES);
+    Lazy::force(&MATCHMAKING_WAIT);
+    Lazy::force(&TURN_DURATION);
+    Lazy::force(&INVALID_INPUTS);
+    Lazy::force(&SEND_FAILURES);
+    Lazy::force(&WEBSOCKET_ERRORS);
+}
+
+pub fn input_error_label(err: &InputError) -> &'static str {
+    match err {
+        InputError::InsufficientSpecial { .. } => "insufficient_special",
+        InputError::InvalidPosition(_) => "invalid_position",
+        InputError::SpecialCollision => "special_collision",
+        InputError::SpecialNotAdjacentToSpecialSquare => "special_not_adjacent_to_special_square",
+        InputError::InkCollision(..) => "ink_collision",
+        InputError::InkNotAdjacentToInk => "ink_not_adjacent_to_ink",
+    }
+}
+
+pub async fn metrics_handler(clients: Clients) -> Result<impl Reply, Rejection> {
+    // Count connections when scraped so the gauge can't drift from the clients map
+    let connected = clients
+        .read()
+        .await
+        .values()
+        .filter(|client| client.sender.is_some())
+        .count();
+    CONNECTED_CLIENTS.set(connected as i64);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(err) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("failed to encode metrics: {}", err);
+        return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, encoder.format_type())
+        .body(buffer)
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashbrown::HashMap;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_metrics_handler() {
+        register();
+        INVALID_INPUTS
+            .with_label_values(&[input_error_label(&InputError::SpecialCollision)])
+            .inc();
+        let clients: Clients = Arc::new(RwLock::new(HashMap::new()));
+        let response = metrics_handler(clients).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("tableturf_connected_clients 0"));
+        assert!(body.contains("tableturf_matchmaking_wait_seconds_bucket"));
+        assert!(body.contains(r#"tableturf_invalid_inputs_total{error="special_collision"}"#));
+    }
+}
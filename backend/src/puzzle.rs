@@ -0,0 +1,114 @@
+use common::puzzle::{Goal, Puzzle, PuzzleError};
+use common::RawInput;
+use hashbrown::HashMap;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+use warp::{http::StatusCode, reply::json, Rejection, Reply};
+
+type Result<T> = std::result::Result<T, Rejection>;
+
+// Puzzles by ID, which is the name of the file they were loaded from without the extension.
+// Puzzles are only read at startup, so they're shared without a lock.
+pub type Puzzles = Arc<HashMap<String, Puzzle>>;
+
+#[derive(Error, Debug)]
+pub enum PuzzleLoadError {
+    #[error("Failed to read puzzle {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Failed to parse puzzle {0}: {1}")]
+    Parse(PathBuf, serde_json::Error),
+    #[error("Puzzle {0} can't be played: {1}")]
+    Invalid(PathBuf, PuzzleError),
+}
+
+#[derive(Serialize, Debug)]
+pub struct PuzzleSummary {
+    id: String,
+    name: String,
+    description: String,
+    turns_left: u32,
+    goal: Goal,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CheckError {
+    error: String,
+}
+
+// Loads every .json file in the directory as a puzzle. There are no puzzles without a directory.
+pub fn load(dir: Option<&Path>) -> std::result::Result<Puzzles, PuzzleLoadError> {
+    let mut puzzles = HashMap::new();
+    let Some(dir) = dir else {
+        return Ok(Arc::new(puzzles));
+    };
+    let entries = std::fs::read_dir(dir).map_err(|err| PuzzleLoadError::Read(dir.into(), err))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|err| PuzzleLoadError::Read(dir.into(), err))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => return Err(PuzzleLoadError::Read(path, err)),
+        };
+        let puzzle: Puzzle = match serde_json::from_str(&contents) {
+            Ok(puzzle) => puzzle,
+            Err(err) => return Err(PuzzleLoadError::Parse(path, err)),
+        };
+        if let Err(err) = puzzle.game_state() {
+            return Err(PuzzleLoadError::Invalid(path, err));
+        }
+        let id = path.file_stem().unwrap().to_string_lossy().into_owned();
+        puzzles.insert(id, puzzle);
+    }
+    info!("loaded {} puzzles from {:?}", puzzles.len(), dir);
+    Ok(Arc::new(puzzles))
+}
+
+pub async fn list_puzzles_handler(puzzles: Puzzles) -> Result<impl Reply> {
+    let mut summaries: Vec<PuzzleSummary> = puzzles
+        .iter()
+        .map(|(id, puzzle)| PuzzleSummary {
+            id: id.clone(),
+            name: puzzle.name.clone(),
+            description: puzzle.description.clone(),
+            turns_left: puzzle.turns_left,
+            goal: puzzle.goal,
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(json(&summaries))
+}
+
+// The whole puzzle, including the opponent's script, so that it can be played offline
+pub async fn puzzle_handler(id: String, puzzles: Puzzles) -> Result<impl Reply> {
+    match puzzles.get(&id) {
+        Some(puzzle) => Ok(json(puzzle)),
+        None => Err(warp::reject::not_found()),
+    }
+}
+
+// Checks a line of play against the puzzle. Illegal moves are a bad request.
+pub async fn check_handler(
+    id: String,
+    line: Vec<RawInput>,
+    puzzles: Puzzles,
+) -> Result<impl Reply> {
+    let Some(puzzle) = puzzles.get(&id) else {
+        return Err(warp::reject::not_found());
+    };
+    Ok(match puzzle.check(&line) {
+        Ok(verdict) => warp::reply::with_status(json(&verdict), StatusCode::OK),
+        Err(err) => warp::reply::with_status(
+            json(&CheckError {
+                error: err.to_string(),
+            }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
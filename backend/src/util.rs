@@ -1,3 +1,4 @@
+use crate::metrics;
 use thiserror::Error;
 use tracing::warn;
 
@@ -14,6 +15,7 @@ where
     while let Err(err) = result {
         if times == 0 {
             warn!("No more retry attempts. Error: {}", err);
+            metrics::SEND_FAILURES.inc();
             return Err(RetryFailed);
         }
         warn!("Retry triggered. Error: {}", err);
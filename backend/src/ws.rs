@@ -1,21 +1,30 @@
-use crate::client::{Client, Clients, SendMsg, Sender, Status};
-use crate::game::{Game, Games};
-use crate::util;
-use common::{messages, GameState, PlayerNum};
+use crate::ai::{self, Difficulty, UnknownDifficulty};
+use crate::client::{Client, Clients, Peer, Sender, Status};
+use crate::config::Config;
+use crate::game::{self, Game, GameEvent, GameHandle, Games};
+use crate::{metrics, util};
+use common::{messages, DeckRng, GameState, PlayerNum};
 use futures::{FutureExt, StreamExt};
-use hashbrown::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{error, info};
 use uuid::Uuid;
 use warp::ws::{Message, WebSocket};
 
+// How often to check for clients that have waited too long for an opponent
+const AI_FALLBACK_INTERVAL: Duration = Duration::from_secs(1);
+
 pub async fn client_connection(
     ws: WebSocket,
     id: String,
     clients: Clients,
     mut client: Client,
-    mut games: Games,
+    games: Games,
+    config: Arc<Config>,
 ) {
     let (client_ws_sender, mut client_ws_rcv) = ws.split();
     let (client_sender, client_rcv) = mpsc::unbounded_channel();
@@ -24,6 +33,7 @@ pub async fn client_connection(
     tokio::task::spawn(client_rcv.forward(client_ws_sender).map(|result| {
         if let Err(e) = result {
             error!("error sending websocket msg: {}", e);
+            metrics::WEBSOCKET_ERRORS.with_label_values(&["send"]).inc();
         }
     }));
 
@@ -37,18 +47,69 @@ pub async fn client_connection(
             Ok(msg) => msg,
             Err(e) => {
                 error!("error receiving ws message for id: {}): {}", id.clone(), e);
+                metrics::WEBSOCKET_ERRORS
+                    .with_label_values(&["receive"])
+                    .inc();
                 break;
             }
         };
-        client_msg(&id, msg, &clients, &mut games).await;
+        client_msg(&id, msg, &clients, &games, &config).await;
     }
 
-    clients.write().await.remove(&id);
+    let client = clients.write().await.remove(&id);
+    // Let the game's task end the game, since the opponent can no longer play
+    if let Some(Client {
+        status: Status::InGame { uuid, player_num },
+        ..
+    }) = client
+    {
+        if let Some(game) = games.read().await.get(&uuid) {
+            let _ = game.send(GameEvent::Leave(player_num));
+        }
+    }
     info!("{} disconnected", id);
 }
 
+// Spectators only receive messages, so anything they send is ignored
+pub async fn spectator_connection(ws: WebSocket, game_id: String, game: GameHandle) {
+    let (spectator_ws_sender, mut spectator_ws_rcv) = ws.split();
+    let (spectator_sender, spectator_rcv) = mpsc::unbounded_channel();
+
+    let spectator_rcv = UnboundedReceiverStream::new(spectator_rcv);
+    tokio::task::spawn(spectator_rcv.forward(spectator_ws_sender).map(|result| {
+        if let Err(e) = result {
+            error!("error sending websocket msg: {}", e);
+            metrics::WEBSOCKET_ERRORS.with_label_values(&["send"]).inc();
+        }
+    }));
+
+    let id = Uuid::new_v4().as_simple().to_string();
+    let event = GameEvent::Spectate {
+        id: id.clone(),
+        sender: Sender(spectator_sender),
+    };
+    if game.send(event).is_err() {
+        info!("game {} ended before {} could spectate", game_id, id);
+        return;
+    }
+    info!("{} spectating {}", id, game_id);
+
+    while let Some(result) = spectator_ws_rcv.next().await {
+        if let Err(e) = result {
+            error!("error receiving ws message for spectator {}: {}", id, e);
+            metrics::WEBSOCKET_ERRORS
+                .with_label_values(&["receive"])
+                .inc();
+            break;
+        }
+    }
+
+    let _ = game.send(GameEvent::StopSpectating(id.clone()));
+    info!("spectator {} disconnected", id);
+}
+
 #[tracing::instrument]
-async fn client_msg(id: &str, msg: Message, clients: &Clients, games: &mut Games) {
+async fn client_msg(id: &str, msg: Message, clients: &Clients, games: &Games, config: &Config) {
     info!("received message from {}: {:?}", id, msg);
     let message = match msg.to_str() {
         Ok(v) => v.trim(),
@@ -59,9 +120,9 @@ async fn client_msg(id: &str, msg: Message, clients: &Clients, games: &mut Games
         return;
     }
 
-    let mut clients_map = clients.write().await;
-    let client = match clients_map.get_mut(id) {
-        Some(v) => v,
+    // Only hold the clients lock long enough to look up where the message should go
+    let status = match clients.read().await.get(id) {
+        Some(v) => v.status.clone(),
         None => {
             error!(
                 "Message from client {} did not match any connected clients",
@@ -70,88 +131,106 @@ async fn client_msg(id: &str, msg: Message, clients: &Clients, games: &mut Games
             return;
         }
     };
-    match &client.status {
+    match status {
         Status::InGame { uuid, player_num } => {
-            let uuid = uuid.clone();
-            let player_num = *player_num;
-            let mut games_map = games.write().await;
-            let game = match games_map.get_mut(&uuid) {
-                Some(v) => v,
+            let game = match games.read().await.get(&uuid) {
+                Some(v) => v.clone(),
                 None => {
                     error!("Game with ID {} did not match any existing games", uuid);
                     return;
                 }
             };
-            let opponent_id = &game.opponent_id(id.to_string());
-            let [client, opponent] = clients_map.get_many_mut([id, opponent_id]).unwrap();
-            info!("Handling message from player");
-            game.handle_message(
+            let event = GameEvent::Message {
                 player_num,
-                message,
-                client.sender.as_ref().unwrap(),
-                opponent.sender.as_ref().unwrap(),
-            );
-            if game.is_over() {
-                info!("client {} changing state to Idle", id);
-                client.status = Status::Idle;
-                // If the message fails to send even after retries, there's not much we can do but proceed
-                let _ =
-                    util::retry::<(), _, _>(1, || client.sender.as_ref().unwrap().send("leave"));
-                info!("client {} changing state to Idle", opponent_id);
-                opponent.status = Status::Idle;
-                let _ =
-                    util::retry::<(), _, _>(1, || opponent.sender.as_ref().unwrap().send("leave"));
-                games_map.remove(&uuid);
+                msg: message.to_string(),
+            };
+            if game.send(event).is_err() {
+                error!("Game with ID {} is no longer running", uuid);
             }
         }
-        Status::Idle => {
-            if message == "join" {
+        Status::Idle => match parse_join_request(message) {
+            Some(Ok(JoinRequest::Match(difficulty))) => {
                 info!("client {} joining a game", id);
-                client_join(id, &mut clients_map, games).await;
+                client_join(id, clients, games, config, difficulty).await;
             }
-        }
-        Status::JoiningGame => {}
+            Some(Ok(JoinRequest::Practice(difficulty))) => {
+                info!("client {} starting a practice game", id);
+                let mut clients_map = clients.write().await;
+                if let Some(client) = clients_map.get_mut(id) {
+                    start_ai_game(id, client, difficulty, clients, games, config).await;
+                }
+            }
+            Some(Err(err)) => error!("client {} sent an invalid join request: {}", id, err),
+            None => (),
+        },
+        Status::JoiningGame { .. } => {}
     }
 }
 
-#[tracing::instrument]
-async fn client_join(id: &str, clients: &mut HashMap<String, Client>, games: &mut Games) {
-    let mut waiting_clients = clients
-        .iter_mut()
-        .filter(|(_, c)| matches!(c.status, Status::JoiningGame))
-        .map(|(id, _)| id);
-    if let Some(opponent_id) = waiting_clients.next() {
-        let opponent_id = opponent_id.clone();
-        let [client, opponent] = clients.get_many_mut([id, &opponent_id]).unwrap();
-
-        let game_state = GameState::default();
-        let player1 = game_state.player(PlayerNum::P1).clone();
-        let player2 = game_state.player(PlayerNum::P2).clone();
+// The difficulty picks the AI for practice games, and for the AI that takes the opponent's
+// seat if matchmaking takes too long
+enum JoinRequest {
+    Match(Difficulty),
+    Practice(Difficulty),
+}
 
-        // If we cannot serialize the response to the client, panic because that's a bug
-        let client_response = serde_json::to_string(&messages::GameState {
-            board: game_state.board().clone(),
-            player: player1,
-        })
-        .unwrap();
+#[derive(Error, Debug)]
+enum JoinRequestError {
+    #[error(transparent)]
+    UnknownDifficulty(#[from] UnknownDifficulty),
+    #[error("Unexpected \"{0}\" after the difficulty")]
+    TrailingWords(String),
+}
 
-        let opponent_response = serde_json::to_string(&messages::GameState {
-            board: game_state.board().clone(),
-            player: player2,
-        })
-        .unwrap();
+// Parses messages like "join", "join hard" or "practice easy"
+fn parse_join_request(message: &str) -> Option<Result<JoinRequest, JoinRequestError>> {
+    let mut words = message.split_whitespace();
+    let request: fn(Difficulty) -> JoinRequest = match words.next()? {
+        "join" => JoinRequest::Match,
+        "practice" => JoinRequest::Practice,
+        _ => return None,
+    };
+    let difficulty = match words.next() {
+        Some(difficulty) => difficulty.parse(),
+        None => Ok(Difficulty::default()),
+    };
+    let rest: Vec<_> = words.collect();
+    if !rest.is_empty() {
+        return Some(Err(JoinRequestError::TrailingWords(rest.join(" "))));
+    }
+    Some(difficulty.map(request).map_err(JoinRequestError::from))
+}
 
-        // If the message fails to send even after retries, there's not much we can do but proceed
-        let _ = util::retry(1, || client.sender.as_ref().unwrap().send(&client_response));
-        let _ = util::retry(1, || {
-            opponent.sender.as_ref().unwrap().send(&opponent_response)
-        });
-
-        let game_uuid = Uuid::new_v4().as_simple().to_string();
-        games.write().await.insert(
-            game_uuid.clone(),
-            Game::new(game_state, [id.to_string(), opponent_id.to_string()]),
-        );
+#[tracing::instrument]
+async fn client_join(
+    id: &str,
+    clients: &Clients,
+    games: &Games,
+    config: &Config,
+    difficulty: Difficulty,
+) {
+    let mut clients_map = clients.write().await;
+    let opponent_id = clients_map
+        .iter()
+        .find(|(_, c)| matches!(c.status, Status::JoiningGame { .. }))
+        .map(|(id, _)| id.clone());
+    if let Some(opponent_id) = opponent_id {
+        let [client, opponent] = clients_map.get_many_mut([id, &opponent_id]).unwrap();
+        if let Status::JoiningGame { since, .. } = opponent.status {
+            metrics::MATCHMAKING_WAIT.observe(since.elapsed().as_secs_f64());
+        }
+
+        let client_sender = client.sender.clone().unwrap();
+        let opponent_sender = opponent.sender.clone().unwrap();
+        let game_uuid = start_game(
+            GameState::with_rng(config.turns, config.deck_rng()),
+            [id.to_string(), opponent_id.to_string()],
+            [Box::new(client_sender), Box::new(opponent_sender)],
+            clients,
+            games,
+        )
+        .await
+        .0;
         info!("client {} changing state to InGame", id);
         client.status = Status::InGame {
             uuid: game_uuid.clone(),
@@ -163,12 +242,137 @@ async fn client_join(id: &str, clients: &mut HashMap<String, Client>, games: &mu
             player_num: PlayerNum::P2,
         };
     } else {
-        match clients.get_mut(id) {
+        match clients_map.get_mut(id) {
             Some(c) => {
                 info!("client {} changing state to JoiningGame", id);
-                c.status = Status::JoiningGame;
+                c.status = Status::JoiningGame {
+                    since: Instant::now(),
+                    difficulty,
+                };
             }
             None => error!("Joining client {} not in list of registered clients", id),
         }
     }
 }
+
+// Seats the client as Player 1 in a game against an AI
+async fn start_ai_game(
+    id: &str,
+    client: &mut Client,
+    difficulty: Difficulty,
+    clients: &Clients,
+    games: &Games,
+    config: &Config,
+) {
+    let sender = match client.sender.clone() {
+        Some(sender) => sender,
+        None => {
+            error!("client {} is not connected", id);
+            return;
+        }
+    };
+    let (ai_peer, ai_receiver) = ai::peer();
+    let (game_uuid, handle) = start_game(
+        GameState::with_rng(config.turns, config.deck_rng()),
+        [id.to_string(), format!("ai-{}", difficulty)],
+        [Box::new(sender), Box::new(ai_peer)],
+        clients,
+        games,
+    )
+    .await;
+    ai::spawn(ai_receiver, difficulty, PlayerNum::P2, handle);
+    info!("client {} changing state to InGame", id);
+    client.status = Status::InGame {
+        uuid: game_uuid,
+        player_num: PlayerNum::P1,
+    };
+}
+
+// Sends both players their starting state and spawns the game's task.
+// Returns the new game's ID and handle.
+async fn start_game(
+    game_state: GameState<DeckRng>,
+    player_ids: [String; 2],
+    peers: [Peer; 2],
+    clients: &Clients,
+    games: &Games,
+) -> (String, GameHandle) {
+    for (peer, player_num) in peers.iter().zip([PlayerNum::P1, PlayerNum::P2]) {
+        // If we cannot serialize the response to the client, panic because that's a bug
+        let response = serde_json::to_string(&messages::GameState {
+            board: game_state.board().clone(),
+            player: game_state.player(player_num).clone(),
+            turns_left: game_state.turns_left(),
+        })
+        .unwrap();
+        // If the message fails to send even after retries, there's not much we can do but proceed
+        let _ = util::retry(1, || peer.send(&response));
+    }
+
+    let game_uuid = Uuid::new_v4().as_simple().to_string();
+    let handle = game::spawn(
+        game_uuid.clone(),
+        Game::new(game_state, player_ids),
+        peers,
+        clients.clone(),
+        games.clone(),
+    );
+    games
+        .write()
+        .await
+        .insert(game_uuid.clone(), handle.clone());
+    (game_uuid, handle)
+}
+
+// Moves clients that have waited longer than the given time for an opponent into a game
+// against an AI of the difficulty they joined with
+pub async fn ai_fallback(clients: Clients, games: Games, config: Arc<Config>, wait: Duration) {
+    let mut interval = tokio::time::interval(AI_FALLBACK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let mut clients_map = clients.write().await;
+        for (id, client) in clients_map.iter_mut() {
+            if let Status::JoiningGame { since, difficulty } = client.status {
+                if since.elapsed() >= wait {
+                    metrics::MATCHMAKING_WAIT.observe(since.elapsed().as_secs_f64());
+                    info!(
+                        "no opponent found for {}, starting a game against an AI",
+                        id
+                    );
+                    start_ai_game(id, client, difficulty, &clients, &games, &config).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_join_request() {
+        assert!(matches!(
+            parse_join_request("join"),
+            Some(Ok(JoinRequest::Match(Difficulty::Easy)))
+        ));
+        assert!(matches!(
+            parse_join_request("practice hard"),
+            Some(Ok(JoinRequest::Practice(Difficulty::Hard)))
+        ));
+        assert!(matches!(
+            parse_join_request("join medium"),
+            Some(Err(JoinRequestError::UnknownDifficulty(_)))
+        ));
+        assert!(matches!(
+            parse_join_request("join hard typo"),
+            Some(Err(JoinRequestError::TrailingWords(_)))
+        ));
+        assert!(matches!(
+            parse_join_request("practice easy extra"),
+            Some(Err(JoinRequestError::TrailingWords(_)))
+        ));
+        assert!(parse_join_request("true").is_none());
+        assert!(parse_join_request("").is_none());
+    }
+}
@@ -1 +1,177 @@
-pub fn setup() {}
+use futures::{SinkExt, StreamExt};
+use hashbrown::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tableturf_web::client::{Clients, Status};
+use tableturf_web::config::Config;
+use tableturf_web::game::Games;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+// How long a test waits for the server before failing, so a missing message can't hang a test
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+struct RegisterRequest {
+    user_id: usize,
+}
+
+#[derive(Deserialize)]
+struct RegisterResponse {
+    url: String,
+}
+
+// Short matches with fixed card draws, so tests can assert on the exact messages
+pub fn test_config() -> Config {
+    Config {
+        turns: 3,
+        rng_seed: Some(42),
+        ..Config::default()
+    }
+}
+
+// A server running in-process on an ephemeral loopback port
+pub struct TestServer {
+    pub addr: SocketAddr,
+    pub clients: Clients,
+    pub games: Games,
+    http: reqwest::Client,
+}
+
+impl TestServer {
+    // The bind address and public URL of the config are replaced with the server's address
+    pub async fn start(config: Config) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = Config {
+            bind_address: addr,
+            public_url: format!("ws://{}", addr),
+            ..config
+        };
+        let clients: Clients = Arc::new(RwLock::new(HashMap::new()));
+        let games: Games = Arc::new(RwLock::new(HashMap::new()));
+        let config = Arc::new(config);
+        tableturf_web::spawn_background_tasks(config.clone(), clients.clone(), games.clone());
+        let puzzles = tableturf_web::puzzle::load(config.puzzle_dir.as_deref()).unwrap();
+        let routes = tableturf_web::routes(config, clients.clone(), games.clone(), puzzles);
+        tokio::spawn(warp::serve(routes).run_incoming(TcpListenerStream::new(listener)));
+        TestServer {
+            addr,
+            clients,
+            games,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    // Registers a client and opens its websocket connection
+    pub async fn connect(&self, user_id: usize) -> TestClient {
+        let response: RegisterResponse = self
+            .http
+            .post(self.url("/register"))
+            .json(&RegisterRequest { user_id })
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let id = response.url.rsplit('/').next().unwrap().to_string();
+        let (ws, _) = tokio_tungstenite::connect_async(&response.url)
+            .await
+            .unwrap();
+        // The server only knows about the connection once it has stored the client's sender
+        self.wait_for(|clients| clients.get(&id).is_some_and(|c| c.sender.is_some()))
+            .await;
+        TestClient { id, ws }
+    }
+
+    // Matches two clients, making the first Player 1 and the second Player 2
+    pub async fn join(&self, p1: &mut TestClient, p2: &mut TestClient) {
+        p2.send("join").await;
+        let id = p2.id.clone();
+        self.wait_for(|clients| {
+            matches!(
+                clients.get(&id).map(|c| &c.status),
+                Some(Status::JoiningGame { .. })
+            )
+        })
+        .await;
+        p1.send("join").await;
+    }
+
+    pub async fn wait_for_games_to_end(&self) {
+        tokio::time::timeout(TIMEOUT, async {
+            while !self.games.read().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for games to end");
+    }
+
+    // Polls the server's clients until the condition holds
+    pub async fn wait_for<F>(&self, condition: F)
+    where
+        F: Fn(&HashMap<String, tableturf_web::client::Client>) -> bool,
+    {
+        tokio::time::timeout(TIMEOUT, async {
+            while !condition(&*self.clients.read().await) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the server");
+    }
+}
+
+// A scripted client that talks to the server over a real websocket connection
+pub struct TestClient {
+    pub id: String,
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl TestClient {
+    pub async fn send(&mut self, msg: &str) {
+        self.ws.send(Message::text(msg)).await.unwrap();
+    }
+
+    pub async fn send_json<T: Serialize>(&mut self, msg: &T) {
+        self.send(&serde_json::to_string(msg).unwrap()).await;
+    }
+
+    // Waits for the next text message from the server
+    pub async fn recv(&mut self) -> String {
+        loop {
+            let msg = tokio::time::timeout(TIMEOUT, self.ws.next())
+                .await
+                .expect("timed out waiting for a message")
+                .expect("connection closed")
+                .unwrap();
+            match msg {
+                Message::Text(text) => return text,
+                Message::Close(_) => panic!("connection closed by the server"),
+                _ => continue,
+            }
+        }
+    }
+
+    pub async fn recv_json<T: DeserializeOwned>(&mut self) -> T {
+        let msg = self.recv().await;
+        match serde_json::from_str(&msg) {
+            Ok(msg) => msg,
+            Err(err) => panic!("unexpected message {}: {}", msg, err),
+        }
+    }
+
+    pub async fn close(mut self) {
+        self.ws.close(None).await.unwrap();
+    }
+}
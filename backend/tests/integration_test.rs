@@ -1,15 +1,232 @@
 mod common;
 
-#[test]
-fn test_client_connection() {
-    common::setup();
-
-    /*
-        client_connection(
-            ws: WebSocket,
-            id: String,
-            clients: Clients,
-            client: Client,
-            games: Games)
-    */
+use ::common::ai::legal_inputs;
+use ::common::puzzle::{Puzzle, Verdict};
+use ::common::{
+    messages, Action, Board, BoardSpace, HandIndex, InkSpace, Input, Placement, PlayerNum, RawInput,
+};
+use common::{test_config, TestClient, TestServer};
+use tableturf_web::client::Status;
+use tableturf_web::config::Config;
+use warp::http::StatusCode;
+
+fn pass() -> RawInput {
+    RawInput {
+        hand_idx: HandIndex::H1,
+        action: Action::Pass,
+    }
+}
+
+// The first card the player can place without a special attack
+fn first_placement(state: &messages::GameState) -> (RawInput, Placement) {
+    legal_inputs(&state.board, &state.player)
+        .into_iter()
+        .find_map(|(raw_input, valid_input)| match valid_input.get() {
+            Input::Place(placement) if !placement.is_special_activated() => {
+                Some((raw_input, placement))
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+// Every space of the placement holds the player's ink on the board
+fn assert_inked(board: &Board, placement: &Placement, player_num: PlayerNum) {
+    for (position, ink_space) in &placement.ink_spaces().0 {
+        let space = board.get_space(position.x(), position.y());
+        match ink_space {
+            InkSpace::Normal => assert_eq!(space, BoardSpace::Ink { player_num }),
+            InkSpace::Special => assert!(
+                matches!(space, BoardSpace::Special { player_num: p, .. } if p == player_num)
+            ),
+        }
+    }
+}
+
+// Plays through the redraw, returning the states each player starts the match with
+async fn redraw(
+    p1: &mut TestClient,
+    p2: &mut TestClient,
+) -> (messages::GameState, messages::GameState) {
+    p1.send_json(&false).await;
+    p2.send_json(&false).await;
+    (p1.recv_json().await, p2.recv_json().await)
+}
+
+#[tokio::test]
+async fn test_full_game() {
+    let server = TestServer::start(test_config()).await;
+    let mut p1 = server.connect(1).await;
+    let mut p2 = server.connect(2).await;
+    server.join(&mut p1, &mut p2).await;
+
+    let p1_state: messages::GameState = p1.recv_json().await;
+    let p2_state: messages::GameState = p2.recv_json().await;
+    assert_eq!(p1_state.player.player_num(), PlayerNum::P1);
+    assert_eq!(p2_state.player.player_num(), PlayerNum::P2);
+    assert_eq!(p1_state.turns_left, 3);
+
+    let (p1_state, p2_state) = redraw(&mut p1, &mut p2).await;
+    assert_eq!(p1_state.turns_left, 3);
+    assert_eq!(p2_state.player.special, 0);
+
+    // Player 1 places a card while player 2 passes, which charges their special gauge
+    let (input, placement) = first_placement(&p1_state);
+    p1.send_json(&input).await;
+    p2.send_json(&pass()).await;
+    let p1_state: messages::GameState = p1.recv_json().await;
+    let p2_state: messages::GameState = p2.recv_json().await;
+    assert_eq!(p1_state.turns_left, 2);
+    assert_eq!(p1_state.board, p2_state.board);
+    assert_inked(&p1_state.board, &placement, PlayerNum::P1);
+    assert_eq!(p1_state.player.special, 0);
+    assert_eq!(p2_state.player.special, 1);
+
+    p1.send_json(&pass()).await;
+    p2.send_json(&pass()).await;
+    let p1_state: messages::GameState = p1.recv_json().await;
+    let p2_state: messages::GameState = p2.recv_json().await;
+    assert_eq!(p2_state.turns_left, 1);
+    assert_eq!(p1_state.player.special, 1);
+    assert_eq!(p2_state.player.special, 2);
+
+    p1.send_json(&pass()).await;
+    p2.send_json(&pass()).await;
+    let p1_end: messages::GameEnd = p1.recv_json().await;
+    let p2_end: messages::GameEnd = p2.recv_json().await;
+    assert!(matches!(p1_end.outcome, messages::Outcome::Win));
+    assert!(matches!(p2_end.outcome, messages::Outcome::Lose));
+
+    p1.send_json(&false).await;
+    p2.send_json(&true).await;
+    assert_eq!(p1.recv().await, "leave");
+    assert_eq!(p2.recv().await, "leave");
+    server.wait_for_games_to_end().await;
+}
+
+#[tokio::test]
+async fn test_seeded_games_are_reproducible() {
+    let mut hands = vec![];
+    for _ in 0..2 {
+        let server = TestServer::start(test_config()).await;
+        let mut p1 = server.connect(1).await;
+        let mut p2 = server.connect(2).await;
+        server.join(&mut p1, &mut p2).await;
+        let p1_state: messages::GameState = p1.recv_json().await;
+        let p2_state: messages::GameState = p2.recv_json().await;
+        hands.push([
+            p1_state.player.hand().clone(),
+            p2_state.player.hand().clone(),
+        ]);
+    }
+    assert_eq!(hands[0], hands[1]);
+}
+
+#[tokio::test]
+async fn test_opponent_leaving_ends_game() {
+    let server = TestServer::start(test_config()).await;
+    let mut p1 = server.connect(1).await;
+    let mut p2 = server.connect(2).await;
+    server.join(&mut p1, &mut p2).await;
+    let _: messages::GameState = p1.recv_json().await;
+    let _: messages::GameState = p2.recv_json().await;
+
+    p1.close().await;
+    assert_eq!(p2.recv().await, "leave");
+    let p2_id = p2.id.clone();
+    server
+        .wait_for(|clients| matches!(clients.get(&p2_id).map(|c| &c.status), Some(Status::Idle)))
+        .await;
+    server.wait_for_games_to_end().await;
+}
+
+// Plays a whole match against an AI, placing a card on the first turn and passing after
+async fn play_against_ai(player: &mut TestClient) {
+    let _: messages::GameState = player.recv_json().await;
+    player.send_json(&false).await;
+    let state: messages::GameState = player.recv_json().await;
+    let (input, placement) = first_placement(&state);
+    player.send_json(&input).await;
+    let state: messages::GameState = player.recv_json().await;
+    assert_eq!(state.turns_left, 2);
+    assert_inked(&state.board, &placement, state.player.player_num());
+    assert_eq!(state.player.special, 0);
+    player.send_json(&pass()).await;
+    let state: messages::GameState = player.recv_json().await;
+    assert_eq!(state.turns_left, 1);
+    assert_eq!(state.player.special, 1);
+    player.send_json(&pass()).await;
+    let _: messages::GameEnd = player.recv_json().await;
+    player.send_json(&false).await;
+    assert_eq!(player.recv().await, "leave");
+}
+
+#[tokio::test]
+async fn test_practice_game() {
+    let server = TestServer::start(test_config()).await;
+    let mut player = server.connect(1).await;
+    player.send("practice hard").await;
+    play_against_ai(&mut player).await;
+    server.wait_for_games_to_end().await;
+}
+
+#[tokio::test]
+async fn test_ai_fallback() {
+    let config = Config {
+        ai_fallback_secs: Some(0),
+        ..test_config()
+    };
+    let server = TestServer::start(config).await;
+    let mut player = server.connect(1).await;
+    player.send("join easy").await;
+    play_against_ai(&mut player).await;
+    server.wait_for_games_to_end().await;
+}
+
+#[tokio::test]
+async fn test_puzzles() {
+    let config = Config {
+        puzzle_dir: Some(concat!(env!("CARGO_MANIFEST_DIR"), "/puzzles").into()),
+        ..test_config()
+    };
+    let server = TestServer::start(config).await;
+    let http = reqwest::Client::new();
+    let get = |path: &str| http.get(server.url(path)).send();
+
+    let summaries: Vec<serde_json::Value> = get("/puzzles").await.unwrap().json().await.unwrap();
+    assert!(summaries.iter().any(|summary| summary["id"] == "last-word"));
+    let puzzle: Puzzle = get("/puzzles/last-word")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        get("/puzzles/missing").await.unwrap().status(),
+        StatusCode::NOT_FOUND
+    );
+
+    // The puzzle can be solved, checking every move against the server
+    let game_state = puzzle.game_state().unwrap();
+    let mut solved = false;
+    for (input, _) in legal_inputs(game_state.board(), game_state.player(puzzle.player_num)) {
+        let response = http
+            .post(server.url("/puzzles/last-word/check"))
+            .json(&[input])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let verdict: Verdict = response.json().await.unwrap();
+        solved |= verdict.solved;
+    }
+    assert!(solved);
+
+    let response = http
+        .post(server.url("/puzzles/last-word/check"))
+        .json(&[pass(), pass()])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
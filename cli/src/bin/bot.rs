@@ -0,0 +1,147 @@
+use clap::{Parser, ValueEnum};
+use common::ai::{Agent, GreedyAgent, RandomAgent};
+use common::{messages, InputError, RawInput};
+use std::time::Duration;
+use tableturf_cli::connection::{self, Connection, Controller};
+
+// How long to wait before reconnecting after losing the connection to the server
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AgentKind {
+    Random,
+    Greedy,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Play Tableturf on a server with a bot, queueing for matches until stopped")]
+struct Args {
+    /// HTTP URL of the Tableturf server
+    #[arg(
+        long,
+        env = "TABLETURF_SERVER",
+        default_value = "http://127.0.0.1:8000"
+    )]
+    server: String,
+    /// User ID to register with
+    #[arg(long, default_value_t = 0)]
+    user_id: usize,
+    /// Strategy the bot plays with
+    #[arg(long, value_enum, default_value_t = AgentKind::Greedy)]
+    agent: AgentKind,
+    /// Seed for agents that make random choices
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Stop after playing this many games. Plays forever if unset.
+    #[arg(long)]
+    games: Option<usize>,
+    /// Accept rematches instead of queueing for a new opponent after every game
+    #[arg(long)]
+    rematch: bool,
+}
+
+#[derive(Debug, Default)]
+struct Record {
+    wins: usize,
+    losses: usize,
+    draws: usize,
+}
+
+impl Record {
+    fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+}
+
+struct Bot<A: Agent> {
+    agent: A,
+    rematch: bool,
+    record: Record,
+    // Rematches are declined once this many games have been played
+    max_games: Option<usize>,
+}
+
+impl<A: Agent> Controller for Bot<A> {
+    fn redraw(&mut self, state: &messages::GameState) -> bool {
+        self.agent.redraw(&state.board, &state.player)
+    }
+
+    fn turn(&mut self, state: &messages::GameState) -> RawInput {
+        self.agent.choose(&state.board, &state.player)
+    }
+
+    fn invalid_input(&mut self, err: InputError) {
+        eprintln!("agent chose an invalid input: {}", err);
+    }
+
+    fn rematch(&mut self, end: &messages::GameEnd) -> bool {
+        match end.outcome {
+            messages::Outcome::Win => self.record.wins += 1,
+            messages::Outcome::Lose => self.record.losses += 1,
+            messages::Outcome::Draw => self.record.draws += 1,
+        }
+        println!(
+            "{:?} after {} games: {} wins, {} losses, {} draws",
+            end.outcome,
+            self.record.games(),
+            self.record.wins,
+            self.record.losses,
+            self.record.draws
+        );
+        self.rematch && !self.finished()
+    }
+}
+
+impl<A: Agent> Bot<A> {
+    fn finished(&self) -> bool {
+        self.max_games
+            .is_some_and(|max_games| self.record.games() >= max_games)
+    }
+}
+
+// Queues for matches until the bot has played enough games, reconnecting whenever the
+// connection is lost
+async fn run(args: &Args, bot: &mut Bot<impl Agent>) {
+    while !bot.finished() {
+        let mut connection = match Connection::register(&args.server, args.user_id).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                eprintln!("{}", err);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        println!("Connected to {}", args.server);
+        while !bot.finished() {
+            if let Err(err) = connection::play(&mut connection, bot).await {
+                eprintln!("{}", err);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+    match args.agent {
+        AgentKind::Random => {
+            let agent = match args.seed {
+                Some(seed) => RandomAgent::from_seed(seed),
+                None => RandomAgent::default(),
+            };
+            run(&args, &mut bot(&args, agent)).await
+        }
+        AgentKind::Greedy => run(&args, &mut bot(&args, GreedyAgent)).await,
+    }
+}
+
+fn bot<A: Agent>(args: &Args, agent: A) -> Bot<A> {
+    Bot {
+        agent,
+        rematch: args.rematch,
+        record: Record::default(),
+        max_games: args.games,
+    }
+}
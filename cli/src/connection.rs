@@ -0,0 +1,198 @@
+use common::{messages, InputError, RawInput, ValidInput};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Failed to register with the server: {0}")]
+    Register(#[from] reqwest::Error),
+    #[error("Websocket error: {0}")]
+    WebSocket(Box<tungstenite::Error>),
+    #[error("Connection closed by the server")]
+    Closed,
+    #[error("Unexpected message from the server: {0}")]
+    UnexpectedMessage(String),
+}
+
+impl From<tungstenite::Error> for ClientError {
+    fn from(err: tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(err))
+    }
+}
+
+#[derive(Serialize)]
+struct RegisterRequest {
+    user_id: usize,
+}
+
+#[derive(Deserialize)]
+struct RegisterResponse {
+    url: String,
+}
+
+#[derive(Debug)]
+pub enum ServerMessage {
+    GameState(Box<messages::GameState>),
+    GameEnd(messages::GameEnd),
+    // Sent when the game is over and the player can join another one
+    Leave,
+}
+
+// Game messages aren't tagged, so they're told apart by their fields
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GameMessage {
+    GameEnd(messages::GameEnd),
+    GameState(Box<messages::GameState>),
+}
+
+pub struct Connection {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl Connection {
+    // Registers with the server at the given HTTP URL and connects to the websocket it returns
+    pub async fn register(server_url: &str, user_id: usize) -> Result<Self, ClientError> {
+        let response: RegisterResponse = reqwest::Client::new()
+            .post(format!("{}/register", server_url.trim_end_matches('/')))
+            .json(&RegisterRequest { user_id })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let (ws, _) = tokio_tungstenite::connect_async(&response.url).await?;
+        Ok(Connection { ws })
+    }
+
+    pub async fn send(&mut self, msg: &str) -> Result<(), ClientError> {
+        self.ws.send(tungstenite::Message::text(msg)).await?;
+        Ok(())
+    }
+
+    pub async fn send_json<T: Serialize>(&mut self, msg: &T) -> Result<(), ClientError> {
+        // If we cannot serialize our own message, panic because that's a bug
+        self.send(&serde_json::to_string(msg).unwrap()).await
+    }
+
+    // Waits for the next message from the server, skipping control frames
+    pub async fn recv(&mut self) -> Result<ServerMessage, ClientError> {
+        while let Some(msg) = self.ws.next().await {
+            let text = match msg? {
+                tungstenite::Message::Text(text) => text,
+                tungstenite::Message::Close(_) => return Err(ClientError::Closed),
+                _ => continue,
+            };
+            return parse_message(&text);
+        }
+        Err(ClientError::Closed)
+    }
+
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        self.ws.close(None).await?;
+        Ok(())
+    }
+}
+
+fn parse_message(text: &str) -> Result<ServerMessage, ClientError> {
+    if text == "leave" {
+        return Ok(ServerMessage::Leave);
+    }
+    match serde_json::from_str(text) {
+        Ok(GameMessage::GameState(state)) => Ok(ServerMessage::GameState(state)),
+        Ok(GameMessage::GameEnd(end)) => Ok(ServerMessage::GameEnd(end)),
+        Err(_) => Err(ClientError::UnexpectedMessage(text.to_string())),
+    }
+}
+
+// Makes the choices for a player over the course of a match
+pub trait Controller {
+    // Whether to redraw the hand dealt at the start of a game
+    fn redraw(&mut self, state: &messages::GameState) -> bool;
+
+    // Picks the input for the current turn
+    fn turn(&mut self, state: &messages::GameState) -> RawInput;
+
+    // Called when the input picked by turn is invalid, before turn is called again
+    fn invalid_input(&mut self, _err: InputError) {}
+
+    // Whether to play another game against the same opponent
+    fn rematch(&mut self, end: &messages::GameEnd) -> bool;
+}
+
+// Joins a game and plays it, along with any rematches, until the server ends it
+pub async fn play(
+    connection: &mut Connection,
+    controller: &mut impl Controller,
+) -> Result<(), ClientError> {
+    connection.send("join").await?;
+    // The first state of every game is the hand that may be redrawn
+    let mut redraw_pending = true;
+    loop {
+        match connection.recv().await? {
+            ServerMessage::GameState(state) if redraw_pending => {
+                let redraw = controller.redraw(&state);
+                connection.send_json(&redraw).await?;
+                redraw_pending = false;
+            }
+            ServerMessage::GameState(state) => {
+                let input = choose_input(controller, &state);
+                connection.send_json(&input).await?;
+            }
+            ServerMessage::GameEnd(end) => {
+                let rematch = controller.rematch(&end);
+                connection.send_json(&rematch).await?;
+                redraw_pending = true;
+            }
+            ServerMessage::Leave => return Ok(()),
+        }
+    }
+}
+
+// The server ignores invalid inputs, so they're checked here to avoid waiting on a turn
+// that will never end
+fn choose_input(controller: &mut impl Controller, state: &messages::GameState) -> RawInput {
+    loop {
+        let input = controller.turn(state);
+        match ValidInput::new(input.clone(), &state.board, &state.player) {
+            Ok(_) => return input,
+            Err(err) => controller.invalid_input(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{DeckRng, PlayerNum};
+
+    #[test]
+    fn test_parse_message() {
+        assert!(matches!(parse_message("leave"), Ok(ServerMessage::Leave)));
+        assert!(matches!(
+            parse_message("hello"),
+            Err(ClientError::UnexpectedMessage(_))
+        ));
+
+        let game_state = common::GameState::<DeckRng>::default();
+        let state = serde_json::to_string(&messages::GameState {
+            board: game_state.board().clone(),
+            player: game_state.player(PlayerNum::P1).clone(),
+            turns_left: game_state.turns_left(),
+        })
+        .unwrap();
+        assert!(matches!(
+            parse_message(&state),
+            Ok(ServerMessage::GameState(_))
+        ));
+        let end = serde_json::to_string(&messages::GameEnd {
+            board: game_state.board().clone(),
+            outcome: messages::Outcome::Win,
+        })
+        .unwrap();
+        assert!(matches!(parse_message(&end), Ok(ServerMessage::GameEnd(_))));
+    }
+}
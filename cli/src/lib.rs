@@ -0,0 +1,3 @@
+pub mod connection;
+pub mod moves;
+pub mod render;
@@ -0,0 +1,118 @@
+use clap::Parser;
+use common::notation::parse_input;
+use common::{messages, InputError, PlayerNum, RawInput};
+use std::io::{self, BufRead, Write};
+use tableturf_cli::connection::{self, Connection, Controller};
+use tableturf_cli::moves::{parse_move, MOVE_HELP};
+use tableturf_cli::render::{render_board, render_hand};
+
+#[derive(Parser, Debug)]
+#[command(about = "Play Tableturf against another player from the terminal")]
+struct Args {
+    /// HTTP URL of the Tableturf server
+    #[arg(
+        long,
+        env = "TABLETURF_SERVER",
+        default_value = "http://127.0.0.1:8000"
+    )]
+    server: String,
+    /// User ID to register with
+    #[arg(long, default_value_t = 0)]
+    user_id: usize,
+}
+
+// Prompts the player on stdin for every choice
+struct Terminal;
+
+impl Terminal {
+    // Exits when stdin is closed, since there's no one left to play
+    fn prompt(&self, prompt: &str) -> String {
+        print!("{} ", prompt);
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => std::process::exit(0),
+            Ok(_) => line.trim().to_string(),
+        }
+    }
+
+    fn confirm(&self, prompt: &str) -> bool {
+        matches!(
+            self.prompt(&format!("{} [y/N]", prompt)).as_str(),
+            "y" | "Y" | "yes"
+        )
+    }
+
+    fn show_state(&self, state: &messages::GameState) {
+        let player_num = match state.player.player_num() {
+            PlayerNum::P1 => 1,
+            PlayerNum::P2 => 2,
+        };
+        println!();
+        println!("{}", render_board(&state.board));
+        println!(
+            "You are player {} (ink {}). Turns left: {}. Special: {}",
+            player_num, player_num, state.turns_left, state.player.special
+        );
+        println!("{}", render_hand(&state.player));
+    }
+}
+
+impl Controller for Terminal {
+    fn redraw(&mut self, state: &messages::GameState) -> bool {
+        self.show_state(state);
+        self.confirm("Redraw your hand?")
+    }
+
+    fn turn(&mut self, state: &messages::GameState) -> RawInput {
+        self.show_state(state);
+        loop {
+            let line = self.prompt("Your move (or `help`):");
+            // Notation names the card instead of numbering it
+            let input = if line.contains('@') || line.starts_with("Pass ") {
+                parse_input(&line, &state.player).map_err(|err| err.to_string())
+            } else {
+                parse_move(&line).map_err(|err| err.to_string())
+            };
+            match input {
+                Ok(input) => return input,
+                Err(err) => println!("{}\n{}", err, MOVE_HELP),
+            }
+        }
+    }
+
+    fn invalid_input(&mut self, err: InputError) {
+        println!("Invalid move: {}", err);
+    }
+
+    fn rematch(&mut self, end: &messages::GameEnd) -> bool {
+        println!();
+        println!("{}", render_board(&end.board));
+        match end.outcome {
+            messages::Outcome::Win => println!("You win!"),
+            messages::Outcome::Lose => println!("You lose."),
+            messages::Outcome::Draw => println!("It's a draw."),
+        }
+        self.confirm("Play again?")
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+    let mut connection = match Connection::register(&args.server, args.user_id).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    println!("Connected to {}. Waiting for an opponent...", args.server);
+    println!("{}", MOVE_HELP);
+    if let Err(err) = connection::play(&mut connection, &mut Terminal).await {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    println!("The game is over.");
+    let _ = connection.close().await;
+}
@@ -0,0 +1,135 @@
+use common::{Action, HandIndex, RawInput, RawPlacement, Rotation};
+use thiserror::Error;
+
+pub const MOVE_HELP: &str = "Moves are entered as one of:
+  <card> <rotation> <x> <y> [special]   place a card
+  pass <card>                           discard a card to charge special
+where <card> is 1-4, <rotation> is the number of counter-clockwise turns (0-3)
+and <x> <y> are the board coordinates to put the card's pivot on. The pivot is
+marked in the hand, and the card turns around it.
+Add `special` to place the card as a special attack.
+Moves can also be written in notation, e.g. `Splattershot R1 @ (5,2) SP` or
+`Pass Splattershot`.";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseMoveError {
+    #[error("Expected `<card> <rotation> <x> <y> [special]` or `pass <card>`")]
+    Format,
+    #[error("Card must be 1-4, got {0}")]
+    Card(String),
+    #[error("Rotation must be 0-3, got {0}")]
+    Rotation(String),
+    #[error("Invalid coordinate {0}")]
+    Coordinate(String),
+}
+
+pub fn parse_move(s: &str) -> Result<RawInput, ParseMoveError> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    match words.as_slice() {
+        ["pass", card] => Ok(RawInput {
+            hand_idx: parse_card(card)?,
+            action: Action::Pass,
+        }),
+        [card, rotation, x, y, rest @ ..] => {
+            let special_activated = match rest {
+                [] => false,
+                ["special"] => true,
+                _ => return Err(ParseMoveError::Format),
+            };
+            Ok(RawInput {
+                hand_idx: parse_card(card)?,
+                action: Action::Place(RawPlacement {
+                    x: parse_coordinate(x)?,
+                    y: parse_coordinate(y)?,
+                    special_activated,
+                    rotation: parse_rotation(rotation)?,
+                }),
+            })
+        }
+        _ => Err(ParseMoveError::Format),
+    }
+}
+
+fn parse_card(s: &str) -> Result<HandIndex, ParseMoveError> {
+    match s {
+        "1" => Ok(HandIndex::H1),
+        "2" => Ok(HandIndex::H2),
+        "3" => Ok(HandIndex::H3),
+        "4" => Ok(HandIndex::H4),
+        _ => Err(ParseMoveError::Card(s.to_string())),
+    }
+}
+
+fn parse_rotation(s: &str) -> Result<Rotation, ParseMoveError> {
+    match s {
+        "0" => Ok(Rotation::Zero),
+        "1" => Ok(Rotation::One),
+        "2" => Ok(Rotation::Two),
+        "3" => Ok(Rotation::Three),
+        _ => Err(ParseMoveError::Rotation(s.to_string())),
+    }
+}
+
+fn parse_coordinate(s: &str) -> Result<i32, ParseMoveError> {
+    s.parse()
+        .map_err(|_| ParseMoveError::Coordinate(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_place() {
+        let input = parse_move("2 1 10 12 special").unwrap();
+        assert!(matches!(input.hand_idx, HandIndex::H2));
+        match input.action {
+            Action::Place(placement) => {
+                assert_eq!(placement.x, 10);
+                assert_eq!(placement.y, 12);
+                assert_eq!(placement.rotation, Rotation::One);
+                assert!(placement.special_activated);
+            }
+            Action::Pass => panic!("expected a placement"),
+        }
+
+        let input = parse_move("  4 0 -3 5 ").unwrap();
+        assert!(matches!(input.hand_idx, HandIndex::H4));
+        assert!(matches!(
+            input.action,
+            Action::Place(RawPlacement {
+                special_activated: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_pass() {
+        let input = parse_move("pass 3").unwrap();
+        assert!(matches!(input.hand_idx, HandIndex::H3));
+        assert!(matches!(input.action, Action::Pass));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse_move("").unwrap_err(), ParseMoveError::Format);
+        assert_eq!(parse_move("pass").unwrap_err(), ParseMoveError::Format);
+        assert_eq!(
+            parse_move("5 0 1 1").unwrap_err(),
+            ParseMoveError::Card("5".to_string())
+        );
+        assert_eq!(
+            parse_move("1 4 1 1").unwrap_err(),
+            ParseMoveError::Rotation("4".to_string())
+        );
+        assert_eq!(
+            parse_move("1 0 x 1").unwrap_err(),
+            ParseMoveError::Coordinate("x".to_string())
+        );
+        assert_eq!(
+            parse_move("1 0 1 1 now").unwrap_err(),
+            ParseMoveError::Format
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use common::{Board, Card, HandIndex, InkSpace, Player, CARD_WIDTH};
+
+// Draws the board with its row and column numbers, which are the coordinates moves refer to
+pub fn render_board(board: &Board) -> String {
+    let columns: Vec<String> = (0..board.width()).map(|x| format!("{:>2}", x)).collect();
+    let mut lines = vec![format!("   {}", columns.join(" "))];
+    for (y, row) in board.to_string().lines().enumerate() {
+        lines.push(format!("{:>2} {}", y, row));
+    }
+    lines.join("\n")
+}
+
+// The pivot is drawn as O, @ or + depending on whether it's ink, special or empty
+fn render_grid_row(card: &Card, y: usize) -> String {
+    let pivot = card.pivot();
+    card.spaces()[y]
+        .iter()
+        .enumerate()
+        .map(|(x, space)| match (space, (x, y) == pivot) {
+            (None, false) => '.',
+            (Some(InkSpace::Normal), false) => '#',
+            (Some(InkSpace::Special), false) => '*',
+            (None, true) => '+',
+            (Some(InkSpace::Normal), true) => 'O',
+            (Some(InkSpace::Special), true) => '@',
+        })
+        .collect()
+}
+
+// Lists the cards in the player's hand side by side, with their grids and pivots, since
+// placement coordinates are where the pivot goes
+pub fn render_hand(player: &Player) -> String {
+    let cards = HandIndex::ALL.map(|hand_idx| player.get_card(hand_idx));
+    let mut lines = vec![];
+    for (i, card) in cards.iter().enumerate() {
+        lines.push(format!(
+            "{}. {} (priority {}, special cost {})",
+            i + 1,
+            card.name(),
+            card.priority(),
+            card.special()
+        ));
+    }
+    let labels: Vec<String> = (1..=cards.len())
+        .map(|i| format!("{:<width$}", i, width = CARD_WIDTH))
+        .collect();
+    lines.push(labels.join("  ").trim_end().to_string());
+    for y in 0..CARD_WIDTH {
+        let row: Vec<String> = cards.iter().map(|card| render_grid_row(card, y)).collect();
+        lines.push(row.join("  "));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{DeckRng, GameState, PlayerNum};
+
+    #[test]
+    fn test_render_board() {
+        let game_state = GameState::<DeckRng>::default();
+        let board = game_state.board();
+        let rendered = render_board(board);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), board.height() + 1);
+        assert!(lines[0].trim_start().starts_with("0  1  2"));
+        assert!(lines[1].starts_with(" 0 MT"));
+        // Every space lines up under its column number
+        assert_eq!(lines[0].len(), lines[1].len());
+    }
+
+    #[test]
+    fn test_render_hand() {
+        let game_state = GameState::<DeckRng>::default();
+        let player = game_state.player(PlayerNum::P1);
+        let rendered = render_hand(player);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 4 + 1 + CARD_WIDTH);
+        assert!(lines[0].starts_with(&format!("1. {}", player.get_card(HandIndex::H1).name())));
+        assert!(rendered.contains('#'));
+        // Each card's pivot is marked once
+        let pivots = rendered
+            .chars()
+            .filter(|c| ['O', '@', '+'].contains(c))
+            .count();
+        assert_eq!(pivots, 4);
+    }
+}
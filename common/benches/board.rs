@@ -0,0 +1,61 @@
+use common::ai::{legal_inputs, Agent, GreedyAgent};
+use common::{DeckRng, GameState, Player, PlayerNum, ValidInput};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+const TURNS: u32 = 12;
+
+// A game a few turns in, where both players' ink is spread across the board
+fn midgame() -> GameState<DeckRng> {
+    let mut game_state = GameState::with_rng(TURNS, DeckRng::from_seed(7));
+    let mut agent = GreedyAgent;
+    for _ in 0..5 {
+        let [input1, input2] = [PlayerNum::P1, PlayerNum::P2].map(|player_num| {
+            let player = game_state.player(player_num);
+            let raw_input = agent.choose(game_state.board(), player);
+            ValidInput::new(raw_input, game_state.board(), player).unwrap()
+        });
+        game_state.update(input1, input2);
+    }
+    game_state
+}
+
+fn players(game_state: &GameState<DeckRng>) -> [Player; 2] {
+    [PlayerNum::P1, PlayerNum::P2].map(|player_num| game_state.player(player_num).clone())
+}
+
+fn move_generation(c: &mut Criterion) {
+    let game_state = midgame();
+    let board = game_state.board();
+    let player = game_state.player(PlayerNum::P1);
+    c.bench_function("legal_inputs", |b| {
+        b.iter(|| legal_inputs(black_box(board), black_box(player)))
+    });
+}
+
+fn update(c: &mut Criterion) {
+    let game_state = midgame();
+    let mut agent = GreedyAgent;
+    let inputs = [PlayerNum::P1, PlayerNum::P2].map(|player_num| {
+        let player = game_state.player(player_num);
+        let raw_input = agent.choose(game_state.board(), player);
+        ValidInput::new(raw_input, game_state.board(), player).unwrap()
+    });
+    c.bench_function("update", |b| {
+        b.iter_batched(
+            || {
+                let next = GameState::new(
+                    game_state.board().clone(),
+                    players(&game_state),
+                    game_state.turns_left(),
+                    DeckRng::from_seed(0),
+                );
+                (next, inputs.clone())
+            },
+            |(mut next, [input1, input2])| next.update(input1, input2),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, move_generation, update);
+criterion_main!(benches);
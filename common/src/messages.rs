@@ -1,10 +1,28 @@
-use crate::tableturf::{Player, Board};
+use crate::tableturf::{Action, Board, Card, Player};
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GameState {
     pub board: Board,
-    pub player: Player
+    pub player: Player,
+    pub turns_left: u32
+}
+
+// What spectators are shown. Hands are left out so that spectators can't leak them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpectatorState {
+    pub board: Board,
+    // Player 1's special gauge followed by Player 2's
+    pub special: [u32; 2],
+    pub turns_left: u32,
+    // The cards played by Player 1 and Player 2 on the last turn, if any
+    pub revealed: Option<[RevealedCard; 2]>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RevealedCard {
+    pub card: Card,
+    pub action: Action
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -0,0 +1,299 @@
+use crate::tableturf::bitboard::BitBoard;
+use crate::tableturf::board::{Board, BoardSpace};
+use crate::tableturf::deck::HandIndex;
+use crate::tableturf::input::{
+    rotate_input, rotate_pivot, Action, Input, RawInput, RawPlacement, Rotation, ValidInput,
+};
+use crate::tableturf::player::{Player, PlayerNum};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// Picks a player's moves. Agents only see what the player would see: the board and their own hand.
+pub trait Agent {
+    // Whether to redraw the hand dealt at the start of a game
+    fn redraw(&mut self, _board: &Board, _player: &Player) -> bool {
+        false
+    }
+
+    // Picks a legal input for the current turn
+    fn choose(&mut self, board: &Board, player: &Player) -> RawInput;
+}
+
+// Lists every input the player can make, paired with its validated form.
+// Placements that ink the same spaces with different rotations are all included.
+pub fn legal_inputs(board: &Board, player: &Player) -> Vec<(RawInput, ValidInput)> {
+    let player_num = player.player_num();
+    // Ink can only go on empty spaces, and special attacks on anything but walls and specials
+    let occupied =
+        *board.wall_bits() | *board.ink_bits(PlayerNum::P1) | *board.ink_bits(PlayerNum::P2);
+    let special_blocked = *board.wall_bits()
+        | *board.special_bits(PlayerNum::P1)
+        | *board.special_bits(PlayerNum::P2);
+    let mut inputs = vec![];
+    for hand_idx in HandIndex::ALL {
+        let raw_input = RawInput {
+            hand_idx,
+            action: Action::Pass,
+        };
+        if let Some(valid_input) = ValidInput::try_new(raw_input.clone(), board, player) {
+            inputs.push((raw_input, valid_input));
+        }
+        for rotation in Rotation::ALL {
+            let card = player.get_card(hand_idx);
+            let shape = BitBoard::from_grid(&rotate_input(card, rotation));
+            let (pivot_x, pivot_y) = rotate_pivot(card, rotation);
+            for special_activated in [false, true] {
+                // Skip checking every position when the special attack can't be afforded
+                if special_activated && player.special < player.get_card(hand_idx).special() {
+                    continue;
+                }
+                let (blocked, anchor) = if special_activated {
+                    (&special_blocked, board.special_bits(player_num))
+                } else {
+                    (&occupied, board.ink_bits(player_num))
+                };
+                // The pivot is in the middle of the card's ink, so it's on the board
+                // whenever all of the ink is
+                for y in 0..board.height() as i32 {
+                    for x in 0..board.width() as i32 {
+                        // Most positions overlap something or are away from the player's
+                        // ink, which the shifted shape rules out before full validation
+                        let placed = shape.shifted(x - pivot_x as i32, y - pivot_y as i32);
+                        if placed.intersects(blocked) || !placed.touches(anchor) {
+                            continue;
+                        }
+                        let raw_input = RawInput {
+                            hand_idx,
+                            action: Action::Place(RawPlacement {
+                                x,
+                                y,
+                                special_activated,
+                                rotation,
+                            }),
+                        };
+                        if let Some(valid_input) =
+                            ValidInput::try_new(raw_input.clone(), board, player)
+                        {
+                            inputs.push((raw_input, valid_input));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    inputs
+}
+
+// Plays a uniformly random legal input
+#[derive(Debug)]
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn from_seed(seed: u64) -> Self {
+        RandomAgent {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RandomAgent {
+    fn default() -> Self {
+        RandomAgent {
+            rng: StdRng::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn choose(&mut self, board: &Board, player: &Player) -> RawInput {
+        let inputs = legal_inputs(board, player);
+        // Passing is always legal, so there is always an input to choose
+        let (raw_input, _) = inputs.choose(&mut self.rng).unwrap();
+        raw_input.clone()
+    }
+}
+
+// Plays the input that inks the most spaces this turn, preferring placements that
+// advance furthest towards the opponent. Only passes when no card can be placed.
+#[derive(Debug, Default)]
+pub struct GreedyAgent;
+
+impl GreedyAgent {
+    fn score(board: &Board, player_num: PlayerNum, input: &ValidInput) -> Option<usize> {
+        let placement = match input.clone().get() {
+            Input::Pass => return None,
+            Input::Place(placement) => placement,
+        };
+        let spaces = &placement.ink_spaces().0;
+        // Special attacks can ink over the opponent, which takes those spaces from them
+        let taken = spaces
+            .iter()
+            .filter(|(bp, _)| match board.get_space(bp.x(), bp.y()) {
+                BoardSpace::Ink { player_num: owner } => owner != player_num,
+                _ => false,
+            })
+            .count();
+        // Player 1 starts at the bottom of the board and Player 2 at the top
+        let advance = match player_num {
+            PlayerNum::P1 => board.height() - spaces.iter().map(|(bp, _)| bp.y()).min()?,
+            PlayerNum::P2 => spaces.iter().map(|(bp, _)| bp.y()).max()?,
+        };
+        Some((spaces.len() + taken) * board.height() + advance)
+    }
+}
+
+impl Agent for GreedyAgent {
+    fn choose(&mut self, board: &Board, player: &Player) -> RawInput {
+        let inputs = legal_inputs(board, player);
+        let best = inputs
+            .iter()
+            .filter_map(|(raw_input, valid_input)| {
+                GreedyAgent::score(board, player.player_num(), valid_input)
+                    .map(|score| (score, raw_input))
+            })
+            .max_by_key(|(score, _)| *score);
+        match best {
+            Some((_, raw_input)) => raw_input.clone(),
+            // Passing is always legal, so the first input is a pass
+            None => inputs[0].0.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::card::CARD_WIDTH;
+    use crate::tableturf::game_state::{DeckRng, GameState};
+
+    fn play_turn<A: Agent, B: Agent>(game_state: &mut GameState<DeckRng>, p1: &mut A, p2: &mut B) {
+        let board = game_state.board().clone();
+        let input1 = p1.choose(&board, game_state.player(PlayerNum::P1));
+        let input2 = p2.choose(&board, game_state.player(PlayerNum::P2));
+        let input1 = ValidInput::new(input1, &board, game_state.player(PlayerNum::P1)).unwrap();
+        let input2 = ValidInput::new(input2, &board, game_state.player(PlayerNum::P2)).unwrap();
+        game_state.update(input1, input2);
+    }
+
+    #[test]
+    fn test_legal_inputs() {
+        let game_state = GameState::with_rng(12, DeckRng::from_seed(0));
+        let player = game_state.player(PlayerNum::P1);
+        let inputs = legal_inputs(game_state.board(), player);
+        let passes = inputs
+            .iter()
+            .filter(|(raw_input, _)| matches!(raw_input.action, Action::Pass))
+            .count();
+        assert_eq!(passes, 4);
+        // Every card fits next to the starting special space in some orientation
+        for hand_idx in HandIndex::ALL {
+            assert!(inputs.iter().any(|(raw_input, _)| {
+                raw_input.hand_idx == hand_idx && matches!(raw_input.action, Action::Place(_))
+            }));
+        }
+        // Special attacks need special, which players start without
+        assert!(!inputs.iter().any(|(raw_input, _)| matches!(
+            raw_input.action,
+            Action::Place(RawPlacement {
+                special_activated: true,
+                ..
+            })
+        )));
+    }
+
+    // Every input that passes full validation, without ruling positions out beforehand
+    fn brute_force_inputs(board: &Board, player: &Player) -> usize {
+        let mut count = 0;
+        for hand_idx in HandIndex::ALL {
+            let mut actions = vec![Action::Pass];
+            for rotation in Rotation::ALL {
+                for special_activated in [false, true] {
+                    // Every position where part of the grid is on the board
+                    let first = -(CARD_WIDTH as i32);
+                    for y in first..board.height() as i32 + CARD_WIDTH as i32 {
+                        for x in first..board.width() as i32 + CARD_WIDTH as i32 {
+                            actions.push(Action::Place(RawPlacement {
+                                x,
+                                y,
+                                special_activated,
+                                rotation,
+                            }));
+                        }
+                    }
+                }
+            }
+            count += actions
+                .into_iter()
+                .filter(|action| {
+                    let raw_input = RawInput {
+                        hand_idx,
+                        action: action.clone(),
+                    };
+                    ValidInput::new(raw_input, board, player).is_ok()
+                })
+                .count();
+        }
+        count
+    }
+
+    #[test]
+    fn test_legal_inputs_match_validation() {
+        let mut game_state = GameState::with_rng(12, DeckRng::from_seed(3));
+        for _ in 0..4 {
+            play_turn(&mut game_state, &mut GreedyAgent, &mut GreedyAgent);
+        }
+        let board = game_state.board();
+        for player_num in [PlayerNum::P1, PlayerNum::P2] {
+            // Enough special for any special attack
+            let mut player = game_state.player(player_num).clone();
+            player.special = 20;
+            let inputs = legal_inputs(board, &player);
+            assert_eq!(inputs.len(), brute_force_inputs(board, &player));
+            assert!(inputs.iter().any(|(raw_input, _)| matches!(
+                raw_input.action,
+                Action::Place(RawPlacement {
+                    special_activated: true,
+                    ..
+                })
+            )));
+        }
+    }
+
+    #[test]
+    fn test_agents_play_full_game() {
+        let mut game_state = GameState::with_rng(12, DeckRng::from_seed(1));
+        let mut random = RandomAgent::from_seed(1);
+        let mut greedy = GreedyAgent;
+        while game_state.turns_left() > 0 {
+            play_turn(&mut game_state, &mut greedy, &mut random);
+        }
+        assert!(game_state.board().count_inked_spaces(PlayerNum::P1) > 0);
+    }
+
+    #[test]
+    fn test_greedy_places_largest_card() {
+        let game_state = GameState::with_rng(12, DeckRng::from_seed(2));
+        let player = game_state.player(PlayerNum::P1);
+        let input = GreedyAgent.choose(game_state.board(), player);
+        let largest = HandIndex::ALL
+            .iter()
+            .map(|hand_idx| ink_count(player, *hand_idx))
+            .max()
+            .unwrap();
+        assert!(matches!(input.action, Action::Place(_)));
+        assert_eq!(ink_count(player, input.hand_idx), largest);
+    }
+
+    fn ink_count(player: &Player, hand_idx: HandIndex) -> usize {
+        player
+            .get_card(hand_idx)
+            .spaces()
+            .iter()
+            .flatten()
+            .filter(|space| space.is_some())
+            .count()
+    }
+}
@@ -0,0 +1,282 @@
+use crate::tableturf::board::{BoardPosition, MAX_BOARD_HEIGHT, MAX_BOARD_WIDTH};
+use crate::tableturf::card::{Grid, CARD_WIDTH};
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+// Rows in the largest board
+const ROWS: usize = MAX_BOARD_HEIGHT;
+
+// A card's grid can hang off the right edge of the board, and rows are shifted by one
+// space in either direction, so they need room for a whole grid and a spare bit
+const _: () = assert!(MAX_BOARD_WIDTH + CARD_WIDTH < u64::BITS as usize);
+
+// A set of spaces on a board, one word per row with bit x set for column x.
+// Whole rows are tested at once, so checking a placement against the board or finding
+// the spaces next to some ink takes a few shifts rather than a lookup per space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitBoard([u64; ROWS]);
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        BitBoard::EMPTY
+    }
+}
+
+impl BitBoard {
+    pub const EMPTY: BitBoard = BitBoard([0; ROWS]);
+
+    pub fn from_positions<'a>(positions: impl IntoIterator<Item = &'a BoardPosition>) -> Self {
+        let mut bits = BitBoard::EMPTY;
+        for bp in positions {
+            bits.insert(bp.x(), bp.y());
+        }
+        bits
+    }
+
+    // The spaces a card's grid inks when its top-left corner is in the top-left corner
+    pub fn from_grid(grid: &Grid) -> Self {
+        let mut bits = BitBoard::EMPTY;
+        for (row, grid_row) in bits.0.iter_mut().zip(grid) {
+            for (x, space) in grid_row.iter().enumerate() {
+                if space.is_some() {
+                    *row |= 1 << x;
+                }
+            }
+        }
+        bits
+    }
+
+    // Moves every space right by x and down by y, either of which may be negative.
+    // Spaces moved past the top, bottom or left edge are dropped, so callers can tell a
+    // shape hung off the board by its count.
+    pub fn shifted(&self, x: i32, y: i32) -> BitBoard {
+        let mut bits = BitBoard::EMPTY;
+        for (from, row) in self.0.iter().enumerate() {
+            let to = from as i32 + y;
+            if (0..ROWS as i32).contains(&to) {
+                bits.0[to as usize] = if x >= 0 { row << x } else { row >> -x };
+            }
+        }
+        bits
+    }
+
+    pub fn insert(&mut self, x: usize, y: usize) {
+        self.0[y] |= 1 << x;
+    }
+
+    pub fn remove(&mut self, x: usize, y: usize) {
+        self.0[y] &= !(1 << x);
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.0
+            .get(y)
+            .is_some_and(|row| x < u64::BITS as usize && row & (1 << x) != 0)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|row| row.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|row| *row == 0)
+    }
+
+    // The spaces in this set but not the other
+    pub fn without(&self, other: &BitBoard) -> BitBoard {
+        let mut bits = *self;
+        for (row, other_row) in bits.0.iter_mut().zip(other.0) {
+            *row &= !other_row;
+        }
+        bits
+    }
+
+    pub fn intersects(&self, other: &BitBoard) -> bool {
+        self.0
+            .iter()
+            .zip(other.0)
+            .any(|(row, other_row)| row & other_row != 0)
+    }
+
+    // These spaces and every space next to one of them, diagonals included
+    pub fn neighborhood(&self) -> BitBoard {
+        let mut wide = [0; ROWS];
+        for (wide_row, row) in wide.iter_mut().zip(self.0) {
+            *wide_row = row | row << 1 | row >> 1;
+        }
+        let mut bits = BitBoard::EMPTY;
+        for (y, row) in bits.0.iter_mut().enumerate() {
+            *row = wide[y] | wide.get(y + 1).copied().unwrap_or(0);
+            if y > 0 {
+                *row |= wide[y - 1];
+            }
+        }
+        bits
+    }
+
+    // Whether any of these spaces is in the other set or next to a space in it
+    pub fn touches(&self, other: &BitBoard) -> bool {
+        self.0.iter().enumerate().any(|(y, row)| {
+            if *row == 0 {
+                return false;
+            }
+            let wide = row | row << 1 | row >> 1;
+            let above = if y > 0 { other.0[y - 1] } else { 0 };
+            let below = other.0.get(y + 1).copied().unwrap_or(0);
+            wide & (above | other.0[y] | below) != 0
+        })
+    }
+
+    // These spaces and every space in the other set connected to them through it,
+    // diagonals included
+    pub fn fill(&self, within: &BitBoard) -> BitBoard {
+        let mut filled = *self;
+        loop {
+            let grown = filled | filled.neighborhood() & *within;
+            if grown == filled {
+                return filled;
+            }
+            filled = grown;
+        }
+    }
+
+    // Whether any of the eight spaces around the given one is in the set
+    pub fn surrounds(&self, x: usize, y: usize) -> bool {
+        let wide = (0b111 << x) >> 1;
+        let above = if y > 0 { self.0[y - 1] } else { 0 };
+        let below = self.0.get(y + 1).copied().unwrap_or(0);
+        (above | below) & wide != 0 || self.0[y] & wide & !(1 << x) != 0
+    }
+
+    // The x and y coordinates of every space in the set, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.0.iter().enumerate().flat_map(|(y, row)| {
+            let mut row = *row;
+            std::iter::from_fn(move || {
+                if row == 0 {
+                    return None;
+                }
+                let x = row.trailing_zeros() as usize;
+                row &= row - 1;
+                Some((x, y))
+            })
+        })
+    }
+}
+
+impl BitAnd for BitBoard {
+    type Output = BitBoard;
+
+    fn bitand(mut self, other: BitBoard) -> BitBoard {
+        for (row, other_row) in self.0.iter_mut().zip(other.0) {
+            *row &= other_row;
+        }
+        self
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = BitBoard;
+
+    fn bitor(mut self, other: BitBoard) -> BitBoard {
+        self |= other;
+        self
+    }
+}
+
+impl BitOrAssign for BitBoard {
+    fn bitor_assign(&mut self, other: BitBoard) {
+        for (row, other_row) in self.0.iter_mut().zip(other.0) {
+            *row |= other_row;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::card::{InkSpace, CARD_WIDTH};
+
+    fn bits(positions: &[(usize, usize)]) -> BitBoard {
+        let mut bits = BitBoard::EMPTY;
+        for (x, y) in positions {
+            bits.insert(*x, *y);
+        }
+        bits
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = bits(&[(0, 0), (3, 2), (25, 25)]);
+        let b = bits(&[(3, 2), (4, 2)]);
+        assert!(a.contains(25, 25) && !a.contains(1, 0) && !a.contains(64, 0));
+        assert_eq!(a.count(), 3);
+        assert_eq!(a & b, bits(&[(3, 2)]));
+        assert_eq!(a | b, bits(&[(0, 0), (3, 2), (4, 2), (25, 25)]));
+        assert_eq!(a.without(&b), bits(&[(0, 0), (25, 25)]));
+        assert!(a.intersects(&b) && !a.without(&b).intersects(&b));
+        assert_eq!(
+            a.positions().collect::<Vec<_>>(),
+            vec![(0, 0), (3, 2), (25, 25)]
+        );
+
+        assert_eq!(a.shifted(1, 2), bits(&[(1, 2), (4, 4)]));
+        assert_eq!(a.shifted(-1, -2), bits(&[(2, 0), (24, 23)]));
+
+        let mut c = a;
+        c.remove(3, 2);
+        assert_eq!(c, a.without(&b));
+        assert!(BitBoard::default().is_empty());
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let mut grid = [[None; CARD_WIDTH]; CARD_WIDTH];
+        grid[0][1] = Some(InkSpace::Normal);
+        grid[7][7] = Some(InkSpace::Special);
+        let shape = BitBoard::from_grid(&grid);
+        assert_eq!(shape, bits(&[(1, 0), (7, 7)]));
+        assert_eq!(shape.shifted(3, 18), bits(&[(4, 18), (10, 25)]));
+        assert_eq!(shape.shifted(-2, 0), bits(&[(5, 7)]));
+        assert_eq!(shape.shifted(0, 19).count(), 1);
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let center = bits(&[(5, 5)]);
+        let around = bits(&[
+            (4, 4),
+            (5, 4),
+            (6, 4),
+            (4, 5),
+            (6, 5),
+            (4, 6),
+            (5, 6),
+            (6, 6),
+        ]);
+        assert_eq!(center.neighborhood(), around | center);
+        assert!(center.touches(&bits(&[(6, 6)])));
+        assert!(center.touches(&center));
+        assert!(!center.touches(&bits(&[(7, 5)])));
+
+        assert!(around.positions().all(|(x, y)| center.surrounds(x, y)));
+        assert!(!center.surrounds(5, 5) && !center.surrounds(7, 5));
+
+        // Spaces on the edges don't wrap around to the other side
+        let corner = bits(&[(0, 0)]);
+        assert_eq!(
+            corner.neighborhood(),
+            bits(&[(0, 0), (1, 0), (0, 1), (1, 1)])
+        );
+        assert!(corner.surrounds(1, 1) && !corner.surrounds(0, 2));
+        assert!(bits(&[(0, 25)]).touches(&bits(&[(1, 24)])));
+    }
+
+    #[test]
+    fn test_fill() {
+        // Two diagonal lines, with a gap between them
+        let within = bits(&[(1, 1), (2, 2), (3, 3), (5, 5), (6, 6)]);
+        let start = bits(&[(0, 0)]);
+        assert_eq!(start.fill(&within), start | bits(&[(1, 1), (2, 2), (3, 3)]));
+        assert_eq!(start.fill(&BitBoard::EMPTY), start);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::tableturf::bitboard::BitBoard;
 use crate::tableturf::player::PlayerNum;
+use crate::tableturf::zobrist;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
@@ -52,10 +54,12 @@ impl fmt::Display for Coordinate {
 pub enum BoardPositionError {
     #[error("{0} coordinate {1} exceeds board {2}")]
     OutOfBounds(Coordinate, usize, Dimension),
+    #[error("{0} coordinate {1} is before the start of the board")]
+    Negative(Coordinate, i32),
     #[error("Final {coordinate} coordinate with base {base} and offset {offset} overflowed")]
     Overflow {
         coordinate: Coordinate,
-        base: usize,
+        base: i32,
         offset: usize,
     },
 }
@@ -224,37 +228,149 @@ impl BoardPosition {
     }
 
     pub fn is_surrounded(&self, board: &Board) -> bool {
-        self.surrounding_spaces(board)
-            .iter()
-            .all(|s| !matches!(s, BoardSpace::Empty))
+        !board.empty_bits().surrounds(self.0, self.1)
     }
 
     // Test if a single space is adjacent to a player's special space
     pub fn adjacent_to_special(&self, board: &Board, player_num: PlayerNum) -> bool {
-        self.surrounding_spaces(board)
-            .iter()
-            .any(|s| s.is_special(player_num))
+        board.special_bits(player_num).surrounds(self.0, self.1)
     }
 
     // Test if a single space is adjacent to a player's inked space
     pub fn adjacent_to_ink(&self, board: &Board, player_num: PlayerNum) -> bool {
-        self.surrounding_spaces(board)
-            .iter()
-            .any(|s| s.is_ink(player_num))
+        board.ink_bits(player_num).surrounds(self.0, self.1)
     }
 }
 
 pub const MAX_BOARD_WIDTH: usize = 26;
 pub const MAX_BOARD_HEIGHT: usize = 26;
 
+pub(crate) fn player_idx(player_num: PlayerNum) -> usize {
+    match player_num {
+        PlayerNum::P1 => 0,
+        PlayerNum::P2 => 1,
+    }
+}
+
+// The board's spaces sorted into sets, so that rules about neighbouring spaces can be
+// checked a row at a time. Kept in step with the spaces by Board::set_space.
+#[derive(Clone, Debug, PartialEq)]
+struct Layers {
+    empty: BitBoard,
+    // Each player's ink, including their special spaces
+    ink: [BitBoard; 2],
+    special: [BitBoard; 2],
+    activated: BitBoard,
+    // Walls and the out of bounds spaces in irregularly shaped boards
+    walls: BitBoard,
+}
+
+impl Layers {
+    fn new(width: usize, spaces: &[BoardSpace]) -> Self {
+        let mut layers = Layers {
+            empty: BitBoard::EMPTY,
+            ink: [BitBoard::EMPTY; 2],
+            special: [BitBoard::EMPTY; 2],
+            activated: BitBoard::EMPTY,
+            walls: BitBoard::EMPTY,
+        };
+        for (idx, space) in spaces.iter().enumerate() {
+            layers.insert(idx % width, idx / width, *space);
+        }
+        layers
+    }
+
+    // Adds or removes the space in each layer it belongs to
+    fn mark(&mut self, x: usize, y: usize, space: BoardSpace, present: bool) {
+        let mark = |layer: &mut BitBoard| {
+            if present {
+                layer.insert(x, y)
+            } else {
+                layer.remove(x, y)
+            }
+        };
+        match space {
+            BoardSpace::Empty => mark(&mut self.empty),
+            BoardSpace::Ink { player_num } => mark(&mut self.ink[player_idx(player_num)]),
+            BoardSpace::Special {
+                player_num,
+                is_activated,
+            } => {
+                mark(&mut self.ink[player_idx(player_num)]);
+                mark(&mut self.special[player_idx(player_num)]);
+                if is_activated {
+                    mark(&mut self.activated);
+                }
+            }
+            BoardSpace::Wall | BoardSpace::OutOfBounds => mark(&mut self.walls),
+        }
+    }
+
+    fn insert(&mut self, x: usize, y: usize, space: BoardSpace) {
+        self.mark(x, y, space, true);
+    }
+
+    fn remove(&mut self, x: usize, y: usize, space: BoardSpace) {
+        self.mark(x, y, space, false);
+    }
+}
+
+// The serialized form of a board, which leaves out the layers and hash
+#[derive(Deserialize)]
+struct BoardSpaces {
+    width: usize,
+    height: usize,
+    spaces: Vec<BoardSpace>,
+}
+
+impl TryFrom<BoardSpaces> for Board {
+    type Error = BoardError;
+
+    fn try_from(board: BoardSpaces) -> Result<Self, Self::Error> {
+        let BoardSpaces {
+            width,
+            height,
+            spaces,
+        } = board;
+        if height == 0 {
+            return Err(BoardError::NoRows);
+        }
+        if width == 0 {
+            return Err(BoardError::EmptyRows);
+        }
+        if width > MAX_BOARD_WIDTH {
+            return Err(BoardError::TooLarge {
+                dimension: Dimension::Width(width),
+                max: Dimension::Width(MAX_BOARD_WIDTH),
+            });
+        }
+        if height > MAX_BOARD_HEIGHT {
+            return Err(BoardError::TooLarge {
+                dimension: Dimension::Height(height),
+                max: Dimension::Height(MAX_BOARD_HEIGHT),
+            });
+        }
+        if spaces.len() != width * height {
+            return Err(BoardError::MismatchedRowLengths);
+        }
+        Ok(Board::from_spaces(width, height, spaces))
+    }
+}
+
 // This cannot be an array, because custom boards might be loaded at runtime.
-// Note that boards are padded due to some cards being difficult to place at
-// the boundaries of the board.
+// Coordinates count from the top-left space of the board.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "BoardSpaces")]
 pub struct Board {
     width: usize,
     height: usize,
     spaces: Vec<BoardSpace>,
+    // Boxed so that boards stay cheap to move around
+    #[serde(skip)]
+    layers: Box<Layers>,
+    // Zobrist hash of every space, updated as spaces change
+    #[serde(skip)]
+    hash: u64,
 }
 
 impl fmt::Display for Board {
@@ -274,9 +390,6 @@ impl fmt::Display for Board {
     }
 }
 
-const PADDING: usize = 7;
-const DOUBLE_PADDING: usize = PADDING * 2;
-
 impl Board {
     // Ensure that the given board meets the following criteria:
     // - all rows are the same length
@@ -284,7 +397,6 @@ impl Board {
     // - column height does not exceed the max board height
     // - board contains at least one row
     // - rows contain at least one space
-    // spaces is an unpadded board
     pub fn new(spaces: Vec<Vec<BoardSpace>>) -> Result<Self, BoardError> {
         if spaces.is_empty() {
             return Err(BoardError::NoRows);
@@ -310,18 +422,21 @@ impl Board {
         if spaces.iter().any(|row| row.len() != width) {
             return Err(BoardError::MismatchedRowLengths);
         }
-
-        // Need to add padding because some cards may be too difficult to place at board edges
-        let start_padding =
-            (0..(width + DOUBLE_PADDING) * PADDING).map(|_| BoardSpace::OutOfBounds);
-        let end_padding = start_padding.clone();
-        let padded_rows = spaces.iter().flat_map(|row| pad_row(row.iter().cloned()));
-        let padded_spaces = start_padding.chain(padded_rows).chain(end_padding);
-        Ok(Board {
-            width: width + DOUBLE_PADDING,
-            height: height + DOUBLE_PADDING,
-            spaces: padded_spaces.collect(),
-        })
+        Ok(Board::from_spaces(width, height, spaces.concat()))
+    }
+
+    fn from_spaces(width: usize, height: usize, spaces: Vec<BoardSpace>) -> Self {
+        let layers = Box::new(Layers::new(width, &spaces));
+        let hash = spaces.iter().enumerate().fold(0, |hash, (idx, space)| {
+            hash ^ zobrist::space_key(idx % width, idx / width, *space)
+        });
+        Board {
+            width,
+            height,
+            spaces,
+            layers,
+            hash,
+        }
     }
 
     pub fn width(&self) -> usize {
@@ -337,7 +452,38 @@ impl Board {
     }
 
     pub fn set_space(&mut self, position: &BoardPosition, space: BoardSpace) {
-        self.spaces[position.y() * self.width + position.x()] = space;
+        let (x, y) = (position.x(), position.y());
+        let old_space = std::mem::replace(&mut self.spaces[y * self.width + x], space);
+        self.layers.remove(x, y, old_space);
+        self.layers.insert(x, y, space);
+        self.hash ^= zobrist::space_key(x, y, old_space) ^ zobrist::space_key(x, y, space);
+    }
+
+    // Boards with the same spaces have the same hash, however they came about
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn empty_bits(&self) -> &BitBoard {
+        &self.layers.empty
+    }
+
+    // The player's ink spaces, including their special spaces
+    pub fn ink_bits(&self, player_num: PlayerNum) -> &BitBoard {
+        &self.layers.ink[player_idx(player_num)]
+    }
+
+    pub fn special_bits(&self, player_num: PlayerNum) -> &BitBoard {
+        &self.layers.special[player_idx(player_num)]
+    }
+
+    pub fn activated_bits(&self) -> &BitBoard {
+        &self.layers.activated
+    }
+
+    // Walls and out of bounds spaces, which can never be inked
+    pub fn wall_bits(&self) -> &BitBoard {
+        &self.layers.walls
     }
 
     pub fn get_space(&self, x: usize, y: usize) -> BoardSpace {
@@ -345,6 +491,9 @@ impl Board {
     }
 
     fn try_get_space(&self, x: usize, y: usize) -> Option<BoardSpace> {
+        if x >= self.width {
+            return None;
+        }
         let temp = usize::checked_mul(y, self.width)?;
         let idx = usize::checked_add(temp, x)?;
         let space = self.spaces.get(idx)?;
@@ -355,29 +504,17 @@ impl Board {
         &self,
         player_num: PlayerNum,
     ) -> Vec<(BoardPosition, BoardSpace)> {
-        self.spaces
-            .iter()
-            .enumerate()
-            .filter_map(move |(idx, s)| self.get_surrounded_inactive_special(player_num, idx, *s))
+        // A space is surrounded when it isn't next to any empty space
+        let inactive = self
+            .special_bits(player_num)
+            .without(&self.layers.activated);
+        inactive
+            .without(&self.layers.empty.neighborhood())
+            .positions()
+            .map(|(x, y)| (BoardPosition(x, y), self.get_space(x, y)))
             .collect()
     }
 
-    fn get_surrounded_inactive_special(
-        &self,
-        player_num: PlayerNum,
-        idx: usize,
-        space: BoardSpace,
-    ) -> Option<(BoardPosition, BoardSpace)> {
-        let x = idx % self.width;
-        let y = idx / self.width;
-        let board_pos = BoardPosition::new(self, x, y).unwrap();
-        if space.is_inactive_special(player_num) && board_pos.is_surrounded(self) {
-            Some((board_pos, space))
-        } else {
-            None
-        }
-    }
-
     pub fn set_ink(&mut self, ink_spaces: Vec<(BoardPosition, BoardSpace)>) {
         for (bp, s) in ink_spaces {
             self.set_space(&bp, s);
@@ -385,42 +522,40 @@ impl Board {
     }
 
     pub fn count_inked_spaces(&self, player_num: PlayerNum) -> u32 {
-        self.spaces
-            .iter()
-            .filter(|s| s.is_ink(player_num))
-            .fold(0, |acc, _| acc + 1)
+        self.ink_bits(player_num).count()
     }
 
-    // Calculate the absolute board position for a given base position and offsets
+    // Calculate the absolute board position for a given base position and offsets.
+    // The base position may be off the board, as long as the final position isn't.
     pub fn get_absolute_position(
         &self,
         // The number of spaces to the right of the board_x base position
         x_offset: usize,
         // The number of spaces down from the board_y base position
         y_offset: usize,
-        board_x: usize,
-        board_y: usize,
+        board_x: i32,
+        board_y: i32,
     ) -> Result<BoardPosition, BoardPositionError> {
-        let x = usize::checked_add(board_x, x_offset).ok_or(BoardPositionError::Overflow {
-            coordinate: Coordinate::X,
-            base: board_x,
-            offset: x_offset,
-        })?;
-        let y = usize::checked_add(board_y, y_offset).ok_or(BoardPositionError::Overflow {
-            coordinate: Coordinate::Y,
-            base: board_y,
-            offset: y_offset,
-        })?;
+        let offset = |coordinate: Coordinate, base: i32, offset: usize| {
+            let position = i32::try_from(offset)
+                .ok()
+                .and_then(|offset| base.checked_add(offset));
+            let Some(position) = position else {
+                return Err(BoardPositionError::Overflow {
+                    coordinate,
+                    base,
+                    offset,
+                });
+            };
+            usize::try_from(position)
+                .map_err(|_| BoardPositionError::Negative(coordinate, position))
+        };
+        let x = offset(Coordinate::X, board_x, x_offset)?;
+        let y = offset(Coordinate::Y, board_y, y_offset)?;
         BoardPosition::new(self, x, y)
     }
 }
 
-fn pad_row(row: impl Iterator<Item = BoardSpace>) -> impl Iterator<Item = BoardSpace> {
-    let initial_padding = (0..PADDING).map(|_| BoardSpace::OutOfBounds);
-    let end_padding = initial_padding.clone();
-    initial_padding.chain(row).chain(end_padding)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -594,13 +729,13 @@ mod tests {
     fn test_construct_board_position() {
         let empty = BoardSpace::Empty;
         let board = Board::new(vec![vec![empty, empty], vec![empty, empty]]).unwrap();
-        let outside_row = BoardPosition::new(&board, 16, 7);
+        let outside_row = BoardPosition::new(&board, 2, 0);
         assert!(outside_row.is_err());
-        let outside_col = BoardPosition::new(&board, 7, 16);
+        let outside_col = BoardPosition::new(&board, 0, 2);
         assert!(outside_col.is_err());
-        let outside_row_and_col = BoardPosition::new(&board, 16, 16);
+        let outside_row_and_col = BoardPosition::new(&board, 2, 2);
         assert!(outside_row_and_col.is_err());
-        let valid_pos = BoardPosition::new(&board, 15, 15);
+        let valid_pos = BoardPosition::new(&board, 1, 1);
         assert!(valid_pos.is_ok());
     }
 
@@ -609,7 +744,7 @@ mod tests {
         let oob = BoardSpace::OutOfBounds;
         let empty = BoardSpace::Empty;
         let board = Board::new(vec![vec![empty, empty], vec![empty, empty]]).unwrap();
-        let spaces = BoardPosition::new(&board, 7, 7)
+        let spaces = BoardPosition::new(&board, 0, 0)
             .unwrap()
             .surrounding_spaces(&board);
         assert_eq!(spaces[0], oob);
@@ -632,9 +767,9 @@ mod tests {
             vec![empty, empty, empty],
         ])
         .unwrap();
-        let surrounded_pos = BoardPosition::new(&board, 7, 7).unwrap();
+        let surrounded_pos = BoardPosition::new(&board, 0, 0).unwrap();
         assert!(surrounded_pos.is_surrounded(&board));
-        let not_surrounded_pos = BoardPosition::new(&board, 8, 7).unwrap();
+        let not_surrounded_pos = BoardPosition::new(&board, 1, 0).unwrap();
         assert!(!not_surrounded_pos.is_surrounded(&board));
     }
 
@@ -657,11 +792,11 @@ mod tests {
             vec![empty, empty, empty],
         ])
         .unwrap();
-        let pos1 = BoardPosition::new(&board, 7, 7).unwrap();
+        let pos1 = BoardPosition::new(&board, 0, 0).unwrap();
         assert!(pos1.adjacent_to_ink(&board, PlayerNum::P1));
-        let pos2 = BoardPosition::new(&board, 7, 9).unwrap();
+        let pos2 = BoardPosition::new(&board, 0, 2).unwrap();
         assert!(!pos2.adjacent_to_ink(&board, PlayerNum::P1));
-        let pos3 = BoardPosition::new(&board, 9, 9).unwrap();
+        let pos3 = BoardPosition::new(&board, 2, 2).unwrap();
         assert!(pos3.adjacent_to_ink(&board, PlayerNum::P1));
     }
 
@@ -688,13 +823,13 @@ mod tests {
             vec![empty, p2_special, empty],
         ])
         .unwrap();
-        let pos1 = BoardPosition::new(&board, 7, 7).unwrap();
+        let pos1 = BoardPosition::new(&board, 0, 0).unwrap();
         assert!(!pos1.adjacent_to_special(&board, PlayerNum::P1));
-        let pos2 = BoardPosition::new(&board, 7, 9).unwrap();
+        let pos2 = BoardPosition::new(&board, 0, 2).unwrap();
         assert!(!pos2.adjacent_to_special(&board, PlayerNum::P1));
-        let pos3 = BoardPosition::new(&board, 9, 9).unwrap();
+        let pos3 = BoardPosition::new(&board, 2, 2).unwrap();
         assert!(pos3.adjacent_to_special(&board, PlayerNum::P1));
-        let pos4 = BoardPosition::new(&board, 8, 7).unwrap();
+        let pos4 = BoardPosition::new(&board, 1, 0).unwrap();
         assert!(pos4.adjacent_to_special(&board, PlayerNum::P1));
     }
 
@@ -719,8 +854,8 @@ mod tests {
             Board::new(vec![vec![p1_special, p1_ink], vec![p1_ink, p1_ink]]).unwrap();
         let one_special = special_surrounded.get_surrounded_inactive_specials(PlayerNum::P1);
         assert_eq!(one_special.len(), 1);
-        assert_eq!(one_special[0].0.x(), 7);
-        assert_eq!(one_special[0].0.y(), 7);
+        assert_eq!(one_special[0].0.x(), 0);
+        assert_eq!(one_special[0].0.y(), 0);
 
         let off_by_one_board =
             Board::new(vec![vec![p1_special, p1_ink], vec![p1_ink, empty]]).unwrap();
@@ -731,17 +866,17 @@ mod tests {
             Board::new(vec![vec![p1_special, p1_ink], vec![p1_ink, p2_ink]]).unwrap();
         let one_special = enemy_ink_board.get_surrounded_inactive_specials(PlayerNum::P1);
         assert_eq!(one_special.len(), 1);
-        assert_eq!(one_special[0].0.x(), 7);
-        assert_eq!(one_special[0].0.y(), 7);
+        assert_eq!(one_special[0].0.x(), 0);
+        assert_eq!(one_special[0].0.y(), 0);
 
         let multiple_specials_board =
             Board::new(vec![vec![p1_special, p1_ink], vec![p1_ink, p1_special]]).unwrap();
         let two_specials = multiple_specials_board.get_surrounded_inactive_specials(PlayerNum::P1);
         assert_eq!(two_specials.len(), 2);
-        assert_eq!(two_specials[0].0.x(), 7);
-        assert_eq!(two_specials[0].0.y(), 7);
-        assert_eq!(two_specials[1].0.x(), 8);
-        assert_eq!(two_specials[1].0.y(), 8);
+        assert_eq!(two_specials[0].0.x(), 0);
+        assert_eq!(two_specials[0].0.y(), 0);
+        assert_eq!(two_specials[1].0.x(), 1);
+        assert_eq!(two_specials[1].0.y(), 1);
     }
 
     #[test]
@@ -778,11 +913,98 @@ mod tests {
     fn test_get_absolute_position() {
         let empty = BoardSpace::Empty;
         let board = Board::new(vec![vec![empty, empty], vec![empty, empty]]).unwrap();
-        let valid_pos = board.get_absolute_position(7, 7, 0, 0);
+        let valid_pos = board.get_absolute_position(1, 1, 0, 0);
         assert!(valid_pos.is_ok());
         assert_eq!(
             valid_pos.unwrap(),
-            BoardPosition::new(&board, 7, 7).unwrap()
+            BoardPosition::new(&board, 1, 1).unwrap()
         );
+        // The base position can be off the board
+        let from_outside = board.get_absolute_position(7, 6, -7, -5).unwrap();
+        assert_eq!(from_outside, BoardPosition::new(&board, 0, 1).unwrap());
+        assert!(matches!(
+            board.get_absolute_position(0, 7, -1, -7),
+            Err(BoardPositionError::Negative(Coordinate::X, -1))
+        ));
+        assert!(board.get_absolute_position(2, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_layers() {
+        let empty = BoardSpace::Empty;
+        let p1_special = BoardSpace::Special {
+            player_num: PlayerNum::P1,
+            is_activated: false,
+        };
+        let mut board = Board::new(vec![
+            vec![empty, empty, empty],
+            vec![empty, p1_special, empty],
+            vec![empty, empty, BoardSpace::Wall],
+        ])
+        .unwrap();
+        let position = BoardPosition::new(&board, 1, 1).unwrap();
+        assert!(board.special_bits(PlayerNum::P1).contains(1, 1));
+        assert!(board.wall_bits().contains(2, 2) && board.wall_bits().count() == 1);
+        assert_eq!(board.empty_bits().count(), 7);
+
+        // Replacing a space moves it between layers
+        let p2_ink = BoardSpace::Ink {
+            player_num: PlayerNum::P2,
+        };
+        board.set_space(&position, p2_ink);
+        assert!(board.special_bits(PlayerNum::P1).is_empty());
+        assert!(board.ink_bits(PlayerNum::P1).is_empty());
+        assert!(board.ink_bits(PlayerNum::P2).contains(1, 1));
+
+        // Layers aren't serialized, but are rebuilt from the spaces
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(!json.contains("layers"));
+        let deserialized: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, board);
+        assert_eq!(deserialized.hash(), board.hash());
+        let truncated = json.replace(r#""Empty","#, "");
+        assert!(serde_json::from_str::<Board>(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_empty_board() {
+        let no_rows = r#"{"width":3,"height":0,"spaces":[]}"#;
+        let err = serde_json::from_str::<Board>(no_rows).unwrap_err();
+        assert_eq!(err.to_string(), BoardError::NoRows.to_string());
+        let empty_rows = r#"{"width":0,"height":3,"spaces":[]}"#;
+        let err = serde_json::from_str::<Board>(empty_rows).unwrap_err();
+        assert_eq!(err.to_string(), BoardError::EmptyRows.to_string());
+    }
+
+    #[test]
+    fn test_hash() {
+        let empty = BoardSpace::Empty;
+        let board = Board::new(vec![vec![empty, empty], vec![empty, empty]]).unwrap();
+        let p1_ink = BoardSpace::Ink {
+            player_num: PlayerNum::P1,
+        };
+        let p2_ink = BoardSpace::Ink {
+            player_num: PlayerNum::P2,
+        };
+        let first = BoardPosition::new(&board, 0, 0).unwrap();
+        let second = BoardPosition::new(&board, 1, 1).unwrap();
+
+        // The same spaces inked in a different order, or overwritten along the way
+        let mut board1 = board.clone();
+        board1.set_ink(vec![(first, p1_ink), (second, p2_ink)]);
+        let mut board2 = board.clone();
+        board2.set_ink(vec![(second, p1_ink), (second, p2_ink), (first, p1_ink)]);
+        assert_eq!(board1.hash(), board2.hash());
+        assert_ne!(board1.hash(), board.hash());
+
+        let mut board3 = board1.clone();
+        board3.set_space(&second, p1_ink);
+        assert_ne!(board3.hash(), board1.hash());
+        board3.set_space(&second, p2_ink);
+        assert_eq!(board3.hash(), board1.hash());
+
+        // Matches the hash of a board built with those spaces to begin with
+        let built = Board::new(vec![vec![p1_ink, empty], vec![empty, p2_ink]]).unwrap();
+        assert_eq!(built.hash(), board1.hash());
     }
 }
@@ -1,4 +1,5 @@
-use serde::{Serialize, Deserialize};
+use crate::tableturf::input::{rotate_grid_ccw, Rotation};
+use serde::{Deserialize, Serialize};
 
 pub const CARD_WIDTH: usize = 8;
 
@@ -12,12 +13,125 @@ pub type CardSpace = Option<InkSpace>;
 
 pub type Grid = [[CardSpace; CARD_WIDTH]; CARD_WIDTH];
 
+// The smallest rectangle of the grid that holds all of a card's ink
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct BoundingBox {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+// What a card's ink looks like, worked out once when the card is made so it doesn't have
+// to be counted from the grid every time it's needed
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Shape {
+    size: u32,
+    bounds: BoundingBox,
+    trimmed: Grid,
+    // One bit per rotation that inks a differently shaped set of spaces than the ones
+    // before it
+    distinct_rotations: u8,
+    hash: u64,
+}
+
+impl Shape {
+    fn new(spaces: &Grid) -> Self {
+        let (bounds, trimmed) = trim(spaces);
+        let mut rotated = *spaces;
+        let mut seen = vec![];
+        let mut distinct_rotations = 0;
+        let mut hash = u64::MAX;
+        for turns in 0..Rotation::ALL.len() {
+            let (_, shape) = trim(&rotated);
+            if !seen.contains(&shape) {
+                distinct_rotations |= 1 << turns;
+                seen.push(shape);
+            }
+            hash = hash.min(hash_grid(&shape));
+            rotate_grid_ccw(&mut rotated);
+        }
+        Shape {
+            size: spaces.iter().flatten().flatten().count() as u32,
+            bounds,
+            trimmed,
+            distinct_rotations,
+            hash,
+        }
+    }
+}
+
+// The ink's bounding box, and the ink moved into the top-left corner of the grid
+fn trim(spaces: &Grid) -> (BoundingBox, Grid) {
+    let inked: Vec<(usize, usize)> = spaces
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, space)| space.is_some())
+                .map(move |(x, _)| (x, y))
+        })
+        .collect();
+    let xs = || inked.iter().map(|(x, _)| *x);
+    let ys = || inked.iter().map(|(_, y)| *y);
+    let (Some(left), Some(right), Some(top), Some(bottom)) =
+        (xs().min(), xs().max(), ys().min(), ys().max())
+    else {
+        return (BoundingBox::default(), *spaces);
+    };
+    let mut trimmed = [[None; CARD_WIDTH]; CARD_WIDTH];
+    for &(x, y) in &inked {
+        trimmed[y - top][x - left] = spaces[y][x];
+    }
+    let bounds = BoundingBox {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    };
+    (bounds, trimmed)
+}
+
+// FNV-1a rather than the standard library's hasher, so hashes can be stored and compared
+// between builds
+fn hash_grid(grid: &Grid) -> u64 {
+    grid.iter()
+        .flatten()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, space| {
+            let value = match space {
+                None => 0,
+                Some(InkSpace::Normal) => 1,
+                Some(InkSpace::Special) => 2,
+            };
+            (hash ^ value).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+// Cards are sent without their shape, which is worked out again when they're received
+#[derive(Deserialize)]
+struct CardSpec {
+    name: String,
+    priority: u32,
+    spaces: Grid,
+    special: u32,
+}
+
+impl From<CardSpec> for Card {
+    fn from(card: CardSpec) -> Self {
+        Card::new(card.name, card.priority, card.spaces, card.special)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "CardSpec")]
 pub struct Card {
     name: String,
     priority: u32,
     spaces: Grid,
     special: u32,
+    #[serde(skip)]
+    shape: Shape,
 }
 
 impl Card {
@@ -25,6 +139,7 @@ impl Card {
         Card {
             name,
             priority,
+            shape: Shape::new(&spaces),
             spaces,
             special,
         }
@@ -45,4 +160,129 @@ impl Card {
     pub fn special(&self) -> u32 {
         self.special
     }
+
+    // The number of spaces the card inks
+    pub fn size(&self) -> u32 {
+        self.shape.size
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.shape.bounds
+    }
+
+    // The card's ink moved into the top-left corner of its grid, filling the bounding
+    // box's width and height
+    pub fn trimmed(&self) -> Grid {
+        self.shape.trimmed
+    }
+
+    // The rotations that ink differently shaped spaces. Symmetric cards have fewer than
+    // four, e.g. a straight line looks the same after half a turn.
+    pub fn rotations(&self) -> impl Iterator<Item = Rotation> {
+        let distinct_rotations = self.shape.distinct_rotations;
+        Rotation::ALL
+            .into_iter()
+            .enumerate()
+            .filter(move |(turns, _)| distinct_rotations & 1 << turns != 0)
+            .map(|(_, rotation)| rotation)
+    }
+
+    // The same for every card with the same ink, whichever way round it's drawn or
+    // wherever it is in the grid. Cards with different ink hash differently unless
+    // they happen to collide.
+    pub fn shape_hash(&self) -> u64 {
+        self.shape.hash
+    }
+
+    // The space in the grid, as (x, y), that the card is placed and turned around. Like in
+    // the official game this is the middle of the ink, rounding towards the top-left when
+    // the ink is an even number of spaces across.
+    pub fn pivot(&self) -> (usize, usize) {
+        let BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        } = self.shape.bounds;
+        (
+            x + width.saturating_sub(1) / 2,
+            y + height.saturating_sub(1) / 2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pivot() {
+        let mut spaces: Grid = [[None; CARD_WIDTH]; CARD_WIDTH];
+        spaces[2][1] = Some(InkSpace::Normal);
+        spaces[2][4] = Some(InkSpace::Special);
+        spaces[5][3] = Some(InkSpace::Normal);
+        let card = Card::new("Test".to_string(), 1, spaces, 1);
+        // Four spaces across and four down, so the pivot rounds up and left
+        assert_eq!(card.pivot(), (2, 3));
+        spaces[2][5] = Some(InkSpace::Normal);
+        let card = Card::new("Test".to_string(), 1, spaces, 1);
+        assert_eq!(card.pivot(), (3, 3));
+    }
+
+    fn card(ink: &[(usize, usize)]) -> Card {
+        let mut spaces: Grid = [[None; CARD_WIDTH]; CARD_WIDTH];
+        for &(x, y) in ink {
+            spaces[y][x] = Some(InkSpace::Normal);
+        }
+        Card::new("Test".to_string(), 1, spaces, 1)
+    }
+
+    #[test]
+    fn test_shape() {
+        let l_shape = card(&[(3, 2), (3, 3), (3, 4), (4, 4)]);
+        assert_eq!(l_shape.size(), 4);
+        assert_eq!(
+            l_shape.bounding_box(),
+            BoundingBox {
+                x: 3,
+                y: 2,
+                width: 2,
+                height: 3
+            }
+        );
+        let trimmed = l_shape.trimmed();
+        assert!(trimmed[0][0].is_some() && trimmed[2][1].is_some());
+        assert_eq!(trimmed.iter().flatten().flatten().count(), 4);
+        assert_eq!(l_shape.rotations().count(), 4);
+
+        // Symmetric cards only have the rotations that look different
+        let line = card(&[(2, 3), (3, 3), (4, 3)]);
+        assert_eq!(
+            line.rotations().collect::<Vec<_>>(),
+            vec![Rotation::Zero, Rotation::One]
+        );
+        let square = card(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(square.rotations().collect::<Vec<_>>(), vec![Rotation::Zero]);
+        // Making a space special breaks the symmetry
+        let mut spaces = square.spaces();
+        spaces[0][0] = Some(InkSpace::Special);
+        let special_square = Card::new("Test".to_string(), 1, spaces, 1);
+        assert_eq!(special_square.rotations().count(), 4);
+    }
+
+    #[test]
+    fn test_shape_hash() {
+        let l_shape = card(&[(3, 2), (3, 3), (3, 4), (4, 4)]);
+        // Moved and rotated
+        let turned = card(&[(0, 0), (1, 0), (2, 0), (0, 1)]);
+        let mirrored = card(&[(3, 2), (3, 3), (3, 4), (2, 4)]);
+        assert_eq!(l_shape.shape_hash(), turned.shape_hash());
+        assert_ne!(l_shape.shape_hash(), mirrored.shape_hash());
+
+        // Shapes are worked out again when cards are deserialized
+        let json = serde_json::to_string(&l_shape).unwrap();
+        assert!(!json.contains("shape"));
+        let parsed: Card = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, l_shape);
+    }
 }
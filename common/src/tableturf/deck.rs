@@ -15,7 +15,7 @@ pub enum HandError {
     DuplicateCards([DeckIndex; HAND_SIZE]),
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum HandIndex {
     H1,
     H2,
@@ -23,7 +23,13 @@ pub enum HandIndex {
     H4,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl HandIndex {
+    // Every slot of the hand, in order
+    pub const ALL: [HandIndex; HAND_SIZE] =
+        [HandIndex::H1, HandIndex::H2, HandIndex::H3, HandIndex::H4];
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Hand([DeckIndex; HAND_SIZE]);
 
 impl Index<HandIndex> for Hand {
@@ -51,7 +57,7 @@ impl IndexMut<HandIndex> for Hand {
 
 impl Hand {
     pub fn new(hand: [DeckIndex; HAND_SIZE]) -> Result<Self, HandError> {
-        let deduped: HashSet<DeckIndex> = HashSet::from_iter(hand.into_iter());
+        let deduped: HashSet<DeckIndex> = HashSet::from_iter(hand);
         if deduped.len() != HAND_SIZE {
             return Err(HandError::DuplicateCards(hand));
         }
@@ -173,6 +179,10 @@ impl Deck {
         }
     }
 
+    pub(crate) fn set_drawable(&mut self, index: DeckIndex, drawable: bool) {
+        self.card_states[idx_to_usize(index)] = drawable;
+    }
+
     pub fn cards(&self) -> &[Card; DECK_SIZE] {
         &self.cards
     }
@@ -0,0 +1,537 @@
+// A reinforcement learning environment over the rules engine, with a reset/step interface.
+// Observations and actions are from the acting player's point of view. Player 2's view is
+// rotated half a turn, so that both players start at the bottom of the board and a single
+// policy can play either seat.
+use crate::tableturf::ai::{legal_inputs, Agent, GreedyAgent, RandomAgent};
+use crate::tableturf::board::{Board, BoardSpace, MAX_BOARD_HEIGHT, MAX_BOARD_WIDTH};
+use crate::tableturf::card::{Card, InkSpace, CARD_WIDTH};
+use crate::tableturf::deck::{HandIndex, DECK_SIZE, HAND_SIZE};
+use crate::tableturf::game_state::{
+    default_board, default_deck, DeckRng, GameState, Outcome, DEFAULT_TURNS,
+};
+use crate::tableturf::input::{Action, RawInput, RawPlacement, Rotation, ValidInput};
+use crate::tableturf::player::PlayerNum;
+use thiserror::Error;
+
+// Planes of the board, each MAX_BOARD_HEIGHT x MAX_BOARD_WIDTH: own ink (including special
+// spaces), own special spaces, opponent ink, opponent special spaces, activated special
+// spaces and spaces that can't be inked. Boards smaller than the maximum are in the
+// top-left corner, with the rest of the planes marked as spaces that can't be inked.
+pub const BOARD_PLANES: usize = 6;
+pub const BOARD_FEATURES: usize = BOARD_PLANES * MAX_BOARD_HEIGHT * MAX_BOARD_WIDTH;
+// Planes of each card in hand, each CARD_WIDTH x CARD_WIDTH and unrotated: ink and special spaces
+pub const CARD_PLANES: usize = 2;
+pub const HAND_FEATURES: usize = HAND_SIZE * CARD_PLANES * CARD_WIDTH * CARD_WIDTH;
+// Own special, opponent special and turns left, then each hand card's special cost,
+// priority and number of spaces
+pub const SCALAR_FEATURES: usize = 3 + HAND_SIZE * 3;
+
+// Placements are numbered by the space the card's pivot goes on, which is always on the
+// board for legal placements
+const PLACEMENT_WIDTH: usize = MAX_BOARD_WIDTH;
+const PLACEMENT_HEIGHT: usize = MAX_BOARD_HEIGHT;
+// For each card in hand: passing it, then placing it at every position and rotation,
+// without and then with a special attack
+const ACTIONS_PER_CARD: usize = 1 + 2 * Rotation::ALL.len() * PLACEMENT_HEIGHT * PLACEMENT_WIDTH;
+pub const ACTIONS: usize = HAND_SIZE * ACTIONS_PER_CARD;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EnvError {
+    #[error("Action {0} is not in the action space")]
+    OutOfRange(usize),
+    #[error("Action {action} is illegal: {reason}")]
+    Illegal { action: usize, reason: String },
+    #[error("The game is over. Reset to start a new one")]
+    GameOver,
+    #[error("Both seats are played by the caller in self-play")]
+    SelfPlay,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Opponent {
+    // The caller plays both seats with step_both
+    SelfPlay,
+    Random,
+    Greedy,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnvConfig {
+    pub board: Board,
+    pub decks: [[Card; DECK_SIZE]; 2],
+    pub turns: u32,
+    pub opponent: Opponent,
+    // The seat played by the caller when playing against an opponent
+    pub seat: PlayerNum,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            board: default_board(),
+            decks: [default_deck(), default_deck()],
+            turns: DEFAULT_TURNS,
+            opponent: Opponent::Random,
+            seat: PlayerNum::P1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub board: Vec<f32>,
+    pub hand: Vec<f32>,
+    pub scalars: Vec<f32>,
+    // Whether each action is legal
+    pub mask: Vec<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    // 1 for a win, -1 for a loss and 0 for a draw once the game is over, and 0 until then
+    pub reward: f32,
+    pub done: bool,
+}
+
+fn other(player_num: PlayerNum) -> PlayerNum {
+    match player_num {
+        PlayerNum::P1 => PlayerNum::P2,
+        PlayerNum::P2 => PlayerNum::P1,
+    }
+}
+
+fn rotation_index(rotation: Rotation) -> usize {
+    Rotation::ALL.iter().position(|r| *r == rotation).unwrap()
+}
+
+// Player 2 sees the board rotated half a turn. Cards turn around their pivot, so rotating
+// a card by the same amount keeps its spaces in place and only the pivot's space and the
+// rotation of placements change.
+fn flip_placement(placement: &RawPlacement, board: &Board) -> RawPlacement {
+    RawPlacement {
+        x: board.width() as i32 - 1 - placement.x,
+        y: board.height() as i32 - 1 - placement.y,
+        special_activated: placement.special_activated,
+        rotation: Rotation::ALL[(rotation_index(placement.rotation) + 2) % Rotation::ALL.len()],
+    }
+}
+
+// Turns an action index into the input it stands for on the given board, or None if it's
+// outside the action space or off the board
+pub fn decode_action(action: usize, board: &Board, player_num: PlayerNum) -> Option<RawInput> {
+    if action >= ACTIONS {
+        return None;
+    }
+    let hand_idx = HandIndex::ALL[action / ACTIONS_PER_CARD];
+    let placement = match (action % ACTIONS_PER_CARD).checked_sub(1) {
+        None => {
+            return Some(RawInput {
+                hand_idx,
+                action: Action::Pass,
+            })
+        }
+        Some(placement) => placement,
+    };
+    let x = placement % PLACEMENT_WIDTH;
+    let y = placement / PLACEMENT_WIDTH % PLACEMENT_HEIGHT;
+    let rotation = placement / (PLACEMENT_WIDTH * PLACEMENT_HEIGHT) % Rotation::ALL.len();
+    let special_activated =
+        placement / (PLACEMENT_WIDTH * PLACEMENT_HEIGHT * Rotation::ALL.len()) == 1;
+    if x >= board.width() || y >= board.height() {
+        return None;
+    }
+    let mut placement = RawPlacement {
+        x: x as i32,
+        y: y as i32,
+        special_activated,
+        rotation: Rotation::ALL[rotation],
+    };
+    if player_num == PlayerNum::P2 {
+        placement = flip_placement(&placement, board);
+    }
+    Some(RawInput {
+        hand_idx,
+        action: Action::Place(placement),
+    })
+}
+
+// The action index of an input on the given board, or None if it's off the board
+pub fn encode_action(input: &RawInput, board: &Board, player_num: PlayerNum) -> Option<usize> {
+    let hand = HandIndex::ALL
+        .iter()
+        .position(|h| *h == input.hand_idx)
+        .unwrap();
+    let placement = match &input.action {
+        Action::Pass => return Some(hand * ACTIONS_PER_CARD),
+        Action::Place(placement) => placement,
+    };
+    let placement = match player_num {
+        PlayerNum::P1 => placement.clone(),
+        PlayerNum::P2 => flip_placement(placement, board),
+    };
+    let x = usize::try_from(placement.x).ok()?;
+    let y = usize::try_from(placement.y).ok()?;
+    if x >= PLACEMENT_WIDTH || y >= PLACEMENT_HEIGHT {
+        return None;
+    }
+    let index = ((usize::from(placement.special_activated) * Rotation::ALL.len()
+        + rotation_index(placement.rotation))
+        * PLACEMENT_HEIGHT
+        + y)
+        * PLACEMENT_WIDTH
+        + x;
+    Some(hand * ACTIONS_PER_CARD + 1 + index)
+}
+
+pub struct Env {
+    config: EnvConfig,
+    game_state: GameState<DeckRng>,
+    opponent: Option<Box<dyn Agent + Send + Sync>>,
+}
+
+impl Env {
+    // Creates an environment and starts its first game
+    pub fn new(config: EnvConfig, seed: u64) -> Self {
+        let game_state = GameState::with_stage(
+            config.board.clone(),
+            config.decks.clone(),
+            config.turns,
+            DeckRng::from_seed(seed),
+        );
+        let mut env = Env {
+            config,
+            game_state,
+            opponent: None,
+        };
+        // Sets up the opponent, dealing the same cards again
+        env.reset(seed);
+        env
+    }
+
+    // Starts a new game. The same seed always deals the same cards, and an opponent that
+    // makes random choices makes the same ones as long as it's given the same moves.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game_state = GameState::with_stage(
+            self.config.board.clone(),
+            self.config.decks.clone(),
+            self.config.turns,
+            DeckRng::from_seed(seed),
+        );
+        self.opponent = match self.config.opponent {
+            Opponent::SelfPlay => None,
+            Opponent::Random => Some(Box::new(RandomAgent::from_seed(seed.wrapping_add(1)))),
+            Opponent::Greedy => Some(Box::new(GreedyAgent)),
+        };
+        let opponent_num = other(self.config.seat);
+        if let Some(opponent) = &mut self.opponent {
+            let board = self.game_state.board();
+            if opponent.redraw(board, self.game_state.player(opponent_num)) {
+                self.game_state.redraw_hand(opponent_num);
+            }
+        }
+        self.observe(self.config.seat)
+    }
+
+    pub fn game_state(&self) -> &GameState<DeckRng> {
+        &self.game_state
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.game_state.turns_left() == 0
+    }
+
+    pub fn observe(&self, player_num: PlayerNum) -> Observation {
+        Observation {
+            board: self.board_features(player_num),
+            hand: self.hand_features(player_num),
+            scalars: self.scalar_features(player_num),
+            mask: self.action_mask(player_num),
+        }
+    }
+
+    pub fn action_mask(&self, player_num: PlayerNum) -> Vec<bool> {
+        let mut mask = vec![false; ACTIONS];
+        if self.is_done() {
+            return mask;
+        }
+        let board = self.game_state.board();
+        for (input, _) in legal_inputs(board, self.game_state.player(player_num)) {
+            if let Some(action) = encode_action(&input, board, player_num) {
+                mask[action] = true;
+            }
+        }
+        mask
+    }
+
+    fn board_features(&self, player_num: PlayerNum) -> Vec<f32> {
+        let board = self.game_state.board();
+        let plane_size = MAX_BOARD_HEIGHT * MAX_BOARD_WIDTH;
+        let mut features = vec![0.0; BOARD_FEATURES];
+        // Everything starts off as unplayable, until it's found to be on the board
+        features[(BOARD_PLANES - 1) * plane_size..].fill(1.0);
+        let width = board.width();
+        let height = board.height();
+        for y in 0..height {
+            for x in 0..width {
+                let space = board.get_space(x, y);
+                let (view_x, view_y) = match player_num {
+                    PlayerNum::P1 => (x, y),
+                    PlayerNum::P2 => (width - 1 - x, height - 1 - y),
+                };
+                let cell = view_y * MAX_BOARD_WIDTH + view_x;
+                let mut set = |plane: usize, value: bool| {
+                    features[plane * plane_size + cell] = if value { 1.0 } else { 0.0 };
+                };
+                let opponent = other(player_num);
+                set(0, space.is_ink(player_num));
+                set(1, space.is_special(player_num));
+                set(2, space.is_ink(opponent));
+                set(3, space.is_special(opponent));
+                set(
+                    4,
+                    matches!(
+                        space,
+                        BoardSpace::Special {
+                            is_activated: true,
+                            ..
+                        }
+                    ),
+                );
+                set(
+                    5,
+                    matches!(space, BoardSpace::Wall | BoardSpace::OutOfBounds),
+                );
+            }
+        }
+        features
+    }
+
+    fn hand_features(&self, player_num: PlayerNum) -> Vec<f32> {
+        let player = self.game_state.player(player_num);
+        let mut features = Vec::with_capacity(HAND_FEATURES);
+        for hand_idx in HandIndex::ALL {
+            let spaces = player.get_card(hand_idx).spaces();
+            for ink_space in [InkSpace::Normal, InkSpace::Special] {
+                features.extend(spaces.iter().flatten().map(|space| {
+                    if *space == Some(ink_space) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }));
+            }
+        }
+        features
+    }
+
+    fn scalar_features(&self, player_num: PlayerNum) -> Vec<f32> {
+        let player = self.game_state.player(player_num);
+        let mut features = vec![
+            player.special as f32,
+            self.game_state.player(other(player_num)).special as f32,
+            self.game_state.turns_left() as f32,
+        ];
+        for hand_idx in HandIndex::ALL {
+            let card = player.get_card(hand_idx);
+            features.extend([
+                card.special() as f32,
+                card.priority() as f32,
+                card.size() as f32,
+            ]);
+        }
+        features
+    }
+
+    fn validate(&self, action: usize, player_num: PlayerNum) -> Result<ValidInput, EnvError> {
+        let board = self.game_state.board();
+        let input = decode_action(action, board, player_num).ok_or(EnvError::OutOfRange(action))?;
+        ValidInput::new(input, board, self.game_state.player(player_num)).map_err(|err| {
+            EnvError::Illegal {
+                action,
+                reason: err.to_string(),
+            }
+        })
+    }
+
+    fn reward(&self, player_num: PlayerNum) -> f32 {
+        if !self.is_done() {
+            return 0.0;
+        }
+        match (self.game_state.check_winner(), player_num) {
+            (Outcome::Draw, _) => 0.0,
+            (Outcome::P1Win, PlayerNum::P1) | (Outcome::P2Win, PlayerNum::P2) => 1.0,
+            _ => -1.0,
+        }
+    }
+
+    fn step_for(&self, player_num: PlayerNum) -> Step {
+        Step {
+            observation: self.observe(player_num),
+            reward: self.reward(player_num),
+            done: self.is_done(),
+        }
+    }
+
+    // Plays the caller's action against the opponent
+    pub fn step(&mut self, action: usize) -> Result<Step, EnvError> {
+        if self.is_done() {
+            return Err(EnvError::GameOver);
+        }
+        if self.opponent.is_none() {
+            return Err(EnvError::SelfPlay);
+        }
+        let seat = self.config.seat;
+        let opponent_num = other(seat);
+        let input = self.validate(action, seat)?;
+        let opponent = self.opponent.as_mut().unwrap();
+        let board = self.game_state.board();
+        let opponent_player = self.game_state.player(opponent_num);
+        let opponent_input = opponent.choose(board, opponent_player);
+        // Agents only pick from the legal inputs, so anything else is a bug in the agent
+        let opponent_input = ValidInput::new(opponent_input, board, opponent_player)
+            .expect("opponent chose an illegal input");
+        match seat {
+            PlayerNum::P1 => self.game_state.update(input, opponent_input),
+            PlayerNum::P2 => self.game_state.update(opponent_input, input),
+        };
+        Ok(self.step_for(seat))
+    }
+
+    // Plays both seats' actions, Player 1's first. Each seat's action is from its own
+    // point of view, like its observation.
+    pub fn step_both(&mut self, actions: [usize; 2]) -> Result<[Step; 2], EnvError> {
+        if self.is_done() {
+            return Err(EnvError::GameOver);
+        }
+        let input1 = self.validate(actions[0], PlayerNum::P1)?;
+        let input2 = self.validate(actions[1], PlayerNum::P2)?;
+        self.game_state.update(input1, input2);
+        Ok([PlayerNum::P1, PlayerNum::P2].map(|player_num| self.step_for(player_num)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_legal(observation: &Observation) -> usize {
+        observation.mask.iter().position(|legal| *legal).unwrap()
+    }
+
+    #[test]
+    fn test_action_encoding() {
+        let env = Env::new(EnvConfig::default(), 0);
+        let board = env.game_state().board();
+        for player_num in [PlayerNum::P1, PlayerNum::P2] {
+            let inputs = legal_inputs(board, env.game_state().player(player_num));
+            let mask = env.action_mask(player_num);
+            assert_eq!(mask.iter().filter(|legal| **legal).count(), inputs.len());
+            for (input, _) in inputs {
+                let action = encode_action(&input, board, player_num).unwrap();
+                let decoded = decode_action(action, board, player_num).unwrap();
+                assert_eq!(
+                    serde_json::to_string(&decoded).unwrap(),
+                    serde_json::to_string(&input).unwrap()
+                );
+            }
+        }
+        assert!(decode_action(ACTIONS, board, PlayerNum::P1).is_none());
+    }
+
+    #[test]
+    fn test_observation() {
+        let env = Env::new(EnvConfig::default(), 1);
+        let p1 = env.observe(PlayerNum::P1);
+        let p2 = env.observe(PlayerNum::P2);
+        assert_eq!(p1.board.len(), BOARD_FEATURES);
+        assert_eq!(p1.hand.len(), HAND_FEATURES);
+        assert_eq!(p1.scalars.len(), SCALAR_FEATURES);
+        assert_eq!(p1.mask.len(), ACTIONS);
+        // The default board is symmetric, so both players see the same board at the start
+        assert_eq!(p1.board, p2.board);
+        let plane_size = MAX_BOARD_HEIGHT * MAX_BOARD_WIDTH;
+        let own_special = &p1.board[plane_size..2 * plane_size];
+        assert_eq!(own_special.iter().sum::<f32>(), 1.0);
+        // Only the default board's 9 columns and 26 rows are playable
+        let unplayable = &p1.board[5 * plane_size..];
+        assert_eq!(
+            unplayable.iter().filter(|space| **space == 0.0).count(),
+            9 * 26
+        );
+    }
+
+    #[test]
+    fn test_seeding() {
+        let config = EnvConfig::default();
+        let mut env1 = Env::new(config.clone(), 3);
+        let mut env2 = Env::new(config, 4);
+        assert_ne!(env1.observe(PlayerNum::P1), env2.observe(PlayerNum::P1));
+        let observation = env2.reset(3);
+        assert_eq!(env1.observe(PlayerNum::P1), observation);
+        let mut observation = observation;
+        while !env1.is_done() {
+            let action = first_legal(&observation);
+            let step1 = env1.step(action).unwrap();
+            let step2 = env2.step(action).unwrap();
+            assert_eq!(step1, step2);
+            observation = step1.observation;
+        }
+    }
+
+    #[test]
+    fn test_play_against_opponent() {
+        let config = EnvConfig {
+            turns: 4,
+            opponent: Opponent::Greedy,
+            seat: PlayerNum::P2,
+            ..EnvConfig::default()
+        };
+        let mut env = Env::new(config, 2);
+        let mut observation = env.reset(2);
+        assert_eq!(env.step(ACTIONS), Err(EnvError::OutOfRange(ACTIONS)));
+        let mut step = None;
+        for _ in 0..4 {
+            let next = env.step(first_legal(&observation)).unwrap();
+            observation = next.observation.clone();
+            step = Some(next);
+        }
+        let step = step.unwrap();
+        assert!(step.done);
+        assert!([-1.0, 0.0, 1.0].contains(&step.reward));
+        assert!(step.observation.mask.iter().all(|legal| !legal));
+        assert_eq!(env.step(0), Err(EnvError::GameOver));
+    }
+
+    #[test]
+    fn test_self_play() {
+        let config = EnvConfig {
+            turns: 3,
+            opponent: Opponent::SelfPlay,
+            ..EnvConfig::default()
+        };
+        let mut env = Env::new(config, 5);
+        assert_eq!(env.step(0), Err(EnvError::SelfPlay));
+        let mut observations = [env.observe(PlayerNum::P1), env.observe(PlayerNum::P2)];
+        // Most placements are illegal at the start, when there's little ink to place next to
+        let illegal = observations[0]
+            .mask
+            .iter()
+            .position(|legal| !legal)
+            .unwrap();
+        assert!(matches!(
+            env.step_both([illegal, 0]),
+            Err(EnvError::Illegal { .. })
+        ));
+        loop {
+            let actions = [first_legal(&observations[0]), first_legal(&observations[1])];
+            let [step1, step2] = env.step_both(actions).unwrap();
+            assert_eq!(step1.reward, -step2.reward);
+            if step1.done {
+                break;
+            }
+            observations = [step1.observation, step2.observation];
+        }
+        assert_eq!(env.game_state().turns_left(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+// Heuristics for judging a position before the game is over. None of these look at the
+// cards in hand, only at how the ink on the board is laid out, so they're cheap enough
+// for bots to call on every candidate move and for the frontend to show after every turn.
+use crate::tableturf::bitboard::BitBoard;
+use crate::tableturf::board::Board;
+use crate::tableturf::player::PlayerNum;
+use serde::{Deserialize, Serialize};
+
+const PLAYERS: [PlayerNum; 2] = [PlayerNum::P1, PlayerNum::P2];
+
+// How many spaces of projected margin make a win about 73% likely. This is a guess rather
+// than something fitted to real games.
+const WIN_PROBABILITY_SCALE: f64 = 4.0;
+
+// Player 1's numbers first in every field
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    pub ink: [u32; 2],
+    // Empty spaces each player gets to first
+    pub territory: [u32; 2],
+    // Empty spaces both players get to at the same time
+    pub contested: u32,
+    // Inactive specials one empty space away from being surrounded, then two away
+    pub nearly_surrounded: [[u32; 2]; 2],
+    pub projected: [f64; 2],
+}
+
+impl Evaluation {
+    // The chance that the player ends up with more ink, from the projected margin
+    pub fn win_probability(&self, player_num: PlayerNum) -> f64 {
+        let margin = match player_num {
+            PlayerNum::P1 => self.projected[0] - self.projected[1],
+            PlayerNum::P2 => self.projected[1] - self.projected[0],
+        };
+        1.0 / (1.0 + (-margin / WIN_PROBABILITY_SCALE).exp())
+    }
+}
+
+impl Board {
+    // The player's ink that's next to an empty space, which is where cards can still be
+    // placed from
+    pub fn frontier(&self, player_num: PlayerNum) -> BitBoard {
+        let ink = *self.ink_bits(player_num);
+        ink & self.empty_bits().neighborhood()
+    }
+
+    // Every empty space connected to the player's frontier through other empty spaces.
+    // Special attacks can ink over walls of enemy ink, so this is a lower bound.
+    pub fn reachable_spaces(&self, player_num: PlayerNum) -> BitBoard {
+        let empty = self.empty_bits();
+        let start = self.frontier(player_num).neighborhood() & *empty;
+        start.fill(empty)
+    }
+
+    // Splits the empty spaces by which player's ink they're closest to, walking outwards
+    // from both frontiers at once. Returns each player's territory and the spaces both
+    // players reach in the same step, which neither side walks through.
+    pub fn territory(&self) -> ([BitBoard; 2], BitBoard) {
+        let empty = *self.empty_bits();
+        let mut claimed = PLAYERS.map(|player_num| self.frontier(player_num));
+        let mut contested = BitBoard::EMPTY;
+        loop {
+            let taken = claimed[0] | claimed[1] | contested;
+            let [grown1, grown2] =
+                claimed.map(|bits| (bits.neighborhood() & empty).without(&taken));
+            if grown1.is_empty() && grown2.is_empty() {
+                break;
+            }
+            let both = grown1 & grown2;
+            claimed[0] |= grown1.without(&both);
+            claimed[1] |= grown2.without(&both);
+            contested |= both;
+        }
+        let territory = [0, 1].map(|i| claimed[i] & empty);
+        (territory, contested)
+    }
+
+    // The contested spaces in groups that are connected to each other, biggest first
+    pub fn contested_regions(&self) -> Vec<BitBoard> {
+        let (_, mut contested) = self.territory();
+        let mut regions = vec![];
+        while !contested.is_empty() {
+            let (x, y) = contested.positions().next().unwrap();
+            let mut start = BitBoard::EMPTY;
+            start.insert(x, y);
+            let region = start.fill(&contested);
+            contested = contested.without(&region);
+            regions.push(region);
+        }
+        regions.sort_by_key(|region| std::cmp::Reverse(region.count()));
+        regions
+    }
+
+    // The player's inactive specials with one empty space left around them, then the
+    // ones with two. See BoardPosition::is_surrounded.
+    pub fn nearly_surrounded_specials(&self, player_num: PlayerNum) -> [u32; 2] {
+        let inactive = self.special_bits(player_num).without(self.activated_bits());
+        let mut counts = [0, 0];
+        for (x, y) in inactive.positions() {
+            let mut special = BitBoard::EMPTY;
+            special.insert(x, y);
+            match (special.neighborhood() & *self.empty_bits()).count() {
+                1 => counts[0] += 1,
+                2 => counts[1] += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+
+    // Ink so far plus the territory each player is likely to fill. Contested spaces are
+    // split evenly.
+    pub fn projected_score(&self) -> [f64; 2] {
+        self.evaluate().projected
+    }
+
+    pub fn evaluate(&self) -> Evaluation {
+        let (territory, contested) = self.territory();
+        let ink = PLAYERS.map(|player_num| self.count_inked_spaces(player_num));
+        let territory = territory.map(|bits| bits.count());
+        let contested = contested.count();
+        let projected = [0, 1].map(|i| (ink[i] + territory[i]) as f64 + contested as f64 / 2.0);
+        Evaluation {
+            ink,
+            territory,
+            contested,
+            nearly_surrounded: PLAYERS
+                .map(|player_num| self.nearly_surrounded_specials(player_num)),
+            projected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::board::BoardSpace;
+    use crate::tableturf::game_state::default_board;
+
+    const EE: BoardSpace = BoardSpace::Empty;
+    const WW: BoardSpace = BoardSpace::Wall;
+    const I1: BoardSpace = BoardSpace::Ink {
+        player_num: PlayerNum::P1,
+    };
+    const I2: BoardSpace = BoardSpace::Ink {
+        player_num: PlayerNum::P2,
+    };
+    const S1: BoardSpace = BoardSpace::Special {
+        player_num: PlayerNum::P1,
+        is_activated: false,
+    };
+
+    fn bits(positions: &[(usize, usize)]) -> BitBoard {
+        let mut bits = BitBoard::EMPTY;
+        for (x, y) in positions {
+            bits.insert(*x, *y);
+        }
+        bits
+    }
+
+    #[test]
+    fn test_reachable_spaces() {
+        // Player 1 is walled into the left column
+        let board = Board::new(vec![
+            vec![EE, WW, EE, EE],
+            vec![I1, WW, EE, I2],
+            vec![EE, WW, EE, EE],
+        ])
+        .unwrap();
+        let reachable = board.reachable_spaces(PlayerNum::P1);
+        assert_eq!(reachable, bits(&[(0, 0), (0, 2)]));
+        assert_eq!(board.reachable_spaces(PlayerNum::P2).count(), 5);
+        assert_eq!(board.frontier(PlayerNum::P1), bits(&[(0, 1)]));
+    }
+
+    #[test]
+    fn test_territory() {
+        let board = Board::new(vec![vec![I1, EE, EE, EE, EE, EE, I2]]).unwrap();
+        let ([territory1, territory2], contested) = board.territory();
+        assert_eq!(territory1, bits(&[(1, 0), (2, 0)]));
+        assert_eq!(territory2, bits(&[(4, 0), (5, 0)]));
+        assert_eq!(contested, bits(&[(3, 0)]));
+        assert_eq!(board.contested_regions(), vec![bits(&[(3, 0)])]);
+
+        let evaluation = board.evaluate();
+        assert_eq!(evaluation.projected, [3.5, 3.5]);
+        assert_eq!(evaluation.win_probability(PlayerNum::P1), 0.5);
+
+        // Contested spaces are only grouped together when they're connected
+        let board = Board::new(vec![
+            vec![I1, EE, EE, EE, I2],
+            vec![I1, WW, WW, WW, I2],
+            vec![I1, EE, EE, EE, I2],
+        ])
+        .unwrap();
+        let ([territory1, territory2], _) = board.territory();
+        assert_eq!(territory1, bits(&[(1, 0), (1, 2)]));
+        assert_eq!(territory2.count(), 2);
+        assert_eq!(
+            board.contested_regions(),
+            vec![bits(&[(2, 0)]), bits(&[(2, 2)])]
+        );
+    }
+
+    #[test]
+    fn test_nearly_surrounded_specials() {
+        let board = Board::new(vec![
+            vec![S1, I1, WW, S1],
+            vec![I2, EE, EE, EE],
+            vec![WW, WW, WW, EE],
+        ])
+        .unwrap();
+        assert_eq!(board.nearly_surrounded_specials(PlayerNum::P1), [1, 1]);
+        assert_eq!(board.nearly_surrounded_specials(PlayerNum::P2), [0, 0]);
+    }
+
+    #[test]
+    fn test_evaluate_default_board() {
+        let evaluation = default_board().evaluate();
+        assert_eq!(evaluation.ink, [1, 1]);
+        assert_eq!(evaluation.projected[0], evaluation.projected[1]);
+        let empty = default_board().empty_bits().count();
+        let [territory1, territory2] = evaluation.territory;
+        assert_eq!(territory1 + territory2 + evaluation.contested, empty);
+        assert_eq!(evaluation.nearly_surrounded, [[0, 0], [0, 0]]);
+    }
+}
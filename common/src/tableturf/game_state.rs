@@ -1,15 +1,19 @@
 use crate::tableturf::board::{Board, BoardPosition, BoardSpace};
 use crate::tableturf::card::{Card, CardSpace, InkSpace};
-use crate::tableturf::deck::{Deck, DeckIndex, DrawRng, Hand, HandIndex};
+use crate::tableturf::deck::{
+    idx_to_usize, parse_idx, Deck, DeckIndex, DrawRng, Hand, HandIndex, DECK_SIZE,
+};
 use crate::tableturf::input::{Input, Placement, ValidInput};
 use crate::tableturf::player::{Player, PlayerNum, Players};
+use crate::tableturf::zobrist;
 use rand::prelude::IteratorRandom;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DeckRng {
     rng: StdRng,
 }
@@ -21,6 +25,15 @@ impl Default for DeckRng {
     }
 }
 
+impl DeckRng {
+    // Creates an RNG that always draws the same cards, for reproducible games
+    pub fn from_seed(seed: u64) -> Self {
+        DeckRng {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
 impl DrawRng for DeckRng {
     fn draw<T, I: Iterator<Item = T> + Sized>(&mut self, iter: I) -> Option<T> {
         iter.choose(&mut self.rng)
@@ -32,22 +45,72 @@ impl DrawRng for DeckRng {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
 pub enum Outcome {
     P1Win,
     P2Win,
     Draw,
 }
 
-#[derive(Debug)]
+// A card played on a turn. Cards that aren't placed are passed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayReport {
+    pub card: Card,
+    pub placed: bool,
+    pub special_activated: bool,
+    // Spaces of the placement the player still has inked at the end of the turn
+    pub inked: u32,
+}
+
+impl PlayReport {
+    fn new(card: &Card) -> Self {
+        PlayReport {
+            card: card.clone(),
+            placed: false,
+            special_activated: false,
+            inked: 0,
+        }
+    }
+
+    // Returns the spaces the placement inks, so that they can be checked once the turn is over
+    fn place(&mut self, placement: &Placement) -> Vec<BoardPosition> {
+        self.placed = true;
+        self.special_activated = placement.is_special_activated();
+        placement.ink_spaces().0.iter().map(|(bp, _)| *bp).collect()
+    }
+}
+
+// Both players placed the same kind of ink on some of the same spaces, which the cards'
+// priorities decide. Special ink over normal ink always goes to the special's owner, so
+// it isn't counted.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub spaces: u32,
+    // The player whose card took priority, or None if both cards had the same priority
+    pub winner: Option<PlayerNum>,
+}
+
+// What happened on a turn, for analysing games
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TurnReport {
+    // Player 1's play first
+    pub plays: [PlayReport; 2],
+    pub conflict: Option<Conflict>,
+}
+
+#[derive(Clone, Debug)]
 pub struct GameState<R: Debug> {
     board: Board,
     players: Players,
     turns_left: u32,
     rng: R,
+    // Zobrist keys of the players and the turns left, updated as they change. The board
+    // keeps its own part.
+    hash: u64,
 }
 
-fn default_deck() -> [Card; 15] {
+// The starter deck both players use unless they bring their own
+pub fn default_deck() -> [Card; DECK_SIZE] {
     let e: CardSpace = None;
     let i: CardSpace = Some(InkSpace::Normal);
     let s: CardSpace = Some(InkSpace::Special);
@@ -280,66 +343,115 @@ fn default_deck() -> [Card; 15] {
     ]
 }
 
+// The number of turns in a standard match
+pub const DEFAULT_TURNS: u32 = 12;
+
 impl<R: DrawRng + Default + Debug> Default for GameState<R> {
     fn default() -> Self {
-        //let rng = StdRng::from_rng(rand::thread_rng()).unwrap();
-        let mut rng = R::default();
-        let ee = BoardSpace::Empty;
-        let s1 = BoardSpace::Special {
-            player_num: PlayerNum::P1,
-            is_activated: false,
-        };
-        let s2 = BoardSpace::Special {
-            player_num: PlayerNum::P2,
-            is_activated: false,
-        };
-        let board = Board::new(vec![
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, s2, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, s1, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-            vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
-        ])
-        .unwrap();
-        let (deck1, hand1) = Deck::draw_hand(default_deck(), &mut rng);
-        let (deck2, hand2) = Deck::draw_hand(default_deck(), &mut rng);
+        GameState::with_turns(DEFAULT_TURNS)
+    }
+}
 
-        let players = [
-            Player::new(hand1, deck1, PlayerNum::P1, 0),
-            Player::new(hand2, deck2, PlayerNum::P2, 0),
-        ];
-        GameState::new(board, players, 12, rng)
+impl<R: DrawRng + Default + Debug> GameState<R> {
+    // Creates a game on the default board with the default decks that lasts the given number of turns
+    pub fn with_turns(turns_left: u32) -> Self {
+        GameState::with_rng(turns_left, R::default())
     }
 }
 
+// The standard stage, with each player's starting special space in the middle of their end
+pub fn default_board() -> Board {
+    let ee = BoardSpace::Empty;
+    let s1 = BoardSpace::Special {
+        player_num: PlayerNum::P1,
+        is_activated: false,
+    };
+    let s2 = BoardSpace::Special {
+        player_num: PlayerNum::P2,
+        is_activated: false,
+    };
+    Board::new(vec![
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, s2, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, s1, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+        vec![ee, ee, ee, ee, ee, ee, ee, ee, ee],
+    ])
+    .unwrap()
+}
+
+fn draw_players<R: DrawRng>(decks: [[Card; DECK_SIZE]; 2], rng: &mut R) -> [Player; 2] {
+    let [cards1, cards2] = decks;
+    let (deck1, hand1) = Deck::draw_hand(cards1, rng);
+    let (deck2, hand2) = Deck::draw_hand(cards2, rng);
+    [
+        Player::new(hand1, deck1, PlayerNum::P1, 0),
+        Player::new(hand2, deck2, PlayerNum::P2, 0),
+    ]
+}
+
 impl<R: DrawRng + Debug> GameState<R> {
+    // Like with_turns, but draws cards from the given RNG
+    pub fn with_rng(turns_left: u32, rng: R) -> Self {
+        GameState::with_stage(
+            default_board(),
+            [default_deck(), default_deck()],
+            turns_left,
+            rng,
+        )
+    }
+
+    // Creates a game on the given board where each player draws from their own deck
+    pub fn with_stage(
+        board: Board,
+        decks: [[Card; DECK_SIZE]; 2],
+        turns_left: u32,
+        mut rng: R,
+    ) -> Self {
+        let players = draw_players(decks, &mut rng);
+        GameState::new(board, players, turns_left, rng)
+    }
+
+    // Starts a new game on the default board, drawing from the same RNG
+    pub fn restart(&mut self, turns_left: u32) {
+        self.board = default_board();
+        self.players = Players::new(draw_players(
+            [default_deck(), default_deck()],
+            &mut self.rng,
+        ));
+        self.turns_left = turns_left;
+    }
+
     pub fn new(board: Board, players: [Player; 2], turns_left: u32, rng: R) -> Self {
+        let hash =
+            zobrist::turns_key(turns_left) ^ player_hash(&players[0]) ^ player_hash(&players[1]);
         GameState {
             board,
             players: Players::new(players),
             turns_left,
             rng,
+            hash,
         }
     }
 
@@ -355,6 +467,32 @@ impl<R: DrawRng + Debug> GameState<R> {
         self.turns_left
     }
 
+    // For rewinding turns. Everything else should change the game through update.
+    pub(crate) fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    // Changes a player, rehashing them afterwards
+    pub(crate) fn update_player(&mut self, player_num: PlayerNum, f: impl FnOnce(&mut Player)) {
+        let player = &mut self.players[player_num];
+        self.hash ^= player_hash(player);
+        f(player);
+        self.hash ^= player_hash(player);
+    }
+
+    pub(crate) fn set_turns_left(&mut self, turns_left: u32) {
+        self.hash ^= zobrist::turns_key(self.turns_left) ^ zobrist::turns_key(turns_left);
+        self.turns_left = turns_left;
+    }
+
+    // Zobrist hash of the board, both hands, the cards left in both decks, the special
+    // gauges and the turns left, for transposition tables and spotting repeated
+    // positions. Every part is kept up to date as the game changes, so this is free.
+    // Positions with the same hash play out the same way, apart from the RNG.
+    pub fn hash(&self) -> u64 {
+        self.board.hash() ^ self.hash
+    }
+
     pub fn check_winner(&self) -> Outcome {
         let p1_inked_spaces = self.board.count_inked_spaces(PlayerNum::P1);
         let p2_inked_spaces = self.board.count_inked_spaces(PlayerNum::P2);
@@ -367,14 +505,59 @@ impl<R: DrawRng + Debug> GameState<R> {
     }
 
     pub fn redraw_hand(&mut self, player_num: PlayerNum) {
-        self.players[player_num].redraw_hand(&mut self.rng);
+        let player = &mut self.players[player_num];
+        self.hash ^= player_hash(player);
+        player.redraw_hand(&mut self.rng);
+        self.hash ^= player_hash(player);
     }
 
     // input1: player 1's input
     // input2: player 2's input
-    pub fn update(&mut self, input1: ValidInput, input2: ValidInput) {
+    pub fn update(&mut self, input1: ValidInput, input2: ValidInput) -> TurnReport {
+        let hand_idxs = [input1.hand_idx(), input2.hand_idx()];
+        let report = self.play(input1, input2);
+        replace_cards(&mut self.players, &mut self.hash, hand_idxs, &mut self.rng);
+        report
+    }
+
+    // The state after a turn, leaving this one as it is. Replacements for the played
+    // cards are drawn from the given source rather than the game's own RNG, so search
+    // can try out particular draws or share one source across many positions.
+    pub fn apply<D: DrawRng>(&self, input1: ValidInput, input2: ValidInput, draws: &mut D) -> Self
+    where
+        R: Clone,
+    {
+        self.apply_with_report(input1, input2, draws).0
+    }
+
+    // Like apply, but also describes what happened during the turn
+    pub fn apply_with_report<D: DrawRng>(
+        &self,
+        input1: ValidInput,
+        input2: ValidInput,
+        draws: &mut D,
+    ) -> (Self, TurnReport)
+    where
+        R: Clone,
+    {
+        let mut next = self.clone();
+        let hand_idxs = [input1.hand_idx(), input2.hand_idx()];
+        let report = next.play(input1, input2);
+        replace_cards(&mut next.players, &mut next.hash, hand_idxs, draws);
+        (next, report)
+    }
+
+    // Everything that happens in a turn apart from drawing new cards
+    fn play(&mut self, input1: ValidInput, input2: ValidInput) -> TurnReport {
         let hand_idx1 = input1.hand_idx();
         let hand_idx2 = input2.hand_idx();
+        let mut plays = [
+            PlayReport::new(self.players[PlayerNum::P1].get_card(hand_idx1)),
+            PlayReport::new(self.players[PlayerNum::P2].get_card(hand_idx2)),
+        ];
+        let specials = [PlayerNum::P1, PlayerNum::P2].map(|p| self.players[p].special);
+        let mut positions = [vec![], vec![]];
+        let mut conflict = None;
         match (input1.get(), input2.get()) {
             (Input::Pass, Input::Pass) => {
                 self.players[PlayerNum::P1].special += 1;
@@ -382,26 +565,41 @@ impl<R: DrawRng + Debug> GameState<R> {
             }
             (Input::Place(placement), Input::Pass) => {
                 self.players[PlayerNum::P2].special += 1;
+                positions[0] = plays[0].place(&placement);
                 self.place(hand_idx1, placement, PlayerNum::P1);
             }
             (Input::Pass, Input::Place(placement)) => {
                 self.players[PlayerNum::P1].special += 1;
+                positions[1] = plays[1].place(&placement);
                 self.place(hand_idx2, placement, PlayerNum::P2);
             }
             (Input::Place(placement1), Input::Place(placement2)) => {
-                self.place_both(hand_idx1, hand_idx2, placement1, placement2);
+                positions[0] = plays[0].place(&placement1);
+                positions[1] = plays[1].place(&placement2);
+                conflict = self.place_both(hand_idx1, hand_idx2, placement1, placement2);
             }
         };
-        let player1 = &mut self.players[PlayerNum::P1];
-        player1.replace_card(hand_idx1, &mut self.rng);
-        update_special_gauge(player1, PlayerNum::P1, &mut self.board);
-        let player2 = &mut self.players[PlayerNum::P2];
-        player2.replace_card(hand_idx2, &mut self.rng);
-        update_special_gauge(player2, PlayerNum::P2, &mut self.board);
+        for ((play, positions), player_num) in plays
+            .iter_mut()
+            .zip(&positions)
+            .zip([PlayerNum::P1, PlayerNum::P2])
+        {
+            play.inked = positions
+                .iter()
+                .filter(|bp| self.board.get_space(bp.x(), bp.y()).is_ink(player_num))
+                .count() as u32;
+        }
+        for (player_num, special) in [PlayerNum::P1, PlayerNum::P2].into_iter().zip(specials) {
+            let player = &mut self.players[player_num];
+            update_special_gauge(player, player_num, &mut self.board);
+            self.hash ^= zobrist::special_key(player_num, special)
+                ^ zobrist::special_key(player_num, player.special);
+        }
 
         if self.turns_left > 0 {
-            self.turns_left -= 1;
+            self.set_turns_left(self.turns_left - 1);
         }
+        TurnReport { plays, conflict }
     }
 
     fn place(&mut self, hand_idx: HandIndex, placement: Placement, player_num: PlayerNum) {
@@ -416,7 +614,7 @@ impl<R: DrawRng + Debug> GameState<R> {
         hand_idx2: HandIndex,
         placement1: Placement,
         placement2: Placement,
-    ) {
+    ) -> Option<Conflict> {
         // Spend special, if activated
         let player1 = &mut self.players[PlayerNum::P1];
         let priority1 = player1.deck().index(player1.hand()[hand_idx1]).0.priority();
@@ -440,6 +638,16 @@ impl<R: DrawRng + Debug> GameState<R> {
             .collect();
 
         if !overlap.is_empty() {
+            let contested = overlap.iter().filter(|(_, s1, s2)| s1 == s2).count();
+            let conflict = (contested > 0).then(|| Conflict {
+                spaces: contested as u32,
+                // Overlapping ink goes to the card with the lower priority
+                winner: match priority1.cmp(&priority2) {
+                    Ordering::Greater => Some(PlayerNum::P2),
+                    Ordering::Less => Some(PlayerNum::P1),
+                    Ordering::Equal => None,
+                },
+            });
             let overlap_resolved = match priority1.cmp(&priority2) {
                 Ordering::Greater => resolve_overlap(
                     overlap,
@@ -470,11 +678,13 @@ impl<R: DrawRng + Debug> GameState<R> {
             self.board
                 .set_ink(placement2.into_board_spaces(PlayerNum::P2));
             self.board.set_ink(overlap_resolved);
+            conflict
         } else {
             self.board
                 .set_ink(placement1.into_board_spaces(PlayerNum::P1));
             self.board
                 .set_ink(placement2.into_board_spaces(PlayerNum::P2));
+            None
         }
     }
 }
@@ -508,6 +718,44 @@ fn resolve_overlap(
         .collect::<Vec<(BoardPosition, BoardSpace)>>()
 }
 
+// Replaces the played cards, updating the hash for the hands and the drawn cards
+fn replace_cards<D: DrawRng>(
+    players: &mut Players,
+    hash: &mut u64,
+    hand_idxs: [HandIndex; 2],
+    draws: &mut D,
+) {
+    for (player_num, hand_idx) in [PlayerNum::P1, PlayerNum::P2].into_iter().zip(hand_idxs) {
+        let player = &mut players[player_num];
+        let played = player.hand()[hand_idx];
+        player.replace_card(hand_idx, draws);
+        let drawn = player.hand()[hand_idx];
+        if drawn != played {
+            let hand_slot = hand_idx as usize;
+            *hash ^= zobrist::hand_key(player_num, hand_slot, idx_to_usize(played))
+                ^ zobrist::hand_key(player_num, hand_slot, idx_to_usize(drawn))
+                ^ zobrist::drawable_key(player_num, idx_to_usize(drawn));
+        }
+    }
+}
+
+// Zobrist keys of a player's special gauge, hand and the cards left in their deck
+fn player_hash(player: &Player) -> u64 {
+    let player_num = player.player_num();
+    let mut hash = zobrist::special_key(player_num, player.special);
+    for (hand_slot, hand_idx) in HandIndex::ALL.into_iter().enumerate() {
+        let deck_slot = idx_to_usize(player.hand()[hand_idx]);
+        hash ^= zobrist::hand_key(player_num, hand_slot, deck_slot);
+    }
+    for deck_slot in 0..DECK_SIZE {
+        let deck_idx = parse_idx(deck_slot).unwrap();
+        if *player.deck().index(deck_idx).1 {
+            hash ^= zobrist::drawable_key(player_num, deck_slot);
+        }
+    }
+    hash
+}
+
 fn update_special_gauge(player: &mut Player, player_num: PlayerNum, board: &mut Board) {
     let special_spaces = board.get_surrounded_inactive_specials(player_num);
     // activate surrounded special spaces
@@ -527,7 +775,7 @@ fn update_special_gauge(player: &mut Player, player_num: PlayerNum, board: &mut
 mod tests {
     use super::*;
     use crate::tableturf::board::{Board, BoardPosition};
-    use crate::tableturf::deck::{Deck, DeckIndex, Hand, HandIndex};
+    use crate::tableturf::deck::{parse_idx, Deck, DeckIndex, Hand, HandIndex};
     use crate::tableturf::input::{Action, Placement, RawInput, RawPlacement, Rotation};
 
     #[derive(Debug)]
@@ -649,10 +897,10 @@ mod tests {
         .unwrap();
 
         let (deck1, hand1) = draw_hand1();
-        let player1 = Player::new(hand1, deck1, 0);
+        let player1 = Player::new(hand1, deck1, PlayerNum::P1, 0);
 
         let (deck1, hand1) = draw_hand1();
-        let player2 = Player::new(hand1, deck1, 0);
+        let player2 = Player::new(hand1, deck1, PlayerNum::P2, 0);
 
         GameState::new(board, [player1, player2], 12, MockRng1)
     }
@@ -682,7 +930,7 @@ mod tests {
             vec![p2_special, empty, p1_ink],
         ])
         .unwrap();
-        let spaces = board_pos(&board, 8, 8).surrounding_spaces(&board);
+        let spaces = board_pos(&board, 1, 1).surrounding_spaces(&board);
         assert_eq!(spaces[0], empty);
         assert_eq!(spaces[1], p1_ink);
         assert_eq!(spaces[2], p2_ink);
@@ -692,7 +940,7 @@ mod tests {
         assert_eq!(spaces[6], empty);
         assert_eq!(spaces[7], p1_ink);
 
-        let spaces = board_pos(&board, 7, 7).surrounding_spaces(&board);
+        let spaces = board_pos(&board, 0, 0).surrounding_spaces(&board);
         assert_eq!(spaces[0], oob);
         assert_eq!(spaces[1], oob);
         assert_eq!(spaces[2], oob);
@@ -722,16 +970,16 @@ mod tests {
         .unwrap();
 
         let (deck1, hand1) = draw_hand1();
-        let player1 = Player::new(hand1, deck1, 0);
+        let player1 = Player::new(hand1, deck1, PlayerNum::P1, 0);
 
         let (deck2, hand2) = draw_hand2();
-        let player2 = Player::new(hand2, deck2, 0);
+        let player2 = Player::new(hand2, deck2, PlayerNum::P2, 0);
 
         let mut game_state = GameState::new(board, [player1, player2], 12, MockRng1);
 
         let raw_placement = RawPlacement {
-            x: 5,
-            y: 5,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
@@ -743,7 +991,6 @@ mod tests {
                 hand_idx,
                 &game_state.board,
                 &game_state.players[PlayerNum::P1],
-                PlayerNum::P1,
             )
             .unwrap(),
             PlayerNum::P1,
@@ -781,19 +1028,19 @@ mod tests {
         let mut game_state1 = game_state1();
 
         let raw_placement1 = RawPlacement {
-            x: 5,
-            y: 5,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
         let raw_placement2 = RawPlacement {
-            x: 5,
-            y: 5,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
         let hand_idx = HandIndex::H1;
-        game_state1.place_both(
+        let conflict = game_state1.place_both(
             hand_idx,
             hand_idx,
             Placement::new(
@@ -801,7 +1048,6 @@ mod tests {
                 hand_idx,
                 &game_state1.board,
                 &game_state1.players[PlayerNum::P1],
-                PlayerNum::P1,
             )
             .unwrap(),
             Placement::new(
@@ -809,7 +1055,6 @@ mod tests {
                 hand_idx,
                 &game_state1.board,
                 &game_state1.players[PlayerNum::P2],
-                PlayerNum::P2,
             )
             .unwrap(),
         );
@@ -822,19 +1067,23 @@ mod tests {
         ])
         .unwrap();
         assert_eq!(game_state1.board, expected_board1);
+        // Both players played the same card, so neither had priority
+        let conflict = conflict.unwrap();
+        assert!(conflict.spaces > 0);
+        assert_eq!(conflict.winner, None);
 
         let mut game_state_offset = game_state_offset();
         let board_offset = &game_state_offset.board;
 
         let raw_placement1 = RawPlacement {
-            x: 5,
-            y: 5,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
         let raw_placement2 = RawPlacement {
-            x: 6,
-            y: 5,
+            x: 2,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
@@ -847,7 +1096,6 @@ mod tests {
                 hand_idx,
                 board_offset,
                 &game_state_offset.players[PlayerNum::P1],
-                PlayerNum::P1,
             )
             .unwrap(),
             Placement::new(
@@ -855,7 +1103,6 @@ mod tests {
                 hand_idx,
                 board_offset,
                 &game_state_offset.players[PlayerNum::P2],
-                PlayerNum::P2,
             )
             .unwrap(),
         );
@@ -872,14 +1119,14 @@ mod tests {
         let mut game_state2 = game_state2();
 
         let raw_placement1 = RawPlacement {
-            x: 5,
-            y: 5,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Zero,
         };
         let raw_placement2 = RawPlacement {
-            x: 5,
-            y: 4,
+            x: 1,
+            y: 0,
             special_activated: false,
             rotation: Rotation::Zero,
         };
@@ -892,7 +1139,6 @@ mod tests {
                 hand_idx,
                 &game_state2.board,
                 &game_state2.players[PlayerNum::P1],
-                PlayerNum::P1,
             )
             .unwrap(),
             Placement::new(
@@ -900,7 +1146,6 @@ mod tests {
                 hand_idx,
                 &game_state2.board,
                 &game_state2.players[PlayerNum::P2],
-                PlayerNum::P2,
             )
             .unwrap(),
         );
@@ -915,6 +1160,55 @@ mod tests {
         assert_eq!(game_state2.board, expected_board2);
     }
 
+    #[test]
+    fn test_place_both_special_over_normal() {
+        let empty = BoardSpace::Empty;
+        let p1_ink = BoardSpace::Ink {
+            player_num: PlayerNum::P1,
+        };
+        let p2_ink = BoardSpace::Ink {
+            player_num: PlayerNum::P2,
+        };
+        let board = Board::new(vec![
+            vec![empty, empty, empty, empty, p2_ink],
+            vec![empty, empty, empty, empty, empty],
+            vec![empty, empty, empty, empty, empty],
+            vec![empty, empty, empty, empty, empty],
+            vec![empty, p1_ink, empty, empty, empty],
+        ])
+        .unwrap();
+        let (deck, hand) = draw_hand1();
+        let player1 = Player::new(hand, deck, PlayerNum::P1, 0);
+        let (deck, hand) = draw_hand1();
+        let player2 = Player::new(hand, deck, PlayerNum::P2, 0);
+        let mut game_state = GameState::new(board, [player1, player2], 12, MockRng1);
+
+        // Splattershot's special lands on Slosher's ink, and nothing else overlaps.
+        // Slosher has priority, but the special still takes the space.
+        let placement = |hand_idx: HandIndex, x: i32, y: i32, player_num: PlayerNum| {
+            let raw_placement = RawPlacement {
+                x,
+                y,
+                special_activated: false,
+                rotation: Rotation::Zero,
+            };
+            let player = &game_state.players[player_num];
+            Placement::new(raw_placement, hand_idx, &game_state.board, player).unwrap()
+        };
+        let placement1 = placement(HandIndex::H1, 1, 3, PlayerNum::P1);
+        let placement2 = placement(HandIndex::H2, 3, 1, PlayerNum::P2);
+        let conflict = game_state.place_both(HandIndex::H1, HandIndex::H2, placement1, placement2);
+
+        assert_eq!(conflict, None);
+        assert_eq!(
+            game_state.board.get_space(2, 2),
+            BoardSpace::Special {
+                player_num: PlayerNum::P1,
+                is_activated: false,
+            }
+        );
+    }
+
     #[test]
     fn test_resolve_overlap() {
         let empty = BoardSpace::Empty;
@@ -926,28 +1220,28 @@ mod tests {
         ])
         .unwrap();
         let overlap = vec![
-            (board_pos(&board, 7, 7), InkSpace::Normal, InkSpace::Normal),
+            (board_pos(&board, 0, 0), InkSpace::Normal, InkSpace::Normal),
             (
-                board_pos(&board, 8, 7),
+                board_pos(&board, 1, 0),
                 InkSpace::Special,
                 InkSpace::Special,
             ),
-            (board_pos(&board, 9, 7), InkSpace::Special, InkSpace::Normal),
-            (board_pos(&board, 7, 8), InkSpace::Normal, InkSpace::Special),
+            (board_pos(&board, 2, 0), InkSpace::Special, InkSpace::Normal),
+            (board_pos(&board, 0, 1), InkSpace::Normal, InkSpace::Special),
         ];
         let result = resolve_overlap(overlap, BoardSpace::Wall, BoardSpace::Wall);
         let expected = vec![
-            (board_pos(&board, 7, 7), BoardSpace::Wall),
-            (board_pos(&board, 8, 7), BoardSpace::Wall),
+            (board_pos(&board, 0, 0), BoardSpace::Wall),
+            (board_pos(&board, 1, 0), BoardSpace::Wall),
             (
-                board_pos(&board, 9, 7),
+                board_pos(&board, 2, 0),
                 BoardSpace::Special {
                     player_num: PlayerNum::P1,
                     is_activated: false,
                 },
             ),
             (
-                board_pos(&board, 7, 8),
+                board_pos(&board, 0, 1),
                 BoardSpace::Special {
                     player_num: PlayerNum::P2,
                     is_activated: false,
@@ -960,7 +1254,7 @@ mod tests {
     #[test]
     fn test_update_special_gauge() {
         let (deck, hand) = draw_hand1();
-        let mut player = Player::new(hand, deck, 0);
+        let mut player = Player::new(hand, deck, PlayerNum::P1, 0);
         let p1_special = BoardSpace::Special {
             player_num: PlayerNum::P1,
             is_activated: false,
@@ -1008,10 +1302,10 @@ mod tests {
         .unwrap();
 
         let (deck, hand) = draw_hand1();
-        let player1 = Player::new(hand, deck, 0);
+        let player1 = Player::new(hand, deck, PlayerNum::P1, 0);
 
         let (deck, hand) = draw_hand1();
-        let player2 = Player::new(hand, deck, 0);
+        let player2 = Player::new(hand, deck, PlayerNum::P2, 0);
 
         let game_state_p1_win = GameState::new(board, [player1, player2], 12, MockRng1);
         let outcome = game_state_p1_win.check_winner();
@@ -1026,10 +1320,10 @@ mod tests {
         .unwrap();
 
         let (deck, hand) = draw_hand1();
-        let player1 = Player::new(hand, deck, 0);
+        let player1 = Player::new(hand, deck, PlayerNum::P1, 0);
 
         let (deck, hand) = draw_hand1();
-        let player2 = Player::new(hand, deck, 0);
+        let player2 = Player::new(hand, deck, PlayerNum::P2, 0);
 
         let game_state_p2_win = GameState::new(board, [player1, player2], 12, MockRng1);
         let outcome = game_state_p2_win.check_winner();
@@ -1071,7 +1365,6 @@ mod tests {
             },
             &game_state.board,
             &game_state.players[PlayerNum::P1],
-            PlayerNum::P1,
         )
         .unwrap();
 
@@ -1082,7 +1375,6 @@ mod tests {
             },
             &game_state.board,
             &game_state.players[PlayerNum::P2],
-            PlayerNum::P2,
         )
         .unwrap();
 
@@ -1114,15 +1406,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H1,
                 action: Action::Place(RawPlacement {
-                    x: 5,
-                    y: 5,
+                    x: 1,
+                    y: 1,
                     special_activated: false,
                     rotation: Rotation::Zero,
                 }),
             },
             &game_state.board,
             &game_state.players[PlayerNum::P1],
-            PlayerNum::P1,
         )
         .unwrap();
 
@@ -1133,7 +1424,6 @@ mod tests {
             },
             &game_state.board,
             &game_state.players[PlayerNum::P2],
-            PlayerNum::P2,
         )
         .unwrap();
 
@@ -1168,10 +1458,10 @@ mod tests {
         .unwrap();
 
         let (deck, hand) = draw_hand1();
-        let player1 = Player::new(hand, deck, 0);
+        let player1 = Player::new(hand, deck, PlayerNum::P1, 0);
 
         let (deck, hand) = draw_hand1();
-        let player2 = Player::new(hand, deck, 0);
+        let player2 = Player::new(hand, deck, PlayerNum::P2, 0);
 
         let mut game_state = GameState::new(board, [player1, player2], 1, MockRng1);
 
@@ -1179,15 +1469,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H1,
                 action: Action::Place(RawPlacement {
-                    x: 5,
-                    y: 5,
+                    x: 1,
+                    y: 1,
                     special_activated: false,
                     rotation: Rotation::Zero,
                 }),
             },
             &game_state.board,
             &game_state.players[PlayerNum::P1],
-            PlayerNum::P1,
         )
         .unwrap();
 
@@ -1195,15 +1484,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H2,
                 action: Action::Place(RawPlacement {
-                    x: 5,
-                    y: 5,
+                    x: 1,
+                    y: 1,
                     special_activated: false,
                     rotation: Rotation::Zero,
                 }),
             },
             &game_state.board,
             &game_state.players[PlayerNum::P2],
-            PlayerNum::P2,
         )
         .unwrap();
 
@@ -1250,10 +1538,10 @@ mod tests {
         .unwrap();
 
         let (deck, hand) = draw_hand1();
-        let player1 = Player::new(hand, deck, 7);
+        let player1 = Player::new(hand, deck, PlayerNum::P1, 7);
 
         let (deck, hand) = draw_hand1();
-        let player2 = Player::new(hand, deck, 8);
+        let player2 = Player::new(hand, deck, PlayerNum::P2, 8);
 
         let mut game_state = GameState::new(board, [player1, player2], 5, MockRng1);
 
@@ -1261,15 +1549,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H1,
                 action: Action::Place(RawPlacement {
-                    x: 5,
-                    y: 5,
+                    x: 1,
+                    y: 1,
                     special_activated: true,
                     rotation: Rotation::Zero,
                 }),
             },
             &game_state.board,
             &game_state.players[PlayerNum::P1],
-            PlayerNum::P1,
         )
         .unwrap();
 
@@ -1277,15 +1564,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H2,
                 action: Action::Place(RawPlacement {
-                    x: 5,
-                    y: 5,
+                    x: 1,
+                    y: 1,
                     special_activated: true,
                     rotation: Rotation::Zero,
                 }),
             },
             &game_state.board,
             &game_state.players[PlayerNum::P2],
-            PlayerNum::P2,
         )
         .unwrap();
 
@@ -1310,4 +1596,218 @@ mod tests {
             DeckIndex::D5
         );
     }
+
+    #[test]
+    fn test_seeded_rng() {
+        let hands = |game_state: &GameState<DeckRng>| {
+            [PlayerNum::P1, PlayerNum::P2]
+                .map(|player_num| game_state.player(player_num).hand().clone())
+        };
+        let mut game_state1 = GameState::with_rng(DEFAULT_TURNS, DeckRng::from_seed(1));
+        let mut game_state2 = GameState::with_rng(DEFAULT_TURNS, DeckRng::from_seed(1));
+        assert_eq!(hands(&game_state1), hands(&game_state2));
+
+        game_state1.restart(4);
+        game_state2.restart(4);
+        assert_eq!(hands(&game_state1), hands(&game_state2));
+        assert_eq!(game_state1.turns_left(), 4);
+    }
+
+    #[test]
+    fn test_with_stage() {
+        let ee = BoardSpace::Empty;
+        let s1 = BoardSpace::Special {
+            player_num: PlayerNum::P1,
+            is_activated: false,
+        };
+        let s2 = BoardSpace::Special {
+            player_num: PlayerNum::P2,
+            is_activated: false,
+        };
+        let board = Board::new(vec![vec![ee, s2, ee], vec![ee, ee, ee], vec![ee, s1, ee]]).unwrap();
+        let mut deck2 = default_deck();
+        deck2.reverse();
+        let game_state = GameState::with_stage(
+            board.clone(),
+            [default_deck(), deck2.clone()],
+            5,
+            DeckRng::from_seed(1),
+        );
+        assert_eq!(game_state.board(), &board);
+        assert_eq!(game_state.turns_left(), 5);
+        assert_eq!(
+            game_state.player(PlayerNum::P1).deck().cards(),
+            &default_deck()
+        );
+        assert_eq!(game_state.player(PlayerNum::P2).deck().cards(), &deck2);
+    }
+
+    #[test]
+    fn test_turn_report() {
+        let mut game_state = game_state1();
+        let card1 = game_state.players[PlayerNum::P1]
+            .get_card(HandIndex::H1)
+            .clone();
+        let inked_before = game_state.board.count_inked_spaces(PlayerNum::P1);
+
+        let input1 = ValidInput::new(
+            RawInput {
+                hand_idx: HandIndex::H1,
+                action: Action::Place(RawPlacement {
+                    x: 1,
+                    y: 1,
+                    special_activated: false,
+                    rotation: Rotation::Zero,
+                }),
+            },
+            &game_state.board,
+            &game_state.players[PlayerNum::P1],
+        )
+        .unwrap();
+        let input2 = ValidInput::new(
+            RawInput {
+                hand_idx: HandIndex::H2,
+                action: Action::Pass,
+            },
+            &game_state.board,
+            &game_state.players[PlayerNum::P2],
+        )
+        .unwrap();
+
+        let report = game_state.update(input1, input2);
+        let [play1, play2] = &report.plays;
+        assert_eq!(play1.card, card1);
+        assert!(play1.placed);
+        assert!(!play1.special_activated);
+        assert_eq!(
+            play1.inked,
+            game_state.board.count_inked_spaces(PlayerNum::P1) - inked_before
+        );
+        assert!(!play2.placed);
+        assert_eq!(play2.inked, 0);
+        assert_eq!(report.conflict, None);
+    }
+
+    // A game with the same position as the given one, but drawing from its own RNG
+    fn copy_position(game_state: &GameState<DeckRng>, seed: u64) -> GameState<DeckRng> {
+        GameState::new(
+            game_state.board.clone(),
+            [
+                game_state.players[PlayerNum::P1].clone(),
+                game_state.players[PlayerNum::P2].clone(),
+            ],
+            game_state.turns_left,
+            DeckRng::from_seed(seed),
+        )
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut game_state = GameState::with_rng(12, DeckRng::from_seed(1));
+        let same = copy_position(&game_state, 2);
+        assert_eq!(game_state.hash(), same.hash());
+        assert_eq!(
+            game_state.hash(),
+            GameState::with_rng(12, DeckRng::from_seed(1)).hash()
+        );
+        assert_ne!(
+            game_state.hash(),
+            GameState::with_rng(11, DeckRng::from_seed(1)).hash()
+        );
+
+        let mut more_special = copy_position(&game_state, 2);
+        more_special.update_player(PlayerNum::P2, |player| player.special += 1);
+        assert_ne!(more_special.hash(), game_state.hash());
+
+        // Which cards are left to draw matters too
+        let mut fewer_left = copy_position(&game_state, 2);
+        let deck = game_state.players[PlayerNum::P1].deck();
+        let left = (0..DECK_SIZE)
+            .map(|idx| parse_idx(idx).unwrap())
+            .find(|deck_idx| *deck.index(*deck_idx).1)
+            .unwrap();
+        fewer_left.update_player(PlayerNum::P1, |player| {
+            player.deck_mut().set_drawable(left, false)
+        });
+        assert_ne!(fewer_left.hash(), game_state.hash());
+
+        // After a turn the hash matches one computed from scratch for the new position
+        let before = game_state.hash();
+        let placement = crate::tableturf::ai::legal_inputs(
+            &game_state.board,
+            &game_state.players[PlayerNum::P1],
+        )
+        .into_iter()
+        .map(|(_, valid_input)| valid_input)
+        .find(|valid_input| matches!(valid_input.clone().get(), Input::Place(_)))
+        .unwrap();
+        let pass = ValidInput::new(
+            RawInput {
+                hand_idx: HandIndex::H1,
+                action: Action::Pass,
+            },
+            &game_state.board,
+            &game_state.players[PlayerNum::P2],
+        )
+        .unwrap();
+        game_state.update(placement, pass);
+        assert_ne!(game_state.hash(), before);
+        assert_eq!(game_state.hash(), copy_position(&game_state, 3).hash());
+        let rebuilt: Board =
+            serde_json::from_str(&serde_json::to_string(&game_state.board).unwrap()).unwrap();
+        assert_eq!(rebuilt.hash(), game_state.board.hash());
+    }
+
+    #[test]
+    fn test_apply() {
+        let game_state = GameState::with_rng(12, DeckRng::from_seed(4));
+        let placement = crate::tableturf::ai::legal_inputs(
+            &game_state.board,
+            &game_state.players[PlayerNum::P1],
+        )
+        .into_iter()
+        .map(|(_, valid_input)| valid_input)
+        .find(|valid_input| matches!(valid_input.clone().get(), Input::Place(_)))
+        .unwrap();
+        let pass = ValidInput::new(
+            RawInput {
+                hand_idx: HandIndex::H2,
+                action: Action::Pass,
+            },
+            &game_state.board,
+            &game_state.players[PlayerNum::P2],
+        )
+        .unwrap();
+
+        // Drawing the same cards as the game's RNG reaches the same state as update
+        let before = game_state.hash();
+        let (next, report) = game_state.apply_with_report(
+            placement.clone(),
+            pass.clone(),
+            &mut game_state.rng.clone(),
+        );
+        assert_eq!(game_state.hash(), before);
+        assert_eq!(game_state.turns_left, 12);
+        let mut updated = copy_position(&game_state, 4);
+        updated.rng = game_state.rng.clone();
+        assert_eq!(updated.update(placement.clone(), pass.clone()), report);
+        assert_eq!(next.hash(), updated.hash());
+        assert_eq!(next.turns_left, 11);
+        assert_eq!(next.players[PlayerNum::P2].special, 1);
+
+        // Replacement cards come from the given source: MockRng1 draws the first card left
+        let next = game_state.apply(placement, pass, &mut MockRng1);
+        for (player_num, hand_idx) in [
+            (PlayerNum::P1, HandIndex::H1),
+            (PlayerNum::P2, HandIndex::H2),
+        ] {
+            let player = &game_state.players[player_num];
+            let first_left = (0..DECK_SIZE)
+                .find(|idx| *player.deck().index(parse_idx(*idx).unwrap()).1)
+                .unwrap();
+            let drawn = next.players[player_num].hand()[hand_idx];
+            assert_eq!(idx_to_usize(drawn), first_left);
+        }
+        assert_eq!(next.board, updated.board);
+    }
 }
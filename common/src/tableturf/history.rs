@@ -0,0 +1,343 @@
+// Undo and redo for local play and analysis. Each turn is recorded with the inputs, the
+// cards drawn to replace the ones played and the changes it made, so turns can be stepped
+// back and forth exactly, without replaying the game from the start.
+use crate::tableturf::board::{BoardPosition, BoardSpace};
+use crate::tableturf::deck::{DeckIndex, DrawRng, HandIndex};
+use crate::tableturf::game_state::{GameState, TurnReport};
+use crate::tableturf::input::{InputError, RawInput, ValidInput};
+use crate::tableturf::player::PlayerNum;
+use std::fmt::Debug;
+use thiserror::Error;
+
+const PLAYERS: [PlayerNum; 2] = [PlayerNum::P1, PlayerNum::P2];
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("{player_num:?}'s input is illegal: {source}")]
+    Illegal {
+        player_num: PlayerNum,
+        source: InputError,
+    },
+    #[error("Turn {turn} hasn't been played. {recorded} turns are recorded")]
+    NoSuchTurn { turn: usize, recorded: usize },
+}
+
+// What a turn changed for one player
+#[derive(Clone, Debug)]
+struct PlayerChange {
+    hand_idx: HandIndex,
+    played: DeckIndex,
+    // None once the deck has run out
+    drawn: Option<DeckIndex>,
+    special: [u32; 2],
+}
+
+#[derive(Clone, Debug)]
+pub struct Turn {
+    inputs: [RawInput; 2],
+    report: TurnReport,
+    // Each changed space before and after the turn
+    spaces: Vec<(BoardPosition, BoardSpace, BoardSpace)>,
+    players: [PlayerChange; 2],
+    turns_left: [u32; 2],
+}
+
+impl Turn {
+    // Player 1's input first
+    pub fn inputs(&self) -> &[RawInput; 2] {
+        &self.inputs
+    }
+
+    pub fn report(&self) -> &TurnReport {
+        &self.report
+    }
+
+    // The cards each player drew to replace the one they played, if any were left
+    pub fn draws(&self) -> [Option<DeckIndex>; 2] {
+        [self.players[0].drawn, self.players[1].drawn]
+    }
+}
+
+#[derive(Debug)]
+pub struct History<R: Debug> {
+    game_state: GameState<R>,
+    // Turns leading up to the current state, oldest first
+    played: Vec<Turn>,
+    // Turns that were undone, most recently undone last
+    undone: Vec<Turn>,
+}
+
+impl<R: DrawRng + Debug> History<R> {
+    // Starts recording from the given state, which counts as turn 0
+    pub fn new(game_state: GameState<R>) -> Self {
+        History {
+            game_state,
+            played: vec![],
+            undone: vec![],
+        }
+    }
+
+    pub fn game_state(&self) -> &GameState<R> {
+        &self.game_state
+    }
+
+    pub fn into_game_state(self) -> GameState<R> {
+        self.game_state
+    }
+
+    // The number of turns played to reach the current state
+    pub fn turn(&self) -> usize {
+        self.played.len()
+    }
+
+    // The number of turns that can be stepped through, including undone ones
+    pub fn recorded(&self) -> usize {
+        self.played.len() + self.undone.len()
+    }
+
+    // The turns leading up to the current state, oldest first
+    pub fn turns(&self) -> &[Turn] {
+        &self.played
+    }
+
+    // Plays a turn from the current state. Any undone turns are forgotten, and
+    // replacement cards are drawn from the game's RNG as it is now.
+    pub fn play(&mut self, input1: RawInput, input2: RawInput) -> Result<&Turn, HistoryError> {
+        let [valid1, valid2] =
+            [(&input1, PlayerNum::P1), (&input2, PlayerNum::P2)].map(|(input, player_num)| {
+                let player = self.game_state.player(player_num);
+                ValidInput::new(input.clone(), self.game_state.board(), player)
+                    .map_err(|source| HistoryError::Illegal { player_num, source })
+            });
+        let (valid1, valid2) = (valid1?, valid2?);
+
+        let board = self.game_state.board().clone();
+        let hand_idxs = [valid1.hand_idx(), valid2.hand_idx()];
+        let before = PLAYERS.map(|player_num| self.game_state.player(player_num).clone());
+        let turns_left = self.game_state.turns_left();
+        let report = self.game_state.update(valid1, valid2);
+
+        let after = self.game_state.board();
+        let spaces = board
+            .spaces()
+            .iter()
+            .zip(after.spaces())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(idx, (old, new))| {
+                let position = BoardPosition::new(&board, idx % board.width(), idx / board.width());
+                (position.unwrap(), *old, *new)
+            })
+            .collect();
+        let players = [0, 1].map(|i| {
+            let hand_idx = hand_idxs[i];
+            let player = self.game_state.player(PLAYERS[i]);
+            let played = before[i].hand()[hand_idx];
+            let drawn = player.hand()[hand_idx];
+            PlayerChange {
+                hand_idx,
+                played,
+                drawn: (drawn != played).then_some(drawn),
+                special: [before[i].special, player.special],
+            }
+        });
+        self.undone.clear();
+        self.played.push(Turn {
+            inputs: [input1, input2],
+            report,
+            spaces,
+            players,
+            turns_left: [turns_left, self.game_state.turns_left()],
+        });
+        Ok(self.played.last().unwrap())
+    }
+
+    // Steps back a turn, returning the turn that was undone
+    pub fn undo(&mut self) -> Option<&Turn> {
+        let turn = self.played.pop()?;
+        let board = self.game_state.board_mut();
+        for (position, old, _) in &turn.spaces {
+            board.set_space(position, *old);
+        }
+        for (change, player_num) in turn.players.iter().zip(PLAYERS) {
+            self.game_state.update_player(player_num, |player| {
+                player.hand_mut()[change.hand_idx] = change.played;
+                if let Some(drawn) = change.drawn {
+                    player.deck_mut().set_drawable(drawn, true);
+                }
+                player.special = change.special[0];
+            });
+        }
+        self.game_state.set_turns_left(turn.turns_left[0]);
+        self.undone.push(turn);
+        self.undone.last()
+    }
+
+    // Steps forward through a turn that was undone, returning it
+    pub fn redo(&mut self) -> Option<&Turn> {
+        let turn = self.undone.pop()?;
+        let board = self.game_state.board_mut();
+        for (position, _, new) in &turn.spaces {
+            board.set_space(position, *new);
+        }
+        for (change, player_num) in turn.players.iter().zip(PLAYERS) {
+            self.game_state.update_player(player_num, |player| {
+                if let Some(drawn) = change.drawn {
+                    player.hand_mut()[change.hand_idx] = drawn;
+                    player.deck_mut().set_drawable(drawn, false);
+                }
+                player.special = change.special[1];
+            });
+        }
+        self.game_state.set_turns_left(turn.turns_left[1]);
+        self.played.push(turn);
+        self.played.last()
+    }
+
+    // Undoes or redoes turns until the given number of turns have been played
+    pub fn jump_to(&mut self, turn: usize) -> Result<(), HistoryError> {
+        if turn > self.recorded() {
+            return Err(HistoryError::NoSuchTurn {
+                turn,
+                recorded: self.recorded(),
+            });
+        }
+        while self.turn() > turn {
+            self.undo();
+        }
+        while self.turn() < turn {
+            self.redo();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::ai::{Agent, GreedyAgent, RandomAgent};
+    use crate::tableturf::game_state::DeckRng;
+    use crate::tableturf::input::Action;
+
+    fn history(turns: usize) -> (History<DeckRng>, Vec<u64>) {
+        let mut history = History::new(GameState::with_rng(12, DeckRng::from_seed(5)));
+        let mut agents = [RandomAgent::from_seed(1), RandomAgent::from_seed(2)];
+        let mut hashes = vec![history.game_state().hash()];
+        for _ in 0..turns {
+            let game_state = history.game_state();
+            let [input1, input2] = [0, 1].map(|i| {
+                let player = game_state.player(PLAYERS[i]);
+                agents[i].choose(game_state.board(), player)
+            });
+            history.play(input1, input2).unwrap();
+            hashes.push(history.game_state().hash());
+        }
+        (history, hashes)
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (mut history, hashes) = history(4);
+        let before = history.game_state().board().clone();
+        assert_eq!(history.turn(), 4);
+
+        for turn in (0..4).rev() {
+            assert!(history.undo().is_some());
+            assert_eq!(history.turn(), turn);
+            assert_eq!(history.game_state().hash(), hashes[turn]);
+        }
+        assert!(history.undo().is_none());
+        assert_eq!(history.game_state().turns_left(), 12);
+
+        for hash in &hashes[1..] {
+            assert!(history.redo().is_some());
+            assert_eq!(history.game_state().hash(), *hash);
+        }
+        assert!(history.redo().is_none());
+        assert_eq!(history.game_state().board(), &before);
+    }
+
+    #[test]
+    fn test_undo_restores_deck() {
+        let (mut history, _) = history(0);
+        let game_state = history.game_state();
+        let mut greedy = GreedyAgent;
+        let input1 = greedy.choose(game_state.board(), game_state.player(PlayerNum::P1));
+        let input2 = RawInput {
+            hand_idx: HandIndex::H3,
+            action: Action::Pass,
+        };
+        let draws = history.play(input1, input2).unwrap().draws();
+        let drawn = draws[1].unwrap();
+        let deck = history.game_state().player(PlayerNum::P2).deck();
+        assert!(!deck.index(drawn).1);
+
+        history.undo();
+        let player2 = history.game_state().player(PlayerNum::P2);
+        assert!(player2.deck().index(drawn).1);
+        assert_ne!(player2.hand()[HandIndex::H3], drawn);
+        assert_eq!(player2.special, 0);
+
+        history.redo();
+        let player2 = history.game_state().player(PlayerNum::P2);
+        assert_eq!(player2.hand()[HandIndex::H3], drawn);
+        assert_eq!(player2.special, 1);
+    }
+
+    #[test]
+    fn test_jump_to() {
+        let (mut history, hashes) = history(5);
+        history.jump_to(2).unwrap();
+        assert_eq!(history.game_state().hash(), hashes[2]);
+        assert_eq!(history.turns().len(), 2);
+        assert_eq!(history.recorded(), 5);
+        history.jump_to(5).unwrap();
+        assert_eq!(history.game_state().hash(), hashes[5]);
+        assert!(matches!(
+            history.jump_to(6),
+            Err(HistoryError::NoSuchTurn {
+                turn: 6,
+                recorded: 5
+            })
+        ));
+
+        // Playing a different turn from an earlier state forgets the undone ones
+        history.jump_to(1).unwrap();
+        let pass = |hand_idx| RawInput {
+            hand_idx,
+            action: Action::Pass,
+        };
+        history
+            .play(pass(HandIndex::H1), pass(HandIndex::H2))
+            .unwrap();
+        assert_eq!(history.recorded(), 2);
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn test_illegal_input() {
+        let (mut history, _) = history(0);
+        let pass = RawInput {
+            hand_idx: HandIndex::H1,
+            action: Action::Pass,
+        };
+        let placement = RawInput {
+            hand_idx: HandIndex::H1,
+            action: Action::Place(crate::tableturf::input::RawPlacement {
+                x: 0,
+                y: 0,
+                special_activated: false,
+                rotation: crate::tableturf::input::Rotation::Zero,
+            }),
+        };
+        let err = history.play(pass, placement).unwrap_err();
+        assert!(matches!(
+            err,
+            HistoryError::Illegal {
+                player_num: PlayerNum::P2,
+                ..
+            }
+        ));
+        assert_eq!(history.recorded(), 0);
+    }
+}
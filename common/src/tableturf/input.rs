@@ -1,4 +1,5 @@
-use crate::tableturf::board::{Board, BoardPosition, BoardPositionError, BoardSpace};
+use crate::tableturf::bitboard::BitBoard;
+use crate::tableturf::board::{Board, BoardPosition, BoardPositionError, BoardSpace, Coordinate};
 use crate::tableturf::card::{Card, Grid, InkSpace, CARD_WIDTH};
 use crate::tableturf::deck::HandIndex;
 use crate::tableturf::player::{Player, PlayerNum};
@@ -31,6 +32,16 @@ pub enum Rotation {
     Three,
 }
 
+impl Rotation {
+    // Every rotation, from none to three turns
+    pub const ALL: [Rotation; 4] = [
+        Rotation::Zero,
+        Rotation::One,
+        Rotation::Two,
+        Rotation::Three,
+    ];
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum Action {
     Pass,
@@ -39,9 +50,10 @@ pub enum Action {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct RawPlacement {
-    // The x and y coordinates of the top-left corner of the card's grid
-    pub x: usize,
-    pub y: usize,
+    // The x and y coordinates of the space the card's pivot is placed on, which stays
+    // put when the card is rotated. See Card::pivot.
+    pub x: i32,
+    pub y: i32,
     pub special_activated: bool,
     pub rotation: Rotation,
 }
@@ -88,6 +100,30 @@ pub struct Placement {
     special_activated: bool,
 }
 
+// Why an input was rejected. Ink collisions leave out the copy of the board that their
+// error carries, which would otherwise dominate the cost of listing legal inputs.
+enum Rejection {
+    Input(InputError),
+    InkCollision(Vec<(BoardPosition, InkSpace)>),
+}
+
+impl From<InputError> for Rejection {
+    fn from(err: InputError) -> Self {
+        Rejection::Input(err)
+    }
+}
+
+impl Rejection {
+    fn into_error(self, board: &Board) -> InputError {
+        match self {
+            Rejection::Input(err) => err,
+            Rejection::InkCollision(ink_spaces) => {
+                InputError::InkCollision(InkSpaces(ink_spaces), board.clone())
+            }
+        }
+    }
+}
+
 impl Placement {
     pub fn new(
         raw_placement: RawPlacement,
@@ -95,6 +131,16 @@ impl Placement {
         board: &Board,
         player: &Player,
     ) -> Result<Placement, InputError> {
+        Placement::check(raw_placement, hand_idx, board, player)
+            .map_err(|rejection| rejection.into_error(board))
+    }
+
+    fn check(
+        raw_placement: RawPlacement,
+        hand_idx: HandIndex,
+        board: &Board,
+        player: &Player,
+    ) -> Result<Placement, Rejection> {
         let RawPlacement {
             x: board_x,
             y: board_y,
@@ -103,6 +149,19 @@ impl Placement {
         } = raw_placement;
         let selected_card = player.get_card(hand_idx);
         let grid = rotate_input(selected_card, rotation);
+        let (pivot_x, pivot_y) = rotate_pivot(selected_card, rotation);
+        // The grid's top-left corner, which can't be worked out for coordinates right at
+        // the bottom of i32's range
+        let corner = |coordinate: Coordinate, base: i32, pivot: usize| {
+            base.checked_sub(pivot as i32)
+                .ok_or(InputError::InvalidPosition(BoardPositionError::Overflow {
+                    coordinate,
+                    base,
+                    offset: pivot,
+                }))
+        };
+        let board_x = corner(Coordinate::X, board_x, pivot_x)?;
+        let board_y = corner(Coordinate::Y, board_y, pivot_y)?;
         let result = grid
             .iter()
             .enumerate()
@@ -118,6 +177,7 @@ impl Placement {
             })
             .collect::<Result<Vec<(BoardPosition, InkSpace)>, BoardPositionError>>();
         let ink_spaces = result.map_err(InputError::InvalidPosition)?;
+        let placed = BitBoard::from_positions(ink_spaces.iter().map(|(bp, _)| bp));
 
         if special_activated {
             // Check that player has enough special and that the special isn't
@@ -126,29 +186,27 @@ impl Placement {
                 return Err(InputError::InsufficientSpecial {
                     special: player.special,
                     required: selected_card.special(),
-                });
+                }
+                .into());
             }
 
-            if special_collision(&ink_spaces[..], board) {
-                return Err(InputError::SpecialCollision);
+            if special_collision(&placed, board) {
+                return Err(InputError::SpecialCollision.into());
             }
 
             // Check that ink placement is adjacent to one of the player's special spaces
-            if !placement_adjacent_to_special(&ink_spaces[..], board, player.player_num()) {
-                return Err(InputError::SpecialNotAdjacentToSpecialSquare);
+            if !placement_adjacent_to_special(&placed, board, player.player_num()) {
+                return Err(InputError::SpecialNotAdjacentToSpecialSquare.into());
             }
         // Check that ink placement is over empty squares
         } else {
-            if placement_collision(&ink_spaces[..], board) {
-                return Err(InputError::InkCollision(
-                    InkSpaces(ink_spaces),
-                    board.clone(),
-                ));
+            if placement_collision(&placed, board) {
+                return Err(Rejection::InkCollision(ink_spaces));
             }
 
             // Check that ink placement is adjacent to player's ink
-            if !placement_adjacent_to_ink(&ink_spaces[..], board, player.player_num()) {
-                return Err(InputError::InkNotAdjacentToInk);
+            if !placement_adjacent_to_ink(&placed, board, player.player_num()) {
+                return Err(InputError::InkNotAdjacentToInk.into());
             }
         }
         Ok(Placement {
@@ -180,6 +238,15 @@ impl ValidInput {
     // - card index in hand
     // - special availability
     pub fn new(input: RawInput, board: &Board, player: &Player) -> Result<Self, InputError> {
+        Self::check(input, board, player).map_err(|rejection| rejection.into_error(board))
+    }
+
+    // Like new, but without the reason the input is illegal, which is cheaper to find out
+    pub(crate) fn try_new(input: RawInput, board: &Board, player: &Player) -> Option<Self> {
+        Self::check(input, board, player).ok()
+    }
+
+    fn check(input: RawInput, board: &Board, player: &Player) -> Result<Self, Rejection> {
         let hand_idx = input.hand_idx;
         match input.action {
             Action::Pass => Ok(Self {
@@ -187,7 +254,7 @@ impl ValidInput {
                 input: Input::Pass,
             }),
             Action::Place(raw_placement) => {
-                let placement = Placement::new(raw_placement, hand_idx, board, player)?;
+                let placement = Placement::check(raw_placement, hand_idx, board, player)?;
 
                 Ok(Self {
                     hand_idx,
@@ -217,45 +284,28 @@ fn into_board_space(ink_space: &InkSpace, player_num: PlayerNum) -> BoardSpace {
 }
 
 // Test if an entire placement of ink is adjacent to a player's inked space
-fn placement_adjacent_to_ink(
-    inked_spaces: &[(BoardPosition, InkSpace)],
-    board: &Board,
-    player_num: PlayerNum,
-) -> bool {
-    inked_spaces
-        .iter()
-        .any(|(bp, _)| bp.adjacent_to_ink(board, player_num))
+fn placement_adjacent_to_ink(placed: &BitBoard, board: &Board, player_num: PlayerNum) -> bool {
+    placed.touches(board.ink_bits(player_num))
 }
 
 // Test if an entire placement of ink is adjacent to one of the player's special spaces
-fn placement_adjacent_to_special(
-    inked_spaces: &[(BoardPosition, InkSpace)],
-    board: &Board,
-    player_num: PlayerNum,
-) -> bool {
-    inked_spaces
-        .iter()
-        .any(|(bp, _)| bp.adjacent_to_special(board, player_num))
+fn placement_adjacent_to_special(placed: &BitBoard, board: &Board, player_num: PlayerNum) -> bool {
+    placed.touches(board.special_bits(player_num))
 }
 
 // Test if an entire placement of ink overlaps ink or walls
-fn placement_collision(inked_spaces: &[(BoardPosition, InkSpace)], board: &Board) -> bool {
-    inked_spaces
-        .iter()
-        .any(|(bp, _)| !matches!(board.get_space(bp.x(), bp.y()), BoardSpace::Empty))
+fn placement_collision(placed: &BitBoard, board: &Board) -> bool {
+    !placed.without(board.empty_bits()).is_empty()
 }
 
 // Test if an entire special placement of ink overlaps any special spaces or walls
-fn special_collision(inked_spaces: &[(BoardPosition, InkSpace)], board: &Board) -> bool {
-    inked_spaces.iter().any(|(bp, _)| {
-        let board_space = board.get_space(bp.x(), bp.y());
-        matches!(board_space, BoardSpace::Special { .. })
-            || matches!(board_space, BoardSpace::Wall)
-            || matches!(board_space, BoardSpace::OutOfBounds)
-    })
+fn special_collision(placed: &BitBoard, board: &Board) -> bool {
+    placed.intersects(board.wall_bits())
+        || placed.intersects(board.special_bits(PlayerNum::P1))
+        || placed.intersects(board.special_bits(PlayerNum::P2))
 }
 
-fn rotate_grid_ccw(grid: &mut Grid) {
+pub(crate) fn rotate_grid_ccw(grid: &mut Grid) {
     for i in 0..(CARD_WIDTH / 2) {
         for j in i..(CARD_WIDTH - i - 1) {
             let temp = grid[i][j];
@@ -287,6 +337,17 @@ pub fn rotate_input(card: &Card, rotation: Rotation) -> Grid {
     grid
 }
 
+// Where the card's pivot ends up in the grid returned by rotate_input
+pub fn rotate_pivot(card: &Card, rotation: Rotation) -> (usize, usize) {
+    let (x, y) = card.pivot();
+    match rotation {
+        Rotation::Zero => (x, y),
+        Rotation::One => (y, CARD_WIDTH - 1 - x),
+        Rotation::Two => (CARD_WIDTH - 1 - x, CARD_WIDTH - 1 - y),
+        Rotation::Three => (CARD_WIDTH - 1 - y, x),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,6 +459,12 @@ mod tests {
                 [e, e, e, e, e, e, e, e],
             ]
         );
+
+        // The pivot stays on the same space of the card
+        assert_eq!(rotate_pivot(&splattershot, Rotation::Zero), (3, 3));
+        assert_eq!(rotate_pivot(&splattershot, Rotation::One), (3, 4));
+        assert_eq!(rotate_pivot(&splattershot, Rotation::Two), (4, 4));
+        assert_eq!(rotate_pivot(&splattershot, Rotation::Three), (4, 3));
     }
 
     fn custom_deck() -> [Card; 15] {
@@ -660,22 +727,21 @@ mod tests {
         .unwrap();
         let (deck, hand) = draw_hand2();
         let special = 5;
-        let player = Player::new(hand, deck.clone(), special);
+        let player = Player::new(hand, deck.clone(), PlayerNum::P1, special);
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_ok());
         let placement = placement.unwrap();
         let ink_spaces = placement.ink_spaces.0;
         assert_eq!(ink_spaces.len(), 3);
-        assert_eq!(ink_spaces[0].0, BoardPosition::new(&board, 7, 7).unwrap());
-        assert_eq!(ink_spaces[1].0, BoardPosition::new(&board, 8, 7).unwrap());
-        assert_eq!(ink_spaces[2].0, BoardPosition::new(&board, 7, 8).unwrap());
+        assert_eq!(ink_spaces[0].0, BoardPosition::new(&board, 0, 0).unwrap());
+        assert_eq!(ink_spaces[1].0, BoardPosition::new(&board, 1, 0).unwrap());
+        assert_eq!(ink_spaces[2].0, BoardPosition::new(&board, 0, 1).unwrap());
 
         // Test placing ink on top of an inked space
         let board = Board::new(vec![
@@ -685,13 +751,12 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_err());
 
         // Test placing special on top of an inked space
@@ -702,17 +767,16 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: true,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_ok());
 
         let (_draw, hand) = draw_hand();
-        let player_no_special = Player::new(hand, deck.clone(), 0);
+        let player_no_special = Player::new(hand, deck.clone(), PlayerNum::P1, 0);
         // Test placing special with insufficient special meter
         let board = Board::new(vec![
             vec![empty, p1_ink, empty],
@@ -721,18 +785,12 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: true,
             rotation: Rotation::Two,
         };
-        let placement = Placement::new(
-            raw_placement,
-            HandIndex::H1,
-            &board,
-            &player_no_special,
-            PlayerNum::P1,
-        );
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player_no_special);
         assert!(placement.is_err());
 
         // Test placing special on top of a special space
@@ -743,13 +801,12 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: true,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_err());
 
         // Test placing ink without any ink nearby
@@ -760,13 +817,12 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_err());
 
         // Test placing special without any special nearby
@@ -777,13 +833,12 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: true,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_err());
 
         // Test placing ink with a special space nearby
@@ -794,16 +849,56 @@ mod tests {
         ])
         .unwrap();
         let raw_placement = RawPlacement {
-            x: 4,
-            y: 4,
+            x: 1,
+            y: 1,
             special_activated: false,
             rotation: Rotation::Two,
         };
-        let placement =
-            Placement::new(raw_placement, HandIndex::H1, &board, &player, PlayerNum::P1);
+        let placement = Placement::new(raw_placement, HandIndex::H1, &board, &player);
         assert!(placement.is_ok());
     }
 
+    #[test]
+    fn test_placement_at_extreme_coordinates() {
+        let board = Board::new(vec![vec![BoardSpace::Empty; 3]; 3]).unwrap();
+        let (deck, hand) = draw_hand2();
+        let player = Player::new(hand, deck, PlayerNum::P1, 0);
+        let place = |x: i32, y: i32| {
+            let raw_placement = RawPlacement {
+                x,
+                y,
+                special_activated: false,
+                rotation: Rotation::Two,
+            };
+            Placement::new(raw_placement, HandIndex::H1, &board, &player)
+        };
+        assert!(matches!(
+            place(i32::MIN, 1),
+            Err(InputError::InvalidPosition(BoardPositionError::Overflow {
+                coordinate: Coordinate::X,
+                base: i32::MIN,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            place(1, i32::MIN),
+            Err(InputError::InvalidPosition(BoardPositionError::Overflow {
+                coordinate: Coordinate::Y,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            place(i32::MAX, i32::MAX),
+            Err(InputError::InvalidPosition(
+                BoardPositionError::OutOfBounds(..)
+            ))
+        ));
+    }
+
+    fn placed(ink_spaces: &[(BoardPosition, InkSpace)]) -> BitBoard {
+        BitBoard::from_positions(ink_spaces.iter().map(|(bp, _)| bp))
+    }
+
     #[test]
     fn test_placement_collision() {
         let empty = BoardSpace::Empty;
@@ -817,11 +912,11 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
-        assert!(!placement_collision(&ink_spaces, &board));
+        assert!(!placement_collision(&placed(&ink_spaces), &board));
 
         let board = Board::new(vec![
             vec![empty, p1_ink, empty],
@@ -830,11 +925,11 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
-        assert!(placement_collision(&ink_spaces, &board));
+        assert!(placement_collision(&placed(&ink_spaces), &board));
     }
 
     #[test]
@@ -854,12 +949,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(placement_adjacent_to_ink(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -871,12 +966,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(placement_adjacent_to_ink(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -888,12 +983,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(!placement_adjacent_to_ink(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -913,11 +1008,11 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
-        assert!(!special_collision(&ink_spaces, &board));
+        assert!(!special_collision(&placed(&ink_spaces), &board));
 
         let board = Board::new(vec![
             vec![empty, p1_ink, empty],
@@ -926,11 +1021,11 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
-        assert!(!special_collision(&ink_spaces, &board));
+        assert!(!special_collision(&placed(&ink_spaces), &board));
 
         let board = Board::new(vec![
             vec![empty, wall, empty],
@@ -939,11 +1034,11 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
-        assert!(special_collision(&ink_spaces, &board));
+        assert!(special_collision(&placed(&ink_spaces), &board));
     }
 
     #[test]
@@ -963,12 +1058,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(!placement_adjacent_to_special(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -980,12 +1075,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(placement_adjacent_to_special(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -997,12 +1092,12 @@ mod tests {
         ])
         .unwrap();
         let ink_spaces = vec![
-            (BoardPosition::new(&board, 7, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 8, 7).unwrap(), InkSpace::Normal),
-            (BoardPosition::new(&board, 7, 8).unwrap(), InkSpace::Special),
+            (BoardPosition::new(&board, 0, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 1, 0).unwrap(), InkSpace::Normal),
+            (BoardPosition::new(&board, 0, 1).unwrap(), InkSpace::Special),
         ];
         assert!(!placement_adjacent_to_special(
-            &ink_spaces,
+            &placed(&ink_spaces),
             &board,
             PlayerNum::P1
         ));
@@ -1030,11 +1125,10 @@ mod tests {
             },
             &board,
             &player,
-            PlayerNum::P1,
         );
         assert!(input.is_ok());
 
-        // Test placing a card as far to the top-left as possible
+        // Test placing a card in the top-left corner of the board
         let input = ValidInput::new(
             RawInput {
                 hand_idx: HandIndex::H3,
@@ -1047,24 +1141,44 @@ mod tests {
             },
             &board,
             &player,
-            PlayerNum::P1,
         );
         assert!(input.is_ok());
 
+        // Test placing the same card with its ink off the left of the board
+        let input = ValidInput::new(
+            RawInput {
+                hand_idx: HandIndex::H3,
+                action: Action::Place(RawPlacement {
+                    x: -1,
+                    y: 0,
+                    special_activated: false,
+                    rotation: Rotation::Zero,
+                }),
+            },
+            &board,
+            &player,
+        );
+        assert!(matches!(
+            input,
+            Err(InputError::InvalidPosition(BoardPositionError::Negative(
+                Coordinate::X,
+                -1
+            )))
+        ));
+
         // Test placing a card as far to the bottom-right as possible
         let input = ValidInput::new(
             RawInput {
                 hand_idx: HandIndex::H2,
                 action: Action::Place(RawPlacement {
-                    x: 9,
-                    y: 9,
+                    x: 2,
+                    y: 2,
                     special_activated: false,
                     rotation: Rotation::Zero,
                 }),
             },
             &board,
             &player,
-            PlayerNum::P1,
         );
         assert!(input.is_ok());
 
@@ -1073,15 +1187,14 @@ mod tests {
             RawInput {
                 hand_idx: HandIndex::H2,
                 action: Action::Place(RawPlacement {
-                    x: 10,
-                    y: 9,
+                    x: 3,
+                    y: 2,
                     special_activated: false,
                     rotation: Rotation::Zero,
                 }),
             },
             &board,
             &player,
-            PlayerNum::P1,
         );
         assert!(input.is_err());
     }
@@ -1,13 +1,30 @@
+pub mod ai;
+mod bitboard;
 mod board;
 mod card;
 mod deck;
+pub mod env;
+mod eval;
 mod game_state;
+pub mod history;
 mod input;
+pub mod notation;
 mod player;
+pub mod puzzle;
+pub mod solver;
+mod zobrist;
 
-pub use board::{Board, BoardSpace};
-pub use card::{Card, CardSpace, Grid, InkSpace, CARD_WIDTH};
-pub use deck::{Deck, DeckIndex, DrawRng, Hand, HandIndex};
-pub use game_state::{DeckRng, GameState, Outcome};
-pub use input::{rotate_input, Action, InputError, RawInput, RawPlacement, Rotation, ValidInput};
+pub use bitboard::BitBoard;
+pub use board::{Board, BoardError, BoardSpace};
+pub use card::{BoundingBox, Card, CardSpace, Grid, InkSpace, CARD_WIDTH};
+pub use deck::{Deck, DeckIndex, DrawRng, Hand, HandIndex, DECK_SIZE, HAND_SIZE};
+pub use eval::Evaluation;
+pub use game_state::{
+    default_board, default_deck, Conflict, DeckRng, GameState, Outcome, PlayReport, TurnReport,
+    DEFAULT_TURNS,
+};
+pub use input::{
+    rotate_input, rotate_pivot, Action, Input, InputError, Placement, RawInput, RawPlacement,
+    Rotation, ValidInput,
+};
 pub use player::{Player, PlayerNum};
@@ -0,0 +1,186 @@
+// A compact way of writing down a move for logs, replays and bug reports, e.g.
+// `Splattershot R1 @ (12,7) SP` or `Pass Splattershot`. Cards are named rather than
+// numbered by their place in the hand, and the rotation is the number of counter-clockwise
+// turns. Coordinates are the space the card's pivot is placed on.
+use crate::tableturf::deck::HandIndex;
+use crate::tableturf::input::{Action, RawInput, RawPlacement, Rotation};
+use crate::tableturf::player::Player;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NotationError {
+    #[error("Expected `<card> R<rotation> @ (<x>,<y>) [SP]` or `Pass <card>`")]
+    Format,
+    #[error("There's no {0} in the hand")]
+    UnknownCard(String),
+    #[error("Rotation must be R0-R3, got {0}")]
+    Rotation(String),
+    #[error("Invalid coordinate {0}")]
+    Coordinate(String),
+}
+
+// Writes the input down, naming the card it plays from the player's hand
+pub fn format_input(input: &RawInput, player: &Player) -> String {
+    let name = player.get_card(input.hand_idx).name();
+    match &input.action {
+        Action::Pass => format!("Pass {}", name),
+        Action::Place(placement) => {
+            let rotation = match placement.rotation {
+                Rotation::Zero => 0,
+                Rotation::One => 1,
+                Rotation::Two => 2,
+                Rotation::Three => 3,
+            };
+            format!(
+                "{} R{} @ ({},{}){}",
+                name,
+                rotation,
+                placement.x,
+                placement.y,
+                if placement.special_activated {
+                    " SP"
+                } else {
+                    ""
+                }
+            )
+        }
+    }
+}
+
+// Reads an input written by format_input. Card names are matched without regard to case,
+// and the first card in the hand with the name is played.
+pub fn parse_input(s: &str, player: &Player) -> Result<RawInput, NotationError> {
+    let s = s.trim();
+    if let Some(name) = s.strip_prefix("Pass ") {
+        return Ok(RawInput {
+            hand_idx: find_card(name.trim(), player)?,
+            action: Action::Pass,
+        });
+    }
+    let (s, special_activated) = match s.strip_suffix(" SP") {
+        Some(s) => (s, true),
+        None => (s, false),
+    };
+    let (card, position) = s.split_once(" @ ").ok_or(NotationError::Format)?;
+    let (name, rotation) = card.trim().rsplit_once(' ').ok_or(NotationError::Format)?;
+    let (x, y) = position
+        .trim()
+        .strip_prefix('(')
+        .and_then(|position| position.strip_suffix(')'))
+        .and_then(|position| position.split_once(','))
+        .ok_or(NotationError::Format)?;
+    Ok(RawInput {
+        hand_idx: find_card(name.trim(), player)?,
+        action: Action::Place(RawPlacement {
+            x: parse_coordinate(x)?,
+            y: parse_coordinate(y)?,
+            special_activated,
+            rotation: parse_rotation(rotation)?,
+        }),
+    })
+}
+
+fn find_card(name: &str, player: &Player) -> Result<HandIndex, NotationError> {
+    HandIndex::ALL
+        .into_iter()
+        .find(|&hand_idx| player.get_card(hand_idx).name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| NotationError::UnknownCard(name.to_string()))
+}
+
+fn parse_rotation(s: &str) -> Result<Rotation, NotationError> {
+    match s {
+        "R0" => Ok(Rotation::Zero),
+        "R1" => Ok(Rotation::One),
+        "R2" => Ok(Rotation::Two),
+        "R3" => Ok(Rotation::Three),
+        _ => Err(NotationError::Rotation(s.to_string())),
+    }
+}
+
+fn parse_coordinate(s: &str) -> Result<i32, NotationError> {
+    s.trim()
+        .parse()
+        .map_err(|_| NotationError::Coordinate(s.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::game_state::{DeckRng, GameState};
+    use crate::tableturf::player::PlayerNum;
+
+    fn player() -> Player {
+        let game_state = GameState::with_rng(12, DeckRng::from_seed(3));
+        game_state.player(PlayerNum::P1).clone()
+    }
+
+    fn place(hand_idx: HandIndex, x: i32, y: i32, special_activated: bool) -> RawInput {
+        RawInput {
+            hand_idx,
+            action: Action::Place(RawPlacement {
+                x,
+                y,
+                special_activated,
+                rotation: Rotation::Three,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let player = player();
+        let name = player.get_card(HandIndex::H2).name();
+        let inputs = [
+            place(HandIndex::H2, 12, 7, false),
+            place(HandIndex::H2, -4, -7, true),
+            RawInput {
+                hand_idx: HandIndex::H2,
+                action: Action::Pass,
+            },
+        ];
+        let expected = [
+            format!("{} R3 @ (12,7)", name),
+            format!("{} R3 @ (-4,-7) SP", name),
+            format!("Pass {}", name),
+        ];
+        for (input, expected) in inputs.iter().zip(expected) {
+            let notation = format_input(input, &player);
+            assert_eq!(notation, expected);
+            let parsed = parse_input(&notation, &player).unwrap();
+            assert_eq!(parsed.hand_idx, HandIndex::H2);
+            assert_eq!(format_input(&parsed, &player), notation);
+        }
+    }
+
+    #[test]
+    fn test_parse_loosely() {
+        let player = player();
+        let name = player.get_card(HandIndex::H4).name().to_uppercase();
+        let input = parse_input(&format!("  {}  R0 @ ( 1, 2 )  ", name), &player).unwrap();
+        assert_eq!(input.hand_idx, HandIndex::H4);
+        match input.action {
+            Action::Place(placement) => assert_eq!((placement.x, placement.y), (1, 2)),
+            Action::Pass => panic!("expected a placement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let player = player();
+        let name = player.get_card(HandIndex::H1).name();
+        let parse = |s: String| parse_input(&s, &player).unwrap_err();
+        assert_eq!(parse(format!("{} R1", name)), NotationError::Format);
+        assert_eq!(
+            parse("Nothing R1 @ (0,0)".to_string()),
+            NotationError::UnknownCard("Nothing".to_string())
+        );
+        assert_eq!(
+            parse(format!("{} R4 @ (0,0)", name)),
+            NotationError::Rotation("R4".to_string())
+        );
+        assert_eq!(
+            parse(format!("{} R1 @ (0,x)", name)),
+            NotationError::Coordinate("x".to_string())
+        );
+    }
+}
@@ -10,7 +10,7 @@ pub enum PlayerNum {
     P2,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Players([Player; 2]);
 
 impl Index<PlayerNum> for Players {
@@ -68,6 +68,16 @@ impl Player {
         &self.deck
     }
 
+    // For rewinding turns, which puts played cards back in the hand and drawn ones back in
+    // the deck
+    pub(crate) fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    pub(crate) fn deck_mut(&mut self) -> &mut Deck {
+        &mut self.deck
+    }
+
     pub fn get_card(&self, hand_idx: HandIndex) -> &Card {
         self.deck.index(self.hand[hand_idx]).0
     }
@@ -150,6 +160,6 @@ mod tests {
         player.replace_card(HandIndex::H1, &mut MockRng);
         let deck_idx = player.hand[HandIndex::H1];
         assert_eq!(deck_idx, DeckIndex::D5);
-        assert_eq!(*player.deck().index(deck_idx).1, false);
+        assert!(!*player.deck().index(deck_idx).1);
     }
 }
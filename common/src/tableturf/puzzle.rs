@@ -0,0 +1,312 @@
+// Puzzles are positions set up by hand, where one player has to find a line of play that
+// reaches a goal against an opponent whose moves are scripted. Cards are drawn in a fixed
+// order too, so a puzzle plays out the same way every time and a line can be checked
+// without a server, e.g. in the browser.
+use crate::tableturf::board::Board;
+use crate::tableturf::card::Card;
+use crate::tableturf::deck::{Deck, DeckIndex, DrawRng, Hand, DECK_SIZE, HAND_SIZE};
+use crate::tableturf::game_state::GameState;
+use crate::tableturf::input::{InputError, RawInput, ValidInput};
+use crate::tableturf::player::{Player, PlayerNum};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PuzzleError {
+    #[error("{player_num:?} has {draws} cards to draw, but a deck only has room for {}", DECK_SIZE - HAND_SIZE)]
+    TooManyDraws { player_num: PlayerNum, draws: usize },
+    #[error("{player_num:?} has {draws} cards to draw, but needs {needed} to last the puzzle")]
+    NotEnoughDraws {
+        player_num: PlayerNum,
+        draws: usize,
+        needed: usize,
+    },
+    #[error("The opponent has {moves} moves scripted, but the puzzle lasts {turns} turns")]
+    ScriptLength { moves: usize, turns: u32 },
+    #[error("The line has {moves} moves, but the puzzle lasts {turns} turns")]
+    LineLength { moves: usize, turns: u32 },
+    // Turns count from 1
+    #[error("The move on turn {turn} is illegal: {source}")]
+    Illegal { turn: usize, source: InputError },
+    #[error("The opponent's scripted move on turn {turn} is illegal: {source}")]
+    IllegalScript { turn: usize, source: InputError },
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Goal {
+    // End with at least this many more inked spaces than the opponent
+    WinBy(u32),
+    // End with at least this many inked spaces
+    InkAtLeast(u32),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PuzzlePlayer {
+    pub hand: [Card; HAND_SIZE],
+    // The cards drawn after each turn but the last, in order
+    #[serde(default)]
+    pub draws: Vec<Card>,
+    #[serde(default)]
+    pub special: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Puzzle {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub board: Board,
+    pub turns_left: u32,
+    // The player solving the puzzle
+    pub player_num: PlayerNum,
+    // Player 1 first
+    pub players: [PuzzlePlayer; 2],
+    // The opponent's move on each turn
+    pub script: Vec<RawInput>,
+    pub goal: Goal,
+}
+
+// How a line of play did
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct Verdict {
+    pub solved: bool,
+    // The solving player's inked spaces, then the opponent's
+    pub ink: [u32; 2],
+}
+
+// Deals the first cards of each deck and then draws the rest in order
+#[derive(Copy, Clone, Debug, Default)]
+pub struct InOrder;
+
+impl DrawRng for InOrder {
+    fn draw<T, I: Iterator<Item = T> + Sized>(&mut self, mut iter: I) -> Option<T> {
+        iter.next()
+    }
+
+    fn draw_hand<I: Iterator<Item = DeckIndex> + Sized>(&mut self, iter: I) -> Hand {
+        let cards: Vec<DeckIndex> = iter.take(HAND_SIZE).collect();
+        Hand::new(cards.try_into().unwrap()).unwrap()
+    }
+}
+
+fn opponent(player_num: PlayerNum) -> PlayerNum {
+    match player_num {
+        PlayerNum::P1 => PlayerNum::P2,
+        PlayerNum::P2 => PlayerNum::P1,
+    }
+}
+
+impl Puzzle {
+    // The position the puzzle starts from
+    pub fn game_state(&self) -> Result<GameState<InOrder>, PuzzleError> {
+        if self.script.len() != self.turns_left as usize {
+            return Err(PuzzleError::ScriptLength {
+                moves: self.script.len(),
+                turns: self.turns_left,
+            });
+        }
+        let [player1, player2] = [PlayerNum::P1, PlayerNum::P2].map(|player_num| {
+            let puzzle_player = &self.players[player_num as usize];
+            let draws = puzzle_player.draws.len();
+            let needed = self.turns_left.saturating_sub(1) as usize;
+            if draws > DECK_SIZE - HAND_SIZE {
+                return Err(PuzzleError::TooManyDraws { player_num, draws });
+            }
+            if draws < needed {
+                return Err(PuzzleError::NotEnoughDraws {
+                    player_num,
+                    draws,
+                    needed,
+                });
+            }
+            // The rest of the deck is only drawn from after the last turn, so any card will do
+            let mut cards: Vec<Card> = puzzle_player.hand.to_vec();
+            cards.extend(puzzle_player.draws.iter().cloned());
+            cards.resize(DECK_SIZE, puzzle_player.hand[0].clone());
+            let (deck, hand) = Deck::draw_hand(cards.try_into().unwrap(), &mut InOrder);
+            Ok(Player::new(hand, deck, player_num, puzzle_player.special))
+        });
+        Ok(GameState::new(
+            self.board.clone(),
+            [player1?, player2?],
+            self.turns_left,
+            InOrder,
+        ))
+    }
+
+    // Plays the given moves against the opponent's script and judges the final position
+    pub fn check(&self, line: &[RawInput]) -> Result<Verdict, PuzzleError> {
+        let mut game_state = self.game_state()?;
+        if line.len() != self.turns_left as usize {
+            return Err(PuzzleError::LineLength {
+                moves: line.len(),
+                turns: self.turns_left,
+            });
+        }
+        for (idx, (input, scripted)) in line.iter().zip(&self.script).enumerate() {
+            let turn = idx + 1;
+            let board = game_state.board();
+            let player = game_state.player(self.player_num);
+            let input = ValidInput::new(input.clone(), board, player)
+                .map_err(|source| PuzzleError::Illegal { turn, source })?;
+            let opponent = game_state.player(opponent(self.player_num));
+            let scripted = ValidInput::new(scripted.clone(), board, opponent)
+                .map_err(|source| PuzzleError::IllegalScript { turn, source })?;
+            match self.player_num {
+                PlayerNum::P1 => game_state.update(input, scripted),
+                PlayerNum::P2 => game_state.update(scripted, input),
+            };
+        }
+        Ok(self.verdict(&game_state))
+    }
+
+    // Judges a position, e.g. once the puzzle has been played out
+    pub fn verdict<R: DrawRng + Debug>(&self, game_state: &GameState<R>) -> Verdict {
+        let board = game_state.board();
+        let own = board.count_inked_spaces(self.player_num);
+        let other = board.count_inked_spaces(opponent(self.player_num));
+        let solved = match self.goal {
+            Goal::WinBy(margin) => own >= other + margin,
+            Goal::InkAtLeast(spaces) => own >= spaces,
+        };
+        Verdict {
+            solved,
+            ink: [own, other],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::ai::legal_inputs;
+    use crate::tableturf::deck::HandIndex;
+    use crate::tableturf::game_state::{default_board, default_deck};
+    use crate::tableturf::input::{Action, RawPlacement, Rotation};
+
+    fn pass(hand_idx: HandIndex) -> RawInput {
+        RawInput {
+            hand_idx,
+            action: Action::Pass,
+        }
+    }
+
+    fn test_puzzle(turns_left: u32) -> Puzzle {
+        let deck = default_deck();
+        let player = |first: usize| PuzzlePlayer {
+            hand: std::array::from_fn(|i| deck[first + i].clone()),
+            draws: deck[first + 4..first + 6].to_vec(),
+            special: 0,
+        };
+        Puzzle {
+            name: "Test".to_string(),
+            description: String::new(),
+            board: default_board(),
+            turns_left,
+            player_num: PlayerNum::P1,
+            players: [player(0), player(6)],
+            script: vec![pass(HandIndex::H1); turns_left as usize],
+            goal: Goal::WinBy(5),
+        }
+    }
+
+    #[test]
+    fn test_game_state() {
+        let puzzle = test_puzzle(3);
+        let game_state = puzzle.game_state().unwrap();
+        let player2 = game_state.player(PlayerNum::P2);
+        assert_eq!(player2.get_card(HandIndex::H1), &puzzle.players[1].hand[0]);
+        assert_eq!(player2.get_card(HandIndex::H4), &puzzle.players[1].hand[3]);
+        assert_eq!(game_state.turns_left(), 3);
+
+        // Scripted passes draw the next cards in order
+        let mut game_state = game_state;
+        for draw in &puzzle.players[1].draws {
+            let input1 = ValidInput::new(
+                pass(HandIndex::H2),
+                game_state.board(),
+                game_state.player(PlayerNum::P1),
+            )
+            .unwrap();
+            let input2 = ValidInput::new(
+                pass(HandIndex::H1),
+                game_state.board(),
+                game_state.player(PlayerNum::P2),
+            )
+            .unwrap();
+            game_state.update(input1, input2);
+            assert_eq!(
+                game_state.player(PlayerNum::P2).get_card(HandIndex::H1),
+                draw
+            );
+        }
+
+        assert!(matches!(
+            test_puzzle(4).game_state(),
+            Err(PuzzleError::NotEnoughDraws {
+                player_num: PlayerNum::P1,
+                draws: 2,
+                needed: 3
+            })
+        ));
+        let mut short_script = test_puzzle(2);
+        short_script.script.pop();
+        assert!(matches!(
+            short_script.game_state(),
+            Err(PuzzleError::ScriptLength { moves: 1, turns: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_check() {
+        let puzzle = test_puzzle(1);
+        let game_state = puzzle.game_state().unwrap();
+        let player = game_state.player(PlayerNum::P1);
+        // Any placement against a pass wins by more than five
+        let (placement, _) = legal_inputs(game_state.board(), player)
+            .into_iter()
+            .find(|(input, _)| matches!(input.action, Action::Place(_)))
+            .unwrap();
+        let verdict = puzzle.check(&[placement]).unwrap();
+        assert!(verdict.solved);
+        assert_eq!(verdict.ink[1], 1);
+
+        let verdict = puzzle.check(&[pass(HandIndex::H1)]).unwrap();
+        assert_eq!(
+            verdict,
+            Verdict {
+                solved: false,
+                ink: [1, 1]
+            }
+        );
+
+        let far_away = RawInput {
+            hand_idx: HandIndex::H1,
+            action: Action::Place(RawPlacement {
+                x: 0,
+                y: 0,
+                special_activated: false,
+                rotation: Rotation::Zero,
+            }),
+        };
+        assert!(matches!(
+            puzzle.check(&[far_away]),
+            Err(PuzzleError::Illegal { turn: 1, .. })
+        ));
+        assert!(matches!(
+            puzzle.check(&[]),
+            Err(PuzzleError::LineLength { moves: 0, turns: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_serde() {
+        let puzzle = test_puzzle(2);
+        let json = serde_json::to_string(&puzzle).unwrap();
+        let parsed: Puzzle = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.goal, Goal::WinBy(5));
+        assert_eq!(parsed.board, puzzle.board);
+        assert_eq!(parsed.script.len(), 2);
+    }
+}
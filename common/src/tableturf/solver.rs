@@ -0,0 +1,573 @@
+// Solves the last few turns of a game exactly, for post-game analysis and puzzles.
+// Both players move at once, so each position is a matrix game between their moves rather
+// than a minimax tree. Its payoff is the ink margin at the end of the game, averaged over
+// the cards either player might draw next, and the players' optimal mixed strategies are
+// found with a linear program. Both hands are assumed to be known, as they are once every
+// card has been revealed.
+use crate::tableturf::ai::legal_inputs;
+use crate::tableturf::bitboard::BitBoard;
+use crate::tableturf::board::Board;
+use crate::tableturf::card::InkSpace;
+use crate::tableturf::deck::{parse_idx, Deck, DeckIndex, DrawRng, Hand, DECK_SIZE};
+use crate::tableturf::game_state::GameState;
+use crate::tableturf::input::{Input, RawInput, ValidInput};
+use crate::tableturf::player::{Player, PlayerNum};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use thiserror::Error;
+
+pub const DEFAULT_MAX_POSITIONS: usize = 1_000_000;
+
+// Tolerance for the linear program's floating point comparisons
+const EPSILON: f64 = 1e-9;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SolverError {
+    #[error("The game is over, so there are no moves to solve")]
+    GameOver,
+    #[error("Solving would take more than {0} positions")]
+    TooManyPositions(usize),
+}
+
+// One of a player's moves, with how often to play it and how it fares
+#[derive(Clone, Debug)]
+pub struct MoveValue {
+    // Every input with the same effect, e.g. the rotations of a symmetric card that ink
+    // the same spaces
+    pub inputs: Vec<RawInput>,
+    // How often the optimal strategy plays this move
+    pub probability: f64,
+    // The expected ink margin when the opponent plays their optimal strategy
+    pub value: f64,
+}
+
+// Margins are player 1's inked spaces minus player 2's at the end of the game, so player 1
+// wants them as high as possible and player 2 as low as possible
+#[derive(Clone, Debug)]
+pub struct Solution {
+    // The expected margin when both players play optimally
+    pub value: f64,
+    // Player 1's moves, then player 2's
+    pub moves: [Vec<MoveValue>; 2],
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Solver {
+    max_positions: usize,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::new(DEFAULT_MAX_POSITIONS)
+    }
+}
+
+impl Solver {
+    // Gives up before playing out more than max_positions positions, whether or not they
+    // were already solved. Every pair of moves before the last turn is played out once per
+    // pair of cards the players could draw, so only the last two turns of a game are
+    // usually practical; earlier positions run out of budget.
+    pub fn new(max_positions: usize) -> Self {
+        Solver { max_positions }
+    }
+
+    pub fn solve<R: DrawRng + Clone + Debug>(
+        &self,
+        game_state: &GameState<R>,
+    ) -> Result<Solution, SolverError> {
+        if game_state.turns_left() == 0 {
+            return Err(SolverError::GameOver);
+        }
+        let mut search = Search {
+            max_positions: self.max_positions,
+            positions: 0,
+            values: HashMap::new(),
+        };
+        let moves = [PlayerNum::P1, PlayerNum::P2]
+            .map(|player_num| distinct_moves(game_state.board(), game_state.player(player_num)));
+        let payoff = search.payoff(game_state, &moves)?;
+        let game = solve_matrix_game(&payoff);
+
+        let [moves1, moves2] = moves;
+        let moves1 = moves1
+            .into_iter()
+            .zip(&payoff)
+            .zip(&game.rows)
+            .map(|((m, row), probability)| MoveValue {
+                inputs: m.inputs,
+                probability: *probability,
+                value: row.iter().zip(&game.cols).map(|(a, q)| a * q).sum(),
+            })
+            .collect();
+        let moves2 = moves2
+            .into_iter()
+            .enumerate()
+            .zip(&game.cols)
+            .map(|((j, m), probability)| MoveValue {
+                inputs: m.inputs,
+                probability: *probability,
+                value: payoff
+                    .iter()
+                    .zip(&game.rows)
+                    .map(|(row, p)| row[j] * p)
+                    .sum(),
+            })
+            .collect();
+        Ok(Solution {
+            value: game.value,
+            moves: [moves1, moves2],
+        })
+    }
+}
+
+// Inputs that change the game in the same way, which only need to be searched once
+struct Move {
+    inputs: Vec<RawInput>,
+    valid_input: ValidInput,
+    // The spaces the move inks
+    footprint: BitBoard,
+}
+
+fn distinct_moves(board: &Board, player: &Player) -> Vec<Move> {
+    let mut moves: Vec<Move> = vec![];
+    let mut seen: HashMap<_, usize> = HashMap::new();
+    for (raw_input, valid_input) in legal_inputs(board, player) {
+        let (footprint, specials, special_activated) = match valid_input.clone().get() {
+            Input::Pass => (BitBoard::EMPTY, BitBoard::EMPTY, false),
+            Input::Place(placement) => {
+                let spaces = &placement.ink_spaces().0;
+                let specials = spaces
+                    .iter()
+                    .filter(|(_, space)| matches!(space, InkSpace::Special))
+                    .map(|(bp, _)| bp);
+                (
+                    BitBoard::from_positions(spaces.iter().map(|(bp, _)| bp)),
+                    BitBoard::from_positions(specials),
+                    placement.is_special_activated(),
+                )
+            }
+        };
+        // The card played matters too, since it leaves the hand
+        let key = (raw_input.hand_idx, special_activated, footprint, specials);
+        match seen.get(&key) {
+            Some(&idx) => moves[idx].inputs.push(raw_input),
+            None => {
+                seen.insert(key, moves.len());
+                moves.push(Move {
+                    inputs: vec![raw_input],
+                    valid_input,
+                    footprint,
+                });
+            }
+        }
+    }
+    moves
+}
+
+fn margin<R: DrawRng + Debug>(game_state: &GameState<R>) -> f64 {
+    let board = game_state.board();
+    board.count_inked_spaces(PlayerNum::P1) as f64 - board.count_inked_spaces(PlayerNum::P2) as f64
+}
+
+// The cards left to draw in a deck, one bit per deck slot
+fn drawable(deck: &Deck) -> u16 {
+    (0..DECK_SIZE)
+        .filter(|idx| *deck.index(parse_idx(*idx).unwrap()).1)
+        .fold(0, |mask, idx| mask | 1 << idx)
+}
+
+// Draws the given card of those left in each player's deck, player 1's first
+struct Draws {
+    picks: [usize; 2],
+    drawn: usize,
+}
+
+impl Draws {
+    fn new(pick1: usize, pick2: usize) -> Self {
+        Draws {
+            picks: [pick1, pick2],
+            drawn: 0,
+        }
+    }
+}
+
+impl DrawRng for Draws {
+    fn draw<T, I: Iterator<Item = T> + Sized>(&mut self, mut iter: I) -> Option<T> {
+        let pick = self.picks[self.drawn];
+        self.drawn += 1;
+        iter.nth(pick)
+    }
+
+    fn draw_hand<I: Iterator<Item = DeckIndex> + Sized>(&mut self, _iter: I) -> Hand {
+        unreachable!("Hands are only dealt at the start of a game")
+    }
+}
+
+struct Search {
+    max_positions: usize,
+    // Positions played out so far, whether or not they were already solved
+    positions: usize,
+    // Values of positions already solved, by their hash
+    values: HashMap<u64, f64>,
+}
+
+impl Search {
+    fn value<R: DrawRng + Clone + Debug>(
+        &mut self,
+        game_state: &GameState<R>,
+    ) -> Result<f64, SolverError> {
+        if game_state.turns_left() == 0 {
+            return Ok(margin(game_state));
+        }
+        let key = game_state.hash();
+        if let Some(value) = self.values.get(&key) {
+            return Ok(*value);
+        }
+        let moves = [PlayerNum::P1, PlayerNum::P2]
+            .map(|player_num| distinct_moves(game_state.board(), game_state.player(player_num)));
+        let value = solve_matrix_game(&self.payoff(game_state, &moves)?).value;
+        self.values.insert(key, value);
+        Ok(value)
+    }
+
+    // Counts positions that are about to be played out, giving up before doing the work
+    // if they would take the search over its budget
+    fn visit(&mut self, positions: usize) -> Result<(), SolverError> {
+        self.positions = self.positions.saturating_add(positions);
+        if self.positions > self.max_positions {
+            return Err(SolverError::TooManyPositions(self.max_positions));
+        }
+        Ok(())
+    }
+
+    // The expected margin after each pair of moves, with player 1's moves as rows
+    fn payoff<R: DrawRng + Clone + Debug>(
+        &mut self,
+        game_state: &GameState<R>,
+        moves: &[Vec<Move>; 2],
+    ) -> Result<Vec<Vec<f64>>, SolverError> {
+        if game_state.turns_left() == 1 {
+            let overlaps: usize = moves[0]
+                .iter()
+                .map(|m1| {
+                    moves[1]
+                        .iter()
+                        .filter(|m2| m1.footprint.intersects(&m2.footprint))
+                        .count()
+                })
+                .sum();
+            self.visit(moves[0].len() + moves[1].len() + overlaps)?;
+            return Ok(last_turn_payoff(game_state, moves));
+        }
+        let counts = [PlayerNum::P1, PlayerNum::P2].map(|player_num| {
+            drawable(game_state.player(player_num).deck())
+                .count_ones()
+                .max(1) as usize
+        });
+        self.visit(moves[0].len() * moves[1].len() * counts[0] * counts[1])?;
+        let mut payoff = vec![vec![0.0; moves[1].len()]; moves[0].len()];
+        for (m1, row) in moves[0].iter().zip(&mut payoff) {
+            for (m2, value) in moves[1].iter().zip(row) {
+                // Each player draws any of the cards left in their deck with equal chance
+                let mut total = 0.0;
+                for pick1 in 0..counts[0] {
+                    for pick2 in 0..counts[1] {
+                        let next = game_state.apply(
+                            m1.valid_input.clone(),
+                            m2.valid_input.clone(),
+                            &mut Draws::new(pick1, pick2),
+                        );
+                        total += self.value(&next)?;
+                    }
+                }
+                *value = total / (counts[0] * counts[1]) as f64;
+            }
+        }
+        Ok(payoff)
+    }
+}
+
+// On the last turn, moves that ink different spaces don't affect each other, so their
+// effects on the margin can be found separately and added up. Only moves that overlap need
+// to be played out together.
+fn last_turn_payoff<R: DrawRng + Clone + Debug>(
+    game_state: &GameState<R>,
+    moves: &[Vec<Move>; 2],
+) -> Vec<Vec<f64>> {
+    let before = margin(game_state);
+    // Passing is always legal and inks nothing, so it's a neutral move to play against
+    let pass = |moves: &[Move]| {
+        moves
+            .iter()
+            .find(|m| m.footprint.is_empty())
+            .unwrap()
+            .valid_input
+            .clone()
+    };
+    let (pass1, pass2) = (pass(&moves[0]), pass(&moves[1]));
+    let play = |input1: &ValidInput, input2: &ValidInput| {
+        margin(&game_state.apply(input1.clone(), input2.clone(), &mut Draws::new(0, 0)))
+    };
+    let gains1: Vec<f64> = moves[0]
+        .iter()
+        .map(|m| play(&m.valid_input, &pass2) - before)
+        .collect();
+    let gains2: Vec<f64> = moves[1]
+        .iter()
+        .map(|m| play(&pass1, &m.valid_input) - before)
+        .collect();
+    moves[0]
+        .iter()
+        .zip(&gains1)
+        .map(|(m1, gain1)| {
+            moves[1]
+                .iter()
+                .zip(&gains2)
+                .map(|(m2, gain2)| {
+                    if m1.footprint.intersects(&m2.footprint) {
+                        play(&m1.valid_input, &m2.valid_input)
+                    } else {
+                        before + gain1 + gain2
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// The optimal strategies of a zero-sum game where the row player gets the payoff and the
+// column player pays it
+#[derive(Debug)]
+struct MatrixGame {
+    value: f64,
+    rows: Vec<f64>,
+    cols: Vec<f64>,
+}
+
+// Solves the game with the simplex method. Shifting the payoffs to be positive makes the
+// value positive too, so the column player's strategy scaled by the inverse of the value
+// is the solution of: maximize sum(w) such that payoff * w <= 1 and w >= 0. The row
+// player's strategy is the dual solution, which the final tableau has for free.
+fn solve_matrix_game(payoff: &[Vec<f64>]) -> MatrixGame {
+    let rows = payoff.len();
+    let cols = payoff[0].len();
+    let min = payoff
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let shift = 1.0 - min;
+
+    // Columns are the column player's weights, then a slack variable per row, then the
+    // constraints' right hand sides
+    let rhs = cols + rows;
+    let mut tableau: Vec<Vec<f64>> = payoff
+        .iter()
+        .enumerate()
+        .map(|(i, payoff_row)| {
+            let mut row = vec![0.0; rhs + 1];
+            for (entry, a) in row.iter_mut().zip(payoff_row) {
+                *entry = a + shift;
+            }
+            row[cols + i] = 1.0;
+            row[rhs] = 1.0;
+            row
+        })
+        .collect();
+    let mut objective = vec![0.0; rhs + 1];
+    objective[..cols].fill(-1.0);
+    // The slack variables start out basic
+    let mut basis: Vec<usize> = (cols..rhs).collect();
+
+    // Bland's rule, taking the first improving column and breaking ties between rows by
+    // the lowest basic variable, so degenerate pivots can't cycle
+    while let Some(entering) = (0..rhs).find(|&j| objective[j] < -EPSILON) {
+        let mut leaving: Option<(usize, f64)> = None;
+        for (i, row) in tableau.iter().enumerate() {
+            if row[entering] <= EPSILON {
+                continue;
+            }
+            let ratio = row[rhs] / row[entering];
+            let better = match leaving {
+                None => true,
+                Some((best, best_ratio)) => {
+                    ratio < best_ratio - EPSILON
+                        || (ratio < best_ratio + EPSILON && basis[i] < basis[best])
+                }
+            };
+            if better {
+                leaving = Some((i, ratio));
+            }
+        }
+        // Every column has a positive entry in some row, so the program is bounded
+        let (leaving, _) = leaving.unwrap();
+
+        let pivot = tableau[leaving][entering];
+        tableau[leaving]
+            .iter_mut()
+            .for_each(|entry| *entry /= pivot);
+        let pivot_row = tableau[leaving].clone();
+        for (i, row) in tableau.iter_mut().enumerate() {
+            let factor = row[entering];
+            if i != leaving && factor != 0.0 {
+                row.iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(entry, p)| *entry -= factor * p);
+            }
+        }
+        let factor = objective[entering];
+        objective
+            .iter_mut()
+            .zip(&pivot_row)
+            .for_each(|(entry, p)| *entry -= factor * p);
+        basis[leaving] = entering;
+    }
+
+    let total = objective[rhs];
+    let mut col_strategy = vec![0.0; cols];
+    for (row, var) in tableau.iter().zip(&basis) {
+        if *var < cols {
+            col_strategy[*var] = row[rhs];
+        }
+    }
+    let row_strategy = objective[cols..rhs].to_vec();
+    MatrixGame {
+        value: 1.0 / total - shift,
+        rows: normalize(row_strategy),
+        cols: normalize(col_strategy),
+    }
+}
+
+// Clears rounding errors so the probabilities are non-negative and add up to one
+fn normalize(mut strategy: Vec<f64>) -> Vec<f64> {
+    strategy.iter_mut().for_each(|p| *p = p.max(0.0));
+    let total: f64 = strategy.iter().sum();
+    strategy.iter_mut().for_each(|p| *p /= total);
+    strategy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tableturf::ai::{Agent, GreedyAgent};
+    use crate::tableturf::game_state::DeckRng;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_matrix_game() {
+        // Matching pennies
+        let game = solve_matrix_game(&[vec![1.0, -1.0], vec![-1.0, 1.0]]);
+        assert_close(game.value, 0.0);
+        assert_close(game.rows[0], 0.5);
+        assert_close(game.cols[0], 0.5);
+
+        // Rock paper scissors, with a bonus for winning with rock
+        let game = solve_matrix_game(&[
+            vec![0.0, -1.0, 2.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-1.0, 1.0, 0.0],
+        ]);
+        assert_close(game.value, 1.0 / 12.0);
+        for (p, expected) in game.rows.iter().zip([0.25, 5.0 / 12.0, 1.0 / 3.0]) {
+            assert_close(*p, expected);
+        }
+        for (q, expected) in game.cols.iter().zip([1.0 / 3.0, 5.0 / 12.0, 0.25]) {
+            assert_close(*q, expected);
+        }
+
+        // A saddle point, where neither player needs to mix
+        let game = solve_matrix_game(&[vec![3.0, 1.0, 4.0], vec![5.0, 2.0, 6.0]]);
+        assert_close(game.value, 2.0);
+        assert_eq!(game.rows, vec![0.0, 1.0]);
+        assert_eq!(game.cols, vec![0.0, 1.0, 0.0]);
+    }
+
+    fn endgame(turns_left: u32, seed: u64) -> GameState<DeckRng> {
+        let mut game_state = GameState::with_rng(12, DeckRng::from_seed(seed));
+        while game_state.turns_left() > turns_left {
+            let board = game_state.board().clone();
+            let [input1, input2] = [PlayerNum::P1, PlayerNum::P2].map(|player_num| {
+                let player = game_state.player(player_num);
+                ValidInput::new(GreedyAgent.choose(&board, player), &board, player).unwrap()
+            });
+            game_state.update(input1, input2);
+        }
+        game_state
+    }
+
+    // Neither player can do better than the value by changing their strategy alone
+    fn assert_equilibrium(solution: &Solution) {
+        for (moves, sign) in solution.moves.iter().zip([1.0, -1.0]) {
+            assert_close(moves.iter().map(|m| m.probability).sum(), 1.0);
+            for m in moves {
+                assert!(sign * (m.value - solution.value) < 1e-6);
+                if m.probability > 1e-6 {
+                    assert_close(m.value, solution.value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_last_turn() {
+        let game_state = endgame(1, 4);
+        let solution = Solver::default().solve(&game_state).unwrap();
+        assert_equilibrium(&solution);
+
+        // Adding up separate moves gives the same margins as playing them out
+        let moves = [PlayerNum::P1, PlayerNum::P2]
+            .map(|player_num| distinct_moves(game_state.board(), game_state.player(player_num)));
+        let payoff = last_turn_payoff(&game_state, &moves);
+        for (m1, row) in moves[0].iter().zip(&payoff).step_by(7) {
+            for (m2, value) in moves[1].iter().zip(row).step_by(5) {
+                let next = game_state.apply(
+                    m1.valid_input.clone(),
+                    m2.valid_input.clone(),
+                    &mut DeckRng::from_seed(0),
+                );
+                assert_eq!(*value, margin(&next));
+            }
+        }
+
+        // Every legal input is part of exactly one move
+        let inputs: usize = solution.moves[0].iter().map(|m| m.inputs.len()).sum();
+        let legal = legal_inputs(game_state.board(), game_state.player(PlayerNum::P1));
+        assert_eq!(inputs, legal.len());
+    }
+
+    #[test]
+    fn test_two_turns() {
+        let game_state = endgame(2, 3);
+        let solution = Solver::default().solve(&game_state).unwrap();
+        assert_equilibrium(&solution);
+
+        let inputs: usize = solution.moves[1].iter().map(|m| m.inputs.len()).sum();
+        let legal = legal_inputs(game_state.board(), game_state.player(PlayerNum::P2));
+        assert_eq!(inputs, legal.len());
+    }
+
+    #[test]
+    fn test_errors() {
+        let game_state = endgame(0, 1);
+        assert_eq!(
+            Solver::default().solve(&game_state).unwrap_err(),
+            SolverError::GameOver
+        );
+
+        let game_state = endgame(2, 1);
+        assert_eq!(
+            Solver::new(10).solve(&game_state).unwrap_err(),
+            SolverError::TooManyPositions(10)
+        );
+
+        // Positions that are played out but never stored still count
+        let game_state = endgame(3, 7);
+        assert_eq!(
+            Solver::new(1000).solve(&game_state).unwrap_err(),
+            SolverError::TooManyPositions(1000)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::tableturf::board::{player_idx, BoardSpace};
+use crate::tableturf::player::PlayerNum;
+
+// Zobrist keys are made by mixing a number that says what they stand for, rather than
+// drawn at random into a table, so hashes are the same in every process and on every
+// platform. Replays and transposition tables can be shared between them.
+fn key(kind: u64, value: u64) -> u64 {
+    // splitmix64's finalizer
+    let mut z = (kind << 56 | value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce5_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const SPACE: u64 = 1;
+const HAND: u64 = 2;
+const SPECIAL: u64 = 3;
+const TURNS: u64 = 4;
+const DECK: u64 = 5;
+
+pub(crate) fn space_key(x: usize, y: usize, space: BoardSpace) -> u64 {
+    let state = match space {
+        BoardSpace::Empty => 0,
+        BoardSpace::Ink { player_num } => 1 + player_idx(player_num) as u64,
+        BoardSpace::Special {
+            player_num,
+            is_activated,
+        } => 3 + player_idx(player_num) as u64 * 2 + is_activated as u64,
+        BoardSpace::Wall => 7,
+        BoardSpace::OutOfBounds => 8,
+    };
+    key(SPACE, (y as u64) << 24 | (x as u64) << 8 | state)
+}
+
+// The card from the given deck slot being in the given slot of the player's hand
+pub(crate) fn hand_key(player_num: PlayerNum, hand_slot: usize, deck_slot: usize) -> u64 {
+    let player = player_idx(player_num) as u64;
+    key(
+        HAND,
+        player << 16 | (hand_slot as u64) << 8 | deck_slot as u64,
+    )
+}
+
+// The card in the given deck slot still being in the player's deck, waiting to be drawn
+pub(crate) fn drawable_key(player_num: PlayerNum, deck_slot: usize) -> u64 {
+    key(
+        DECK,
+        (player_idx(player_num) as u64) << 8 | deck_slot as u64,
+    )
+}
+
+pub(crate) fn special_key(player_num: PlayerNum, special: u32) -> u64 {
+    key(
+        SPECIAL,
+        (player_idx(player_num) as u64) << 32 | special as u64,
+    )
+}
+
+pub(crate) fn turns_key(turns_left: u32) -> u64 {
+    key(TURNS, turns_left as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let spaces = [
+            BoardSpace::Empty,
+            BoardSpace::Ink {
+                player_num: PlayerNum::P1,
+            },
+            BoardSpace::Ink {
+                player_num: PlayerNum::P2,
+            },
+            BoardSpace::Special {
+                player_num: PlayerNum::P1,
+                is_activated: false,
+            },
+            BoardSpace::Special {
+                player_num: PlayerNum::P1,
+                is_activated: true,
+            },
+            BoardSpace::Special {
+                player_num: PlayerNum::P2,
+                is_activated: false,
+            },
+            BoardSpace::Special {
+                player_num: PlayerNum::P2,
+                is_activated: true,
+            },
+            BoardSpace::Wall,
+            BoardSpace::OutOfBounds,
+        ];
+        let mut keys = HashSet::new();
+        for y in 0..40 {
+            for x in 0..40 {
+                for space in spaces {
+                    assert!(keys.insert(space_key(x, y, space)));
+                }
+            }
+        }
+        for player_num in [PlayerNum::P1, PlayerNum::P2] {
+            for hand_slot in 0..4 {
+                for deck_slot in 0..15 {
+                    assert!(keys.insert(hand_key(player_num, hand_slot, deck_slot)));
+                }
+            }
+            for deck_slot in 0..15 {
+                assert!(keys.insert(drawable_key(player_num, deck_slot)));
+            }
+            for special in 0..50 {
+                assert!(keys.insert(special_key(player_num, special)));
+            }
+        }
+        for turns_left in 0..50 {
+            assert!(keys.insert(turns_key(turns_left)));
+        }
+    }
+}
@@ -14,8 +14,6 @@ use std::rc::Rc;
 use yew::prelude::*;
 use yew_agent::{Bridge, Bridged};
 
-const CURSOR_OFFSET: usize = 4;
-
 #[derive(Debug, Clone)]
 pub enum Message {
     GameInput(GameInput),
@@ -113,7 +111,7 @@ fn process_response(phase: &mut Phase, response: String) {
                 player: game_state.player,
                 hand_idx: HandIndex::H1,
                 rotation: Rotation::Zero,
-                turns_left: 12,
+                turns_left: game_state.turns_left,
             });
         }
         Phase::Battling(ref mut state) => process_battle_response(response, state),
@@ -128,11 +126,13 @@ fn process_battle_response(response: String, state: &mut BattleState) {
             state.board = game_state.board;
             state.hand_idx = HandIndex::H1;
             state.player = game_state.player.clone();
+            state.turns_left = game_state.turns_left;
             state.phase = BattlePhase::Input;
         }
         BattlePhase::Input => {}
         BattlePhase::WaitingForOpponentInput => {
-            if state.turns_left == 0 {
+            // The server sends the outcome instead of a new state after the last turn
+            if state.turns_left == 1 {
                 let game_state: GameEnd = serde_json::from_str(&response).unwrap();
                 state.phase = BattlePhase::GameEnd;
             } else {
@@ -140,8 +140,8 @@ fn process_battle_response(response: String, state: &mut BattleState) {
                 state.board = game_state.board;
                 state.hand_idx = HandIndex::H1;
                 state.player = game_state.player.clone();
+                state.turns_left = game_state.turns_left;
                 state.phase = BattlePhase::Input;
-                state.turns_left -= 1;
             }
         }
         BattlePhase::GameEnd => {}
@@ -151,14 +151,12 @@ fn process_battle_response(response: String, state: &mut BattleState) {
 // Makes sure the player has placed their ink in a legal position before sending to the server
 // Returns the raw input that will be sent to the server
 fn validate_placement(x: usize, y: usize, state: &BattleState) -> Option<RawInput> {
-    let x = usize::checked_sub(x, CURSOR_OFFSET)?;
-    let y = usize::checked_sub(y, CURSOR_OFFSET)?;
-
+    // The card's pivot goes on the space under the cursor
     let input = RawInput {
         hand_idx: state.hand_idx,
         action: Action::Place(RawPlacement {
-            x,
-            y,
+            x: x as i32,
+            y: y as i32,
             special_activated: false,
             rotation: state.rotation,
         }),
@@ -302,6 +300,11 @@ fn view_input(ctx: &Context<Battle>, state: &BattleState) -> Html {
     let special = state.player.special;
     let rotation = state.rotation;
     let player_num = player.player_num();
+    let evaluation = board.evaluate();
+    let (own, other) = match player_num {
+        PlayerNum::P1 => (evaluation.projected[0], evaluation.projected[1]),
+        PlayerNum::P2 => (evaluation.projected[1], evaluation.projected[0]),
+    };
     let hand = player.hand().clone();
     let deck = player.deck().clone();
     let (card1, _) = deck.index(hand[HandIndex::H1]);
@@ -355,6 +358,8 @@ fn view_input(ctx: &Context<Battle>, state: &BattleState) -> Html {
                     PlayerNum::P1 => "1",
                     PlayerNum::P2 => "2",
                 })}</div>
+                <div>{format!("Projected score: {:.0} to {:.0}", own, other)}</div>
+                <div>{format!("Win chance: {:.0}%", evaluation.win_probability(player_num) * 100.0)}</div>
             </div>
             <div class={classes!("special-gauge")}>{format!("Special gauge: {}", special)}</div>
             <button class={classes!("deck")}>{"View deck"}</button>
@@ -432,6 +437,8 @@ pub fn board(props: &BoardProps) -> Html {
 fn update_cursor(x: usize, y: usize, card: &Card, rotation: Rotation) -> HashSet<(usize, usize)> {
     let mut cursor = HashSet::new();
     let spaces = common::rotate_input(card, rotation);
+    // The pivot is under the cursor, so the card turns in place when it's rotated
+    let (pivot_x, pivot_y) = common::rotate_pivot(card, rotation);
     let ink_spaces = spaces
         .iter()
         .flatten()
@@ -441,12 +448,13 @@ fn update_cursor(x: usize, y: usize, card: &Card, rotation: Rotation) -> HashSet
         let card_x = idx % CARD_WIDTH;
         let card_y = idx / CARD_WIDTH;
         match (
-            usize::checked_sub(x + card_x, CURSOR_OFFSET),
-            usize::checked_sub(y + card_y, CURSOR_OFFSET),
+            usize::checked_sub(x + card_x, pivot_x),
+            usize::checked_sub(y + card_y, pivot_y),
         ) {
             (Some(x), Some(y)) => {
                 cursor.insert((x, y));
             }
+            // Spaces off the top or left of the board aren't drawn
             _ => (),
         }
     }
@@ -544,7 +552,7 @@ pub struct CardProps {
 }
 
 #[function_component(CardComponent)]
-fn card(props: &CardProps) -> Html {
+pub fn card(props: &CardProps) -> Html {
     let card = &props.card;
     let callback = props.onclick.clone();
     let hand_idx = props.handidx;
@@ -557,6 +565,7 @@ fn card(props: &CardProps) -> Html {
                     card.spaces().iter().flatten().map(|s| card_space(s)).collect::<Html>()
                 }
             </div>
+            <div>{format!("Squares: {}", card.size())}</div>
             <div>{format!("Priority: {}", card.priority())}</div>
             <div>{format!("Special cost: {}", card.special())}</div>
         </button>
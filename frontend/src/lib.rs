@@ -6,6 +6,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 mod battle;
+mod puzzle;
 mod ws;
 pub mod worker;
 
@@ -20,6 +21,8 @@ pub struct UserInner {
 pub enum Route {
     #[at("/battle")]
     Battle,
+    #[at("/puzzles/:id")]
+    Puzzle { id: String },
     #[at("/")]
     Home,
     #[not_found]
@@ -31,6 +34,7 @@ fn switch(routes: Route) -> Html {
     match routes {
         Route::Home => html! { <Login /> },
         Route::Battle => html! { <battle::Battle /> },
+        Route::Puzzle { id } => html! { <puzzle::PuzzlePage {id} /> },
         Route::NotFound => html! { <h1>{ "404" }</h1> },
     }
 }
@@ -0,0 +1,251 @@
+use crate::battle::{BoardComponent, CardComponent, PassButtonComponent};
+use common::puzzle::{Goal, InOrder, Puzzle, Verdict};
+use common::{
+    Action, GameState, HandIndex, PlayerNum, RawInput, RawPlacement, Rotation, ValidInput,
+};
+use gloo::console::log;
+use reqwasm::http::Request as HttpRequest;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct PuzzleProps {
+    pub id: String,
+}
+
+pub enum Message {
+    Loaded(Box<Puzzle>),
+    Failed(String),
+    ClickCard(HandIndex),
+    RotateCard(Rotation),
+    ToggleSpecial,
+    ClickSpace(usize, usize),
+    Pass,
+    Retry,
+}
+
+// Puzzles are fetched once and then played entirely in the browser, with the opponent's
+// moves taken from the puzzle's script
+enum Phase {
+    Loading,
+    Failed(String),
+    Playing(PuzzleState),
+    Done(PuzzleState, Verdict),
+}
+
+struct PuzzleState {
+    puzzle: Puzzle,
+    game_state: GameState<InOrder>,
+    hand_idx: HandIndex,
+    rotation: Rotation,
+    special_activated: bool,
+    // The moves played so far
+    line: Vec<RawInput>,
+}
+
+impl PuzzleState {
+    fn new(puzzle: Puzzle) -> Result<Self, String> {
+        let game_state = puzzle.game_state().map_err(|err| err.to_string())?;
+        Ok(PuzzleState {
+            puzzle,
+            game_state,
+            hand_idx: HandIndex::H1,
+            rotation: Rotation::Zero,
+            special_activated: false,
+            line: vec![],
+        })
+    }
+
+    fn opponent(&self) -> PlayerNum {
+        match self.puzzle.player_num {
+            PlayerNum::P1 => PlayerNum::P2,
+            PlayerNum::P2 => PlayerNum::P1,
+        }
+    }
+
+    // Plays the input against the opponent's scripted move. Illegal inputs are ignored.
+    fn play(&mut self, input: RawInput) -> Result<(), String> {
+        let board = self.game_state.board();
+        let player = self.game_state.player(self.puzzle.player_num);
+        let Ok(valid_input) = ValidInput::new(input.clone(), board, player) else {
+            return Ok(());
+        };
+        let scripted = self.puzzle.script[self.line.len()].clone();
+        let opponent = self.game_state.player(self.opponent());
+        let scripted = ValidInput::new(scripted, board, opponent)
+            .map_err(|err| format!("The opponent's scripted move is illegal: {}", err))?;
+        match self.puzzle.player_num {
+            PlayerNum::P1 => self.game_state.update(valid_input, scripted),
+            PlayerNum::P2 => self.game_state.update(scripted, valid_input),
+        };
+        self.line.push(input);
+        self.hand_idx = HandIndex::H1;
+        self.special_activated = false;
+        Ok(())
+    }
+}
+
+async fn fetch_puzzle(id: String) -> Message {
+    let url = format!("http://localhost:8000/puzzles/{}", id);
+    let response = match HttpRequest::get(&url).send().await {
+        Ok(response) if response.ok() => response,
+        Ok(_) => return Message::Failed(format!("Puzzle {} not found", id)),
+        Err(err) => return Message::Failed(err.to_string()),
+    };
+    match response.json().await {
+        Ok(puzzle) => Message::Loaded(Box::new(puzzle)),
+        Err(err) => Message::Failed(err.to_string()),
+    }
+}
+
+pub struct PuzzlePage {
+    phase: Phase,
+}
+
+impl Component for PuzzlePage {
+    type Message = Message;
+    type Properties = PuzzleProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_future(fetch_puzzle(ctx.props().id.clone()));
+        Self {
+            phase: Phase::Loading,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let phase = std::mem::replace(&mut self.phase, Phase::Loading);
+        self.phase = match (msg, phase) {
+            (Message::Loaded(puzzle), _) => match PuzzleState::new(*puzzle) {
+                Ok(state) => Phase::Playing(state),
+                Err(err) => Phase::Failed(err),
+            },
+            (Message::Failed(err), _) => Phase::Failed(err),
+            (Message::Retry, Phase::Done(state, _)) | (Message::Retry, Phase::Playing(state)) => {
+                match PuzzleState::new(state.puzzle) {
+                    Ok(state) => Phase::Playing(state),
+                    Err(err) => Phase::Failed(err),
+                }
+            }
+            (msg, Phase::Playing(mut state)) => {
+                let input = match msg {
+                    Message::ClickCard(hand_idx) => {
+                        state.hand_idx = hand_idx;
+                        None
+                    }
+                    Message::RotateCard(rotation) => {
+                        state.rotation = rotation;
+                        None
+                    }
+                    Message::ToggleSpecial => {
+                        state.special_activated = !state.special_activated;
+                        None
+                    }
+                    Message::Pass => Some(RawInput {
+                        hand_idx: state.hand_idx,
+                        action: Action::Pass,
+                    }),
+                    Message::ClickSpace(x, y) => Some(placement(x, y, &state)),
+                    _ => None,
+                };
+                match input.map(|input| state.play(input)) {
+                    Some(Err(err)) => Phase::Failed(err),
+                    _ if state.game_state.turns_left() == 0 => {
+                        let verdict = state.puzzle.verdict(&state.game_state);
+                        Phase::Done(state, verdict)
+                    }
+                    _ => Phase::Playing(state),
+                }
+            }
+            (_, phase) => {
+                log!("Ignoring message outside of play");
+                phase
+            }
+        };
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.phase {
+            Phase::Loading => html! { "Loading puzzle..." },
+            Phase::Failed(err) => html! { format!("Failed to load puzzle: {}", err) },
+            Phase::Playing(state) => view_puzzle(ctx, state),
+            Phase::Done(state, verdict) => view_done(ctx, state, verdict),
+        }
+    }
+}
+
+// The input for placing the selected card with its pivot on the space under the cursor
+fn placement(x: usize, y: usize, state: &PuzzleState) -> RawInput {
+    RawInput {
+        hand_idx: state.hand_idx,
+        action: Action::Place(RawPlacement {
+            x: x as i32,
+            y: y as i32,
+            special_activated: state.special_activated,
+            rotation: state.rotation,
+        }),
+    }
+}
+
+fn goal(goal: &Goal) -> String {
+    match goal {
+        Goal::WinBy(margin) => format!("Win by at least {}", margin),
+        Goal::InkAtLeast(spaces) => format!("Ink at least {} spaces", spaces),
+    }
+}
+
+fn view_puzzle(ctx: &Context<PuzzlePage>, state: &PuzzleState) -> Html {
+    let onclick_space = ctx.link().callback(|(x, y)| Message::ClickSpace(x, y));
+    let onclick_card = ctx.link().callback(Message::ClickCard);
+    let onrightclick_space = ctx.link().callback(Message::RotateCard);
+    let onclick_pass = ctx.link().callback(|_| Message::Pass);
+    let onclick_special = ctx.link().callback(|_| Message::ToggleSpecial);
+    let player = state.game_state.player(state.puzzle.player_num);
+    let selected_card = player.get_card(state.hand_idx).clone();
+    html! {
+        <section id="page">
+            <BoardComponent
+                board={state.game_state.board().clone()}
+                handidx={state.hand_idx}
+                selectedcard={selected_card}
+                rotation={state.rotation}
+                onclick={onclick_space}
+                onrightclick={onrightclick_space}
+            />
+            <div class={classes!("choices")}>
+                {
+                    HandIndex::ALL.into_iter().map(|hand_idx| html! {
+                        <CardComponent
+                            card={player.get_card(hand_idx).clone()}
+                            onclick={onclick_card.clone()}
+                            handidx={hand_idx}
+                            selected={state.hand_idx == hand_idx}/>
+                    }).collect::<Html>()
+                }
+                <PassButtonComponent onclick={onclick_pass}/>
+                <button onclick={onclick_special}>
+                    {if state.special_activated { "Special: on" } else { "Special: off" }}
+                </button>
+            </div>
+            <div class={classes!("timer")}>
+                <div>{&state.puzzle.name}</div>
+                <div>{&state.puzzle.description}</div>
+                <div>{goal(&state.puzzle.goal)}</div>
+                <div>{format!("Turns left: {}", state.game_state.turns_left())}</div>
+            </div>
+            <div class={classes!("special-gauge")}>{format!("Special gauge: {}", player.special)}</div>
+        </section>
+    }
+}
+
+fn view_done(ctx: &Context<PuzzlePage>, state: &PuzzleState, verdict: &Verdict) -> Html {
+    let onclick_retry = ctx.link().callback(|_| Message::Retry);
+    let [own, other] = verdict.ink;
+    html! {
+        <section>
+            <div>{if verdict.solved { "Solved!" } else { "Not quite." }}</div>
+            <div>{format!("You inked {} spaces to your opponent's {}. {}.", own, other, goal(&state.puzzle.goal))}</div>
+            <button onclick={onclick_retry}>{"Try again"}</button>
+        </section>
+    }
+}
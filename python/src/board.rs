@@ -0,0 +1,201 @@
+use common::{Board, BoardSpace, Card, CardSpace, InkSpace, PlayerNum, CARD_WIDTH};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+// Spaces are written the same way the board prints them
+fn parse_space(code: &str) -> PyResult<BoardSpace> {
+    let special = |player_num, is_activated| BoardSpace::Special {
+        player_num,
+        is_activated,
+    };
+    Ok(match code {
+        "MT" => BoardSpace::Empty,
+        "I1" => BoardSpace::Ink {
+            player_num: PlayerNum::P1,
+        },
+        "I2" => BoardSpace::Ink {
+            player_num: PlayerNum::P2,
+        },
+        "S1" => special(PlayerNum::P1, false),
+        "S2" => special(PlayerNum::P2, false),
+        "A1" => special(PlayerNum::P1, true),
+        "A2" => special(PlayerNum::P2, true),
+        "WW" => BoardSpace::Wall,
+        "OB" => BoardSpace::OutOfBounds,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown board space {}. Expected one of MT, I1, I2, S1, S2, A1, A2, WW or OB",
+                code
+            )))
+        }
+    })
+}
+
+#[pyclass(name = "Board", module = "tableturf")]
+#[derive(Clone)]
+pub struct PyBoard(pub Board);
+
+#[pymethods]
+impl PyBoard {
+    // Builds a board from rows of spaces
+    #[new]
+    fn new(rows: Vec<Vec<String>>) -> PyResult<Self> {
+        let spaces = rows
+            .iter()
+            .map(|row| row.iter().map(|code| parse_space(code)).collect())
+            .collect::<PyResult<_>>()?;
+        Board::new(spaces)
+            .map(PyBoard)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.0.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.0.height()
+    }
+
+    // Every space, row by row
+    fn rows(&self) -> Vec<Vec<String>> {
+        (0..self.0.height())
+            .map(|y| {
+                (0..self.0.width())
+                    .map(|x| self.0.get_space(x, y).to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn get_space(&self, x: usize, y: usize) -> String {
+        self.0.get_space(x, y).to_string()
+    }
+
+    fn count_inked_spaces(&self, player: u8) -> PyResult<u32> {
+        Ok(self.0.count_inked_spaces(player_num(player)?))
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __eq__(&self, other: &PyBoard) -> bool {
+        self.0 == other.0
+    }
+}
+
+pub fn player_num(player: u8) -> PyResult<PlayerNum> {
+    match player {
+        1 => Ok(PlayerNum::P1),
+        2 => Ok(PlayerNum::P2),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown player {}. Expected 1 or 2",
+            player
+        ))),
+    }
+}
+
+// Card spaces are 0 when empty, 1 for ink and 2 for special ink
+fn space_value(space: CardSpace) -> u8 {
+    match space {
+        None => 0,
+        Some(InkSpace::Normal) => 1,
+        Some(InkSpace::Special) => 2,
+    }
+}
+
+#[pyclass(name = "Card", module = "tableturf")]
+#[derive(Clone)]
+pub struct PyCard(pub Card);
+
+#[pymethods]
+impl PyCard {
+    #[new]
+    fn new(name: String, priority: u32, special: u32, spaces: Vec<Vec<u8>>) -> PyResult<Self> {
+        let mut grid = [[None; CARD_WIDTH]; CARD_WIDTH];
+        if spaces.len() != CARD_WIDTH || spaces.iter().any(|row| row.len() != CARD_WIDTH) {
+            return Err(PyValueError::new_err(format!(
+                "Card spaces must be {} rows of {} spaces",
+                CARD_WIDTH, CARD_WIDTH
+            )));
+        }
+        for (grid_row, row) in grid.iter_mut().zip(&spaces) {
+            for (grid_space, value) in grid_row.iter_mut().zip(row) {
+                *grid_space = match value {
+                    0 => None,
+                    1 => Some(InkSpace::Normal),
+                    2 => Some(InkSpace::Special),
+                    _ => {
+                        return Err(PyValueError::new_err(format!(
+                            "Unknown card space {}. Expected 0, 1 or 2",
+                            value
+                        )))
+                    }
+                };
+            }
+        }
+        Ok(PyCard(Card::new(name, priority, grid, special)))
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    #[getter]
+    fn priority(&self) -> u32 {
+        self.0.priority()
+    }
+
+    #[getter]
+    fn special(&self) -> u32 {
+        self.0.special()
+    }
+
+    #[getter]
+    fn spaces(&self) -> Vec<Vec<u8>> {
+        self.0
+            .spaces()
+            .iter()
+            .map(|row| row.iter().map(|space| space_value(*space)).collect())
+            .collect()
+    }
+
+    // The number of spaces the card inks
+    #[getter]
+    fn size(&self) -> u32 {
+        self.0.size()
+    }
+
+    // The (x, y) space in the grid that placements put on the board coordinates given
+    #[getter]
+    fn pivot(&self) -> (usize, usize) {
+        self.0.pivot()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Card(name={:?}, priority={}, special={})",
+            self.0.name(),
+            self.0.priority(),
+            self.0.special()
+        )
+    }
+
+    fn __eq__(&self, other: &PyCard) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[pyfunction]
+pub fn default_board() -> PyBoard {
+    PyBoard(common::default_board())
+}
+
+#[pyfunction]
+pub fn default_deck() -> Vec<PyCard> {
+    common::default_deck().into_iter().map(PyCard).collect()
+}
@@ -0,0 +1,127 @@
+use crate::board::{player_num, PyBoard, PyCard};
+use crate::game::{decks, PyMove};
+use common::env::{self, Env, EnvConfig, Observation, Opponent, Step};
+use common::DEFAULT_TURNS;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn opponent(name: &str) -> PyResult<Opponent> {
+    match name {
+        "self-play" => Ok(Opponent::SelfPlay),
+        "random" => Ok(Opponent::Random),
+        "greedy" => Ok(Opponent::Greedy),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown opponent {}. Expected self-play, random or greedy",
+            name
+        ))),
+    }
+}
+
+// Observations are flat lists, which numpy.asarray turns into arrays. The shapes of the
+// board and hand features are given by the module's constants.
+fn observation_dict(py: Python<'_>, observation: Observation) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("board", observation.board)?;
+    dict.set_item("hand", observation.hand)?;
+    dict.set_item("scalars", observation.scalars)?;
+    dict.set_item("mask", observation.mask)?;
+    Ok(dict)
+}
+
+type StepTuple<'py> = (Bound<'py, PyDict>, f32, bool);
+
+fn step_tuple(py: Python<'_>, step: Step) -> PyResult<StepTuple<'_>> {
+    Ok((
+        observation_dict(py, step.observation)?,
+        step.reward,
+        step.done,
+    ))
+}
+
+fn env_error(err: env::EnvError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+#[pyclass(name = "Env", module = "tableturf")]
+pub struct PyEnv(Env);
+
+#[pymethods]
+impl PyEnv {
+    // Plays seat 1 or 2 against an opponent, or both seats in self-play
+    #[new]
+    #[pyo3(signature = (
+        opponent = "random",
+        seat = 1,
+        turns = DEFAULT_TURNS,
+        seed = 0,
+        board = None,
+        decks = None
+    ))]
+    fn new(
+        opponent: &str,
+        seat: u8,
+        turns: u32,
+        seed: u64,
+        board: Option<PyBoard>,
+        decks: Option<[Vec<PyCard>; 2]>,
+    ) -> PyResult<Self> {
+        let config = EnvConfig {
+            board: board.map_or_else(common::default_board, |board| board.0),
+            decks: self::decks(decks)?,
+            turns,
+            opponent: self::opponent(opponent)?,
+            seat: player_num(seat)?,
+        };
+        Ok(PyEnv(Env::new(config, seed)))
+    }
+
+    fn reset<'py>(&mut self, py: Python<'py>, seed: u64) -> PyResult<Bound<'py, PyDict>> {
+        observation_dict(py, self.0.reset(seed))
+    }
+
+    fn observe<'py>(&self, py: Python<'py>, player: u8) -> PyResult<Bound<'py, PyDict>> {
+        observation_dict(py, self.0.observe(player_num(player)?))
+    }
+
+    // Returns the observation, reward and whether the game is over
+    fn step<'py>(&mut self, py: Python<'py>, action: usize) -> PyResult<StepTuple<'py>> {
+        step_tuple(py, self.0.step(action).map_err(env_error)?)
+    }
+
+    fn step_both<'py>(
+        &mut self,
+        py: Python<'py>,
+        action1: usize,
+        action2: usize,
+    ) -> PyResult<(StepTuple<'py>, StepTuple<'py>)> {
+        let [step1, step2] = self.0.step_both([action1, action2]).map_err(env_error)?;
+        Ok((step_tuple(py, step1)?, step_tuple(py, step2)?))
+    }
+
+    #[getter]
+    fn done(&self) -> bool {
+        self.0.is_done()
+    }
+
+    #[getter]
+    fn board(&self) -> PyBoard {
+        PyBoard(self.0.game_state().board().clone())
+    }
+
+    #[getter]
+    fn turns_left(&self) -> u32 {
+        self.0.game_state().turns_left()
+    }
+}
+
+// The move an action stands for from the player's point of view, or None if it's off the board
+#[pyfunction]
+pub fn decode_action(action: usize, board: &PyBoard, player: u8) -> PyResult<Option<PyMove>> {
+    Ok(env::decode_action(action, &board.0, player_num(player)?).map(PyMove))
+}
+
+#[pyfunction]
+pub fn encode_action(input: &PyMove, board: &PyBoard, player: u8) -> PyResult<Option<usize>> {
+    Ok(env::encode_action(&input.0, &board.0, player_num(player)?))
+}
@@ -0,0 +1,269 @@
+use crate::board::{player_num, PyBoard, PyCard};
+use common::ai::legal_inputs;
+use common::{
+    Action, Card, DeckRng, GameState, HandIndex, Outcome, PlayerNum, RawInput, RawPlacement,
+    Rotation, TurnReport, ValidInput, DECK_SIZE, DEFAULT_TURNS,
+};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn hand_index(hand_idx: usize) -> PyResult<HandIndex> {
+    HandIndex::ALL
+        .get(hand_idx)
+        .copied()
+        .ok_or_else(|| PyIndexError::new_err(format!("Hand index {} out of range", hand_idx)))
+}
+
+// A player's input for a turn: passing a card in their hand, or placing it with its pivot
+// at x and y
+#[pyclass(name = "Move", module = "tableturf")]
+#[derive(Clone)]
+pub struct PyMove(pub RawInput);
+
+impl PyMove {
+    fn placement(&self) -> Option<&RawPlacement> {
+        match &self.0.action {
+            Action::Pass => None,
+            Action::Place(placement) => Some(placement),
+        }
+    }
+}
+
+#[pymethods]
+impl PyMove {
+    #[staticmethod]
+    fn pass_card(hand_idx: usize) -> PyResult<Self> {
+        Ok(PyMove(RawInput {
+            hand_idx: hand_index(hand_idx)?,
+            action: Action::Pass,
+        }))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (hand_idx, x, y, rotation = 0, special = false))]
+    fn place(hand_idx: usize, x: i32, y: i32, rotation: usize, special: bool) -> PyResult<Self> {
+        let rotation = *Rotation::ALL.get(rotation).ok_or_else(|| {
+            PyValueError::new_err(format!("Unknown rotation {}. Expected 0 to 3", rotation))
+        })?;
+        Ok(PyMove(RawInput {
+            hand_idx: hand_index(hand_idx)?,
+            action: Action::Place(RawPlacement {
+                x,
+                y,
+                special_activated: special,
+                rotation,
+            }),
+        }))
+    }
+
+    #[getter]
+    fn hand_idx(&self) -> usize {
+        HandIndex::ALL
+            .iter()
+            .position(|h| *h == self.0.hand_idx)
+            .unwrap()
+    }
+
+    #[getter]
+    fn is_pass(&self) -> bool {
+        matches!(self.0.action, Action::Pass)
+    }
+
+    #[getter]
+    fn x(&self) -> Option<i32> {
+        self.placement().map(|placement| placement.x)
+    }
+
+    #[getter]
+    fn y(&self) -> Option<i32> {
+        self.placement().map(|placement| placement.y)
+    }
+
+    // Number of counter-clockwise quarter turns
+    #[getter]
+    fn rotation(&self) -> Option<usize> {
+        self.placement().map(|placement| {
+            Rotation::ALL
+                .iter()
+                .position(|r| *r == placement.rotation)
+                .unwrap()
+        })
+    }
+
+    #[getter]
+    fn special(&self) -> bool {
+        self.placement()
+            .is_some_and(|placement| placement.special_activated)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.placement() {
+            None => format!("Move.pass_card({})", self.hand_idx()),
+            Some(placement) => format!(
+                "Move.place({}, {}, {}, rotation={}, special={})",
+                self.hand_idx(),
+                placement.x,
+                placement.y,
+                self.rotation().unwrap(),
+                if placement.special_activated {
+                    "True"
+                } else {
+                    "False"
+                }
+            ),
+        }
+    }
+}
+
+pub fn decks(decks: Option<[Vec<PyCard>; 2]>) -> PyResult<[[Card; DECK_SIZE]; 2]> {
+    let Some(decks) = decks else {
+        return Ok([common::default_deck(), common::default_deck()]);
+    };
+    let [deck1, deck2] = decks.map(|deck| {
+        let len = deck.len();
+        let cards: Vec<Card> = deck.into_iter().map(|card| card.0).collect();
+        cards.try_into().map_err(|_| {
+            PyValueError::new_err(format!(
+                "Decks need {} cards but one has {}",
+                DECK_SIZE, len
+            ))
+        })
+    });
+    Ok([deck1?, deck2?])
+}
+
+fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::P1Win => "P1Win",
+        Outcome::P2Win => "P2Win",
+        Outcome::Draw => "Draw",
+    }
+}
+
+fn player_number(player_num: PlayerNum) -> u8 {
+    match player_num {
+        PlayerNum::P1 => 1,
+        PlayerNum::P2 => 2,
+    }
+}
+
+fn report_dict<'py>(py: Python<'py>, report: TurnReport) -> PyResult<Bound<'py, PyDict>> {
+    let plays = report
+        .plays
+        .into_iter()
+        .map(|play| {
+            let dict = PyDict::new(py);
+            dict.set_item("card", PyCard(play.card))?;
+            dict.set_item("placed", play.placed)?;
+            dict.set_item("special_activated", play.special_activated)?;
+            dict.set_item("inked", play.inked)?;
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let conflict = report
+        .conflict
+        .map(|conflict| {
+            let dict = PyDict::new(py);
+            dict.set_item("spaces", conflict.spaces)?;
+            dict.set_item("winner", conflict.winner.map(player_number))?;
+            Ok::<_, PyErr>(dict)
+        })
+        .transpose()?;
+    let dict = PyDict::new(py);
+    dict.set_item("plays", plays)?;
+    dict.set_item("conflict", conflict)?;
+    Ok(dict)
+}
+
+#[pyclass(name = "GameState", module = "tableturf")]
+pub struct PyGameState(GameState<DeckRng>);
+
+impl PyGameState {
+    fn validate(&self, player: u8, input: &PyMove) -> PyResult<ValidInput> {
+        let player = self.0.player(player_num(player)?);
+        ValidInput::new(input.0.clone(), self.0.board(), player)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+}
+
+#[pymethods]
+impl PyGameState {
+    // Starts a game, on the default board with the default decks unless others are given.
+    // Without a seed, cards are drawn at random.
+    #[new]
+    #[pyo3(signature = (turns = DEFAULT_TURNS, seed = None, board = None, decks = None))]
+    fn new(
+        turns: u32,
+        seed: Option<u64>,
+        board: Option<PyBoard>,
+        decks: Option<[Vec<PyCard>; 2]>,
+    ) -> PyResult<Self> {
+        let board = board.map_or_else(common::default_board, |board| board.0);
+        let rng = seed.map_or_else(DeckRng::default, DeckRng::from_seed);
+        Ok(PyGameState(GameState::with_stage(
+            board,
+            self::decks(decks)?,
+            turns,
+            rng,
+        )))
+    }
+
+    #[getter]
+    fn board(&self) -> PyBoard {
+        PyBoard(self.0.board().clone())
+    }
+
+    #[getter]
+    fn turns_left(&self) -> u32 {
+        self.0.turns_left()
+    }
+
+    fn hand(&self, player: u8) -> PyResult<Vec<PyCard>> {
+        let player = self.0.player(player_num(player)?);
+        Ok(HandIndex::ALL
+            .iter()
+            .map(|hand_idx| PyCard(player.get_card(*hand_idx).clone()))
+            .collect())
+    }
+
+    fn special(&self, player: u8) -> PyResult<u32> {
+        Ok(self.0.player(player_num(player)?).special)
+    }
+
+    fn redraw_hand(&mut self, player: u8) -> PyResult<()> {
+        self.0.redraw_hand(player_num(player)?);
+        Ok(())
+    }
+
+    // Every move the player can make this turn
+    fn legal_moves(&self, player: u8) -> PyResult<Vec<PyMove>> {
+        let player = self.0.player(player_num(player)?);
+        Ok(legal_inputs(self.0.board(), player)
+            .into_iter()
+            .map(|(input, _)| PyMove(input))
+            .collect())
+    }
+
+    // Raises ValueError with the reason if the move is illegal
+    fn check_move(&self, player: u8, input: &PyMove) -> PyResult<()> {
+        self.validate(player, input).map(|_| ())
+    }
+
+    // Plays a turn and describes what happened
+    fn update<'py>(
+        &mut self,
+        py: Python<'py>,
+        move1: &PyMove,
+        move2: &PyMove,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let input1 = self.validate(1, move1)?;
+        let input2 = self.validate(2, move2)?;
+        report_dict(py, self.0.update(input1, input2))
+    }
+
+    // P1Win, P2Win or Draw, going by the ink on the board now
+    fn outcome(&self) -> &'static str {
+        outcome_name(self.0.check_winner())
+    }
+}
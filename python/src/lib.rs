@@ -0,0 +1,133 @@
+// Python bindings for the rules engine, so that tools written in Python play by the same
+// rules as the server
+mod board;
+mod env;
+mod game;
+
+use pyo3::prelude::*;
+
+#[pymodule]
+fn tableturf(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<board::PyBoard>()?;
+    m.add_class::<board::PyCard>()?;
+    m.add_class::<game::PyMove>()?;
+    m.add_class::<game::PyGameState>()?;
+    m.add_class::<env::PyEnv>()?;
+    m.add_function(wrap_pyfunction!(board::default_board, m)?)?;
+    m.add_function(wrap_pyfunction!(board::default_deck, m)?)?;
+    m.add_function(wrap_pyfunction!(env::decode_action, m)?)?;
+    m.add_function(wrap_pyfunction!(env::encode_action, m)?)?;
+    m.add("ACTIONS", common::env::ACTIONS)?;
+    m.add("BOARD_PLANES", common::env::BOARD_PLANES)?;
+    m.add("BOARD_FEATURES", common::env::BOARD_FEATURES)?;
+    m.add("CARD_PLANES", common::env::CARD_PLANES)?;
+    m.add("HAND_FEATURES", common::env::HAND_FEATURES)?;
+    m.add("SCALAR_FEATURES", common::env::SCALAR_FEATURES)?;
+    m.add("DEFAULT_TURNS", common::DEFAULT_TURNS)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    // Runs Python code with the module imported as tableturf, failing on any exception
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "tableturf").unwrap();
+            tableturf(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("tableturf", module).unwrap();
+            if let Err(err) = py.run(&CString::new(code).unwrap(), Some(&globals), None) {
+                err.print(py);
+                panic!("Python code failed: {}", err);
+            }
+        });
+    }
+
+    #[test]
+    fn test_board_and_cards() {
+        run(r#"
+board = tableturf.Board([["MT", "S2", "MT"], ["MT", "WW", "MT"], ["MT", "S1", "MT"]])
+assert board.width == 3 and board.height == 3
+assert board.get_space(1, 0) == "S2"
+assert board.rows()[0][0] == "MT"
+assert board.count_inked_spaces(1) == 1
+try:
+    tableturf.Board([["MT", "XX"]])
+    raise AssertionError("unknown spaces are rejected")
+except ValueError as err:
+    assert "XX" in str(err)
+
+deck = tableturf.default_deck()
+assert len(deck) == 15
+card = deck[0]
+copy = tableturf.Card(card.name, card.priority, card.special, card.spaces)
+assert copy == card
+assert sum(row.count(2) for row in card.spaces) == 1
+assert card.size == sum(1 for row in card.spaces for space in row if space)
+x, y = card.pivot
+assert 0 <= x < 8 and 0 <= y < 8
+"#);
+    }
+
+    #[test]
+    fn test_game_state() {
+        run(r#"
+game = tableturf.GameState(turns=2, seed=1)
+assert game.board == tableturf.default_board()
+assert len(game.hand(1)) == 4
+moves = game.legal_moves(1)
+assert any(move.is_pass for move in moves)
+placement = next(move for move in moves if not move.is_pass)
+game.check_move(1, placement)
+try:
+    game.check_move(1, tableturf.Move.place(0, 0, 0))
+    raise AssertionError("moves away from the player's ink are rejected")
+except ValueError:
+    pass
+
+report = game.update(placement, tableturf.Move.pass_card(0))
+assert report["plays"][0]["placed"] and not report["plays"][1]["placed"]
+assert report["plays"][0]["inked"] > 0
+assert report["conflict"] is None
+assert game.turns_left == 1 and game.special(2) == 1
+assert game.outcome() == "P1Win"
+"#);
+    }
+
+    #[test]
+    fn test_env() {
+        run(r#"
+env = tableturf.Env(opponent="greedy", seat=2, turns=2, seed=3)
+observation = env.reset(3)
+assert len(observation["board"]) == tableturf.BOARD_FEATURES
+assert len(observation["hand"]) == tableturf.HAND_FEATURES
+assert len(observation["scalars"]) == tableturf.SCALAR_FEATURES
+assert len(observation["mask"]) == tableturf.ACTIONS
+
+legal = [i for i, legal in enumerate(observation["mask"]) if legal]
+moves = [tableturf.decode_action(i, env.board, 2) for i in legal]
+action, move = next((i, move) for i, move in zip(legal, moves) if not move.is_pass)
+assert tableturf.encode_action(move, env.board, 2) == action
+
+done = False
+while not done:
+    action = observation["mask"].index(True)
+    observation, reward, done = env.step(action)
+assert env.done and reward in (-1.0, 0.0, 1.0)
+try:
+    env.step(0)
+    raise AssertionError("finished games can't be played")
+except ValueError:
+    pass
+
+self_play = tableturf.Env(opponent="self-play")
+(observation1, _, _), (observation2, _, _) = self_play.step_both(0, 0)
+assert self_play.turns_left == tableturf.DEFAULT_TURNS - 1
+"#);
+    }
+}
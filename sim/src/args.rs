@@ -0,0 +1,69 @@
+use crate::setup::{self, AgentKind, DeckList, SetupError, Stage, DEFAULT};
+use crate::tournament::Tournament;
+use clap::Args;
+use common::DEFAULT_TURNS;
+
+// Options for setting up games, shared by the binaries
+#[derive(Args, Debug)]
+pub struct GameArgs {
+    /// Agents to enter, separated by commas. An agent can be entered more than once.
+    #[arg(long, value_delimiter = ',', default_values = ["greedy", "random"])]
+    pub agents: Vec<AgentKind>,
+    /// Games each pairing plays on every stage with every deck. Seats alternate between games.
+    #[arg(long, default_value_t = 100)]
+    pub games: usize,
+    /// Stages to play on: `default` or a JSON file of board rows. Can be repeated.
+    #[arg(long = "stage", default_values = [DEFAULT])]
+    pub stages: Vec<String>,
+    /// Decks both players use: `default` or a JSON file of cards. Can be repeated.
+    #[arg(long = "deck", default_values = [DEFAULT])]
+    pub decks: Vec<String>,
+    #[arg(long, default_value_t = DEFAULT_TURNS)]
+    pub turns: u32,
+    /// Seed for every game and agent. The same seed and options always give the same results.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Threads to play games on. Defaults to one per core.
+    #[arg(long)]
+    pub threads: Option<usize>,
+}
+
+impl GameArgs {
+    pub fn tournament(&self) -> Result<Tournament, SetupError> {
+        Ok(Tournament {
+            entrants: setup::entrants(&self.agents),
+            stages: self
+                .stages
+                .iter()
+                .map(|spec| Stage::load(spec))
+                .collect::<Result<_, _>>()?,
+            decks: self
+                .decks
+                .iter()
+                .map(|spec| DeckList::load(spec))
+                .collect::<Result<_, _>>()?,
+            games: self.games,
+            turns: self.turns,
+            seed: self.seed,
+        })
+    }
+
+    // Loads the stages and decks and sets up the threads games are played on,
+    // exiting if anything is wrong
+    pub fn setup(&self) -> Tournament {
+        let tournament = match self.tournament() {
+            Ok(tournament) => tournament,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+        if let Some(threads) = self.threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .unwrap();
+        }
+        tournament
+    }
+}
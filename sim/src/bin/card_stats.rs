@@ -0,0 +1,43 @@
+use clap::{Parser, ValueEnum};
+use tableturf_sim::args::GameArgs;
+use tableturf_sim::cards::{card_stats, to_csv};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Output {
+    Csv,
+    Json,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Collect statistics for every card from simulated Tableturf games")]
+struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+    #[arg(long, value_enum, default_value_t = Output::Csv)]
+    output: Output,
+}
+
+fn main() {
+    let args = Args::parse();
+    let tournament = args.game.setup();
+    // A single agent plays against itself
+    let entrants = tournament.entrants.len();
+    let pairings: Vec<(usize, usize)> = if entrants == 1 {
+        vec![(0, 0)]
+    } else {
+        (0..entrants)
+            .flat_map(|a| (a + 1..entrants).map(move |b| (a, b)))
+            .collect()
+    };
+    let specs = pairings
+        .iter()
+        .enumerate()
+        .flat_map(|(pairing, &(a, b))| tournament.match_specs(a, b, 0, pairing))
+        .collect();
+    let stats = card_stats(&tournament, specs);
+    match args.output {
+        Output::Csv => println!("{}", to_csv(&stats)),
+        // If we cannot serialize our own statistics, panic because that's a bug
+        Output::Json => println!("{}", serde_json::to_string_pretty(&stats).unwrap()),
+    }
+}
@@ -0,0 +1,283 @@
+use crate::game::{play_game_with, GameResult, GameSpec};
+use crate::tournament::Tournament;
+use common::{Card, Outcome, PlayerNum, TurnReport};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+// Counts for a card, where every player in every game is a separate sample
+#[derive(Clone, Debug, Default, PartialEq)]
+struct CardTotals {
+    priority: u32,
+    special_cost: u32,
+    // Times the card was in a player's deck
+    decks: u32,
+    // Times a player placed the card at least once in a game, and how those games went
+    played: u32,
+    wins: u32,
+    draws: u32,
+    placements: u32,
+    ink: u32,
+    conflicts: u32,
+    priority_wins: u32,
+    special_attacks: u32,
+    passes: u32,
+}
+
+impl CardTotals {
+    fn merge(&mut self, other: &CardTotals) {
+        self.priority = other.priority;
+        self.special_cost = other.special_cost;
+        self.decks += other.decks;
+        self.played += other.played;
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.placements += other.placements;
+        self.ink += other.ink;
+        self.conflicts += other.conflicts;
+        self.priority_wins += other.priority_wins;
+        self.special_attacks += other.special_attacks;
+        self.passes += other.passes;
+    }
+}
+
+// Cards are told apart by name, so changed versions of a card need a new name
+#[derive(Clone, Debug, Default)]
+struct CardTable(BTreeMap<String, CardTotals>);
+
+impl CardTable {
+    fn card(&mut self, card: &Card) -> &mut CardTotals {
+        let totals = self.0.entry(card.name()).or_default();
+        totals.priority = card.priority();
+        totals.special_cost = card.special();
+        totals
+    }
+
+    fn add_game(&mut self, cards: &[Card], result: &GameResult, turns: &[TurnReport]) {
+        for (seat, player_num) in [PlayerNum::P1, PlayerNum::P2].into_iter().enumerate() {
+            for card in cards {
+                self.card(card).decks += 1;
+            }
+            let mut played = BTreeSet::new();
+            for turn in turns {
+                let play = &turn.plays[seat];
+                let totals = self.card(&play.card);
+                if !play.placed {
+                    totals.passes += 1;
+                    continue;
+                }
+                played.insert(play.card.name());
+                totals.placements += 1;
+                totals.ink += play.inked;
+                if play.special_activated {
+                    totals.special_attacks += 1;
+                }
+                if let Some(conflict) = turn.conflict {
+                    totals.conflicts += 1;
+                    if conflict.winner == Some(player_num) {
+                        totals.priority_wins += 1;
+                    }
+                }
+            }
+            for name in played {
+                let totals = self.0.get_mut(&name).unwrap();
+                totals.played += 1;
+                match (result.outcome, player_num) {
+                    (Outcome::P1Win, PlayerNum::P1) | (Outcome::P2Win, PlayerNum::P2) => {
+                        totals.wins += 1
+                    }
+                    (Outcome::Draw, _) => totals.draws += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn merge(mut self, other: CardTable) -> CardTable {
+        for (name, totals) in other.0 {
+            self.0.entry(name).or_default().merge(&totals);
+        }
+        self
+    }
+}
+
+// Statistics for a card. Rates are left out when nothing was counted for them.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CardStats {
+    pub card: String,
+    pub priority: u32,
+    pub special_cost: u32,
+    // Times the card was in a player's deck
+    pub games: u32,
+    // Share of those games in which the card was placed at least once
+    pub play_rate: Option<f64>,
+    // Share of the games in which the card was placed that were won, counting draws as half
+    pub win_rate_when_played: Option<f64>,
+    pub placements: u32,
+    // Spaces each placement still had inked at the end of its turn
+    pub average_ink: Option<f64>,
+    // Placements that overlapped the opponent's card, and the share of those where this
+    // card took priority
+    pub conflicts: u32,
+    pub priority_win_rate: Option<f64>,
+    pub special_attacks: u32,
+    pub special_attack_rate: Option<f64>,
+    pub passes: u32,
+}
+
+fn rate(count: f64, total: u32) -> Option<f64> {
+    (total > 0).then(|| count / f64::from(total))
+}
+
+impl CardStats {
+    fn new(card: String, totals: &CardTotals) -> Self {
+        CardStats {
+            card,
+            priority: totals.priority,
+            special_cost: totals.special_cost,
+            games: totals.decks,
+            play_rate: rate(f64::from(totals.played), totals.decks),
+            win_rate_when_played: rate(
+                f64::from(totals.wins) + f64::from(totals.draws) / 2.0,
+                totals.played,
+            ),
+            placements: totals.placements,
+            average_ink: rate(f64::from(totals.ink), totals.placements),
+            conflicts: totals.conflicts,
+            priority_win_rate: rate(f64::from(totals.priority_wins), totals.conflicts),
+            special_attacks: totals.special_attacks,
+            special_attack_rate: rate(f64::from(totals.special_attacks), totals.placements),
+            passes: totals.passes,
+        }
+    }
+}
+
+// Plays the games in parallel and collects statistics for every card in the decks,
+// ordered by name
+pub fn card_stats(tournament: &Tournament, specs: Vec<GameSpec>) -> Vec<CardStats> {
+    let table = specs
+        .into_par_iter()
+        .fold(CardTable::default, |mut table, spec| {
+            let mut turns = vec![];
+            let result = play_game_with(
+                spec,
+                &tournament.entrants,
+                &tournament.stages,
+                &tournament.decks,
+                tournament.turns,
+                |turn| turns.push(turn),
+            );
+            table.add_game(&tournament.decks[result.spec.deck].cards, &result, &turns);
+            table
+        })
+        .reduce(CardTable::default, CardTable::merge);
+    table
+        .0
+        .iter()
+        .map(|(name, totals)| CardStats::new(name.clone(), totals))
+        .collect()
+}
+
+const CSV_HEADER: &str = "card,priority,special_cost,games,play_rate,win_rate_when_played,\
+placements,average_ink,conflicts,priority_win_rate,special_attacks,special_attack_rate,passes";
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_rate(rate: Option<f64>) -> String {
+    rate.map(|rate| format!("{:.4}", rate)).unwrap_or_default()
+}
+
+pub fn to_csv(stats: &[CardStats]) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+    for card in stats {
+        lines.push(format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&card.card),
+            card.priority,
+            card.special_cost,
+            card.games,
+            csv_rate(card.play_rate),
+            csv_rate(card.win_rate_when_played),
+            card.placements,
+            csv_rate(card.average_ink),
+            card.conflicts,
+            csv_rate(card.priority_win_rate),
+            card.special_attacks,
+            csv_rate(card.special_attack_rate),
+            card.passes,
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{entrants, AgentKind, DeckList, Stage, DEFAULT};
+
+    fn tournament() -> Tournament {
+        Tournament {
+            entrants: entrants(&[AgentKind::Greedy, AgentKind::Random]),
+            stages: vec![Stage::load(DEFAULT).unwrap()],
+            decks: vec![DeckList::load(DEFAULT).unwrap()],
+            games: 4,
+            turns: 6,
+            seed: 5,
+        }
+    }
+
+    #[test]
+    fn test_card_stats() {
+        let tournament = tournament();
+        let specs = tournament.match_specs(0, 1, 0, 0);
+        let stats = card_stats(&tournament, specs.clone());
+        assert_eq!(stats.len(), 15);
+        for card in &stats {
+            // Both players have every card in every game
+            assert_eq!(card.games, 8);
+            assert!(card.play_rate.unwrap_or(0.0) <= 1.0);
+            assert!(card.priority_win_rate.unwrap_or(0.0) <= 1.0);
+        }
+        // Every turn each player plays a card, by placing it or passing
+        let plays: u32 = stats.iter().map(|card| card.placements + card.passes).sum();
+        assert_eq!(plays, 4 * 6 * 2);
+        // Both cards in a conflict count it
+        let conflicts: u32 = stats.iter().map(|card| card.conflicts).sum();
+        assert_eq!(conflicts % 2, 0);
+
+        // Results don't depend on how the games were split between threads
+        assert_eq!(card_stats(&tournament, specs), stats);
+    }
+
+    #[test]
+    fn test_csv() {
+        let stats = vec![CardStats {
+            card: "Splat, \"Bomb\"".to_string(),
+            priority: 3,
+            special_cost: 1,
+            games: 10,
+            play_rate: Some(0.5),
+            win_rate_when_played: Some(0.25),
+            placements: 6,
+            average_ink: Some(2.5),
+            conflicts: 0,
+            priority_win_rate: None,
+            special_attacks: 1,
+            special_attack_rate: Some(1.0 / 6.0),
+            passes: 0,
+        }];
+        let csv = to_csv(&stats);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "\"Splat, \"\"Bomb\"\"\",3,1,10,0.5000,0.2500,6,2.5000,0,,1,0.1667,0"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::setup::{DeckList, Entrant, Stage};
+use common::ai::Agent;
+use common::{DeckRng, GameState, Outcome, PlayerNum, TurnReport, ValidInput};
+
+// Everything needed to replay a game exactly
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSpec {
+    // Indices of the entrants in each seat, Player 1 first
+    pub seats: [usize; 2],
+    pub stage: usize,
+    pub deck: usize,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameResult {
+    pub spec: GameSpec,
+    pub outcome: Outcome,
+    // Spaces inked by each seat at the end of the game
+    pub ink: [u32; 2],
+}
+
+// SplitMix64, so that nearby seeds give unrelated games
+pub fn mix_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce5_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn choose(
+    agent: &mut dyn Agent,
+    game_state: &GameState<DeckRng>,
+    player_num: PlayerNum,
+) -> ValidInput {
+    let board = game_state.board();
+    let player = game_state.player(player_num);
+    let input = agent.choose(board, player);
+    // Agents only pick from the legal inputs, so anything else is a bug in the agent
+    ValidInput::new(input, board, player).expect("agent chose an illegal input")
+}
+
+// Plays a game with both players using copies of the same deck
+pub fn play_game(
+    spec: GameSpec,
+    entrants: &[Entrant],
+    stages: &[Stage],
+    decks: &[DeckList],
+    turns: u32,
+) -> GameResult {
+    play_game_with(spec, entrants, stages, decks, turns, |_| {})
+}
+
+// Like play_game, but passes what happened on every turn to on_turn
+pub fn play_game_with(
+    spec: GameSpec,
+    entrants: &[Entrant],
+    stages: &[Stage],
+    decks: &[DeckList],
+    turns: u32,
+    mut on_turn: impl FnMut(TurnReport),
+) -> GameResult {
+    let cards = &decks[spec.deck].cards;
+    let mut game_state = GameState::with_stage(
+        stages[spec.stage].board.clone(),
+        [cards.clone(), cards.clone()],
+        turns,
+        DeckRng::from_seed(spec.seed),
+    );
+    let mut agents = [
+        entrants[spec.seats[0]].kind.build(mix_seed(spec.seed ^ 1)),
+        entrants[spec.seats[1]].kind.build(mix_seed(spec.seed ^ 2)),
+    ];
+    for (agent, player_num) in agents.iter_mut().zip([PlayerNum::P1, PlayerNum::P2]) {
+        if agent.redraw(game_state.board(), game_state.player(player_num)) {
+            game_state.redraw_hand(player_num);
+        }
+    }
+    let [agent1, agent2] = &mut agents;
+    while game_state.turns_left() > 0 {
+        let input1 = choose(agent1.as_mut(), &game_state, PlayerNum::P1);
+        let input2 = choose(agent2.as_mut(), &game_state, PlayerNum::P2);
+        on_turn(game_state.update(input1, input2));
+    }
+    let board = game_state.board();
+    GameResult {
+        outcome: game_state.check_winner(),
+        ink: [
+            board.count_inked_spaces(PlayerNum::P1),
+            board.count_inked_spaces(PlayerNum::P2),
+        ],
+        spec,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{entrants, AgentKind, DEFAULT};
+
+    #[test]
+    fn test_games_are_reproducible() {
+        let entrants = entrants(&[AgentKind::Random, AgentKind::Greedy]);
+        let stages = [Stage::load(DEFAULT).unwrap()];
+        let decks = [DeckList::load(DEFAULT).unwrap()];
+        let spec = GameSpec {
+            seats: [0, 1],
+            stage: 0,
+            deck: 0,
+            seed: 7,
+        };
+        let result1 = play_game(spec.clone(), &entrants, &stages, &decks, 12);
+        let result2 = play_game(spec, &entrants, &stages, &decks, 12);
+        assert_eq!(result1.outcome, result2.outcome);
+        assert_eq!(result1.ink, result2.ink);
+        assert!(result1.ink[0] > 0 && result1.ink[1] > 0);
+    }
+}
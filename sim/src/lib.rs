@@ -0,0 +1,7 @@
+pub mod args;
+pub mod cards;
+pub mod game;
+pub mod report;
+pub mod setup;
+pub mod stats;
+pub mod tournament;
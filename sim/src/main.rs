@@ -0,0 +1,48 @@
+use clap::{Parser, ValueEnum};
+use std::time::Instant;
+use tableturf_sim::args::GameArgs;
+use tableturf_sim::report::{head_to_head_table, setup_table, standings_table};
+use tableturf_sim::tournament::Format;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FormatArg {
+    RoundRobin,
+    Swiss,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Run a tournament between Tableturf agents without a server")]
+struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+    #[arg(long, value_enum, default_value_t = FormatArg::RoundRobin)]
+    format: FormatArg,
+    /// Rounds to play in a Swiss tournament
+    #[arg(long, default_value_t = 3)]
+    rounds: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+    let tournament = args.game.setup();
+    let format = match args.format {
+        FormatArg::RoundRobin => Format::RoundRobin,
+        FormatArg::Swiss => Format::Swiss {
+            rounds: args.rounds,
+        },
+    };
+
+    let start = Instant::now();
+    let results = tournament.run(format);
+    println!(
+        "Played {} games in {:.1}s",
+        results.games.len(),
+        start.elapsed().as_secs_f64()
+    );
+    println!();
+    println!("{}", standings_table(&tournament, &results));
+    println!();
+    println!("{}", head_to_head_table(&tournament, &results));
+    println!();
+    println!("{}", setup_table(&tournament, &results));
+}
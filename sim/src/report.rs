@@ -0,0 +1,171 @@
+use crate::stats::{GameOutcome, Record};
+use crate::tournament::{Results, Tournament};
+use common::Outcome;
+
+fn name_width(names: impl Iterator<Item = usize>) -> usize {
+    names.max().unwrap_or(0).max("Entrant".len())
+}
+
+// Overall results of each entrant, best first
+pub fn standings_table(tournament: &Tournament, results: &Results) -> String {
+    let width = name_width(tournament.entrants.iter().map(|entrant| entrant.name.len()));
+    let mut lines = vec![format!(
+        "{:<width$}  {:>6}  {:>5}  {:>5}  {:>5}  {:>5}  {:>15}  {:>16}",
+        "Entrant", "Points", "W", "D", "L", "Score", "95% CI", "Ink margin"
+    )];
+    for entrant in results.ranking() {
+        let standing = &results.standings[entrant];
+        let record = &standing.record;
+        let (low, high) = record.score_interval();
+        lines.push(format!(
+            "{:<width$}  {:>6.1}  {:>5}  {:>5}  {:>5}  {:>5.3}  {:>15}  {:>16}",
+            tournament.entrants[entrant].name,
+            f64::from(standing.match_points) / 2.0,
+            record.wins,
+            record.draws,
+            record.losses,
+            record.score(),
+            format!("[{:.3}, {:.3}]", low, high),
+            margin(record),
+        ));
+    }
+    lines.join("\n")
+}
+
+// The row entrant's wins, draws and losses against each column entrant
+pub fn head_to_head_table(tournament: &Tournament, results: &Results) -> String {
+    let names: Vec<&str> = tournament
+        .entrants
+        .iter()
+        .map(|entrant| entrant.name.as_str())
+        .collect();
+    let width = name_width(names.iter().map(|name| name.len()));
+    let cells: Vec<Vec<String>> = results
+        .head_to_head
+        .iter()
+        .enumerate()
+        .map(|(a, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(b, record)| match (a == b, record.games()) {
+                    (true, _) => "-".to_string(),
+                    (false, 0) => "".to_string(),
+                    (false, _) => format!("{}-{}-{}", record.wins, record.draws, record.losses),
+                })
+                .collect()
+        })
+        .collect();
+    let column_width = cells
+        .iter()
+        .flatten()
+        .map(|cell| cell.len())
+        .chain(names.iter().map(|name| name.len()))
+        .max()
+        .unwrap_or(0);
+    let header: Vec<String> = names
+        .iter()
+        .map(|name| format!("{:>column_width$}", name))
+        .collect();
+    let mut lines = vec![format!("{:<width$}  {}", "W-D-L", header.join("  "))];
+    for (name, row) in names.iter().zip(cells) {
+        let row: Vec<String> = row
+            .iter()
+            .map(|cell| format!("{:>column_width$}", cell))
+            .collect();
+        lines.push(format!("{:<width$}  {}", name, row.join("  ")));
+    }
+    lines.join("\n")
+}
+
+// How the first player fares on each stage and deck. Every pairing plays both seats
+// equally often, so a lopsided result here points at the setup rather than the agents.
+pub fn setup_table(tournament: &Tournament, results: &Results) -> String {
+    let width = tournament
+        .stages
+        .iter()
+        .flat_map(|stage| {
+            tournament
+                .decks
+                .iter()
+                .map(move |deck| stage.name.len() + deck.name.len() + 3)
+        })
+        .max()
+        .unwrap_or(0)
+        .max("Stage / deck".len());
+    let mut lines = vec![format!(
+        "{:<width$}  {:>6}  {:>5}  {:>5}  {:>5}  {:>8}  {:>16}",
+        "Stage / deck", "Games", "P1 W", "D", "P2 W", "P1 score", "P1 ink margin"
+    )];
+    for (stage_idx, stage) in tournament.stages.iter().enumerate() {
+        for (deck_idx, deck) in tournament.decks.iter().enumerate() {
+            let mut record = Record::default();
+            for game in results
+                .games
+                .iter()
+                .filter(|game| game.spec.stage == stage_idx && game.spec.deck == deck_idx)
+            {
+                let outcome = match game.outcome {
+                    Outcome::P1Win => GameOutcome::Win,
+                    Outcome::Draw => GameOutcome::Draw,
+                    Outcome::P2Win => GameOutcome::Loss,
+                };
+                record.add(outcome, game.ink[0] as i32 - game.ink[1] as i32);
+            }
+            lines.push(format!(
+                "{:<width$}  {:>6}  {:>5}  {:>5}  {:>5}  {:>8.3}  {:>16}",
+                format!("{} / {}", stage.name, deck.name),
+                record.games(),
+                record.wins,
+                record.draws,
+                record.losses,
+                record.score(),
+                margin(&record),
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+fn margin(record: &Record) -> String {
+    format!(
+        "{:+.2} ± {:.2}",
+        record.mean_margin(),
+        record.margin_error()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{entrants, AgentKind, DeckList, Stage, DEFAULT};
+    use crate::tournament::Format;
+
+    #[test]
+    fn test_tables() {
+        let tournament = Tournament {
+            entrants: entrants(&[AgentKind::Greedy, AgentKind::Random]),
+            stages: vec![Stage::load(DEFAULT).unwrap()],
+            decks: vec![DeckList::load(DEFAULT).unwrap()],
+            games: 2,
+            turns: 3,
+            seed: 0,
+        };
+        let results = tournament.run(Format::RoundRobin);
+
+        let standings = standings_table(&tournament, &results);
+        assert_eq!(standings.lines().count(), 3);
+        assert!(standings.lines().any(|line| line.starts_with("random ")));
+
+        let head_to_head = head_to_head_table(&tournament, &results);
+        let lines: Vec<&str> = head_to_head.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].starts_with("random") && lines[2].ends_with('-'));
+
+        let setups = setup_table(&tournament, &results);
+        assert!(setups
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("default / default"));
+    }
+}
@@ -0,0 +1,191 @@
+use common::ai::{Agent, GreedyAgent, RandomAgent};
+use common::{default_board, default_deck, Board, BoardError, BoardSpace, Card, DECK_SIZE};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Name that selects the built-in stage or deck instead of a file
+pub const DEFAULT: &str = "default";
+
+#[derive(Error, Debug)]
+pub enum SetupError {
+    #[error("Failed to read {path}: {source}")]
+    Read { path: String, source: io::Error },
+    #[error("Failed to parse {path}: {source}")]
+    Parse {
+        path: String,
+        source: serde_json::Error,
+    },
+    #[error("Invalid stage {path}: {source}")]
+    Stage { path: String, source: BoardError },
+    #[error("Deck {path} has {len} cards but decks need {DECK_SIZE}")]
+    DeckSize { path: String, len: usize },
+    #[error("Unknown agent {0}. Expected random or greedy")]
+    UnknownAgent(String),
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, SetupError> {
+    let contents = fs::read_to_string(path).map_err(|source| SetupError::Read {
+        path: path.to_string(),
+        source,
+    })?;
+    serde_json::from_str(&contents).map_err(|source| SetupError::Parse {
+        path: path.to_string(),
+        source,
+    })
+}
+
+// Names a stage or deck after the file it was loaded from
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[derive(Clone, Debug)]
+pub struct Stage {
+    pub name: String,
+    pub board: Board,
+}
+
+impl Stage {
+    // Loads the built-in stage, or a JSON file with the board's rows of spaces
+    pub fn load(spec: &str) -> Result<Self, SetupError> {
+        if spec == DEFAULT {
+            return Ok(Stage {
+                name: DEFAULT.to_string(),
+                board: default_board(),
+            });
+        }
+        let spaces: Vec<Vec<BoardSpace>> = read_json(spec)?;
+        let board = Board::new(spaces).map_err(|source| SetupError::Stage {
+            path: spec.to_string(),
+            source,
+        })?;
+        Ok(Stage {
+            name: file_name(spec),
+            board,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeckList {
+    pub name: String,
+    pub cards: [Card; DECK_SIZE],
+}
+
+impl DeckList {
+    // Loads the built-in deck, or a JSON file with a list of cards
+    pub fn load(spec: &str) -> Result<Self, SetupError> {
+        if spec == DEFAULT {
+            return Ok(DeckList {
+                name: DEFAULT.to_string(),
+                cards: default_deck(),
+            });
+        }
+        let cards: Vec<Card> = read_json(spec)?;
+        let len = cards.len();
+        let cards = cards.try_into().map_err(|_| SetupError::DeckSize {
+            path: spec.to_string(),
+            len,
+        })?;
+        Ok(DeckList {
+            name: file_name(spec),
+            cards,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AgentKind {
+    Random,
+    Greedy,
+}
+
+impl FromStr for AgentKind {
+    type Err = SetupError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(AgentKind::Random),
+            "greedy" => Ok(AgentKind::Greedy),
+            _ => Err(SetupError::UnknownAgent(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AgentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgentKind::Random => write!(f, "random"),
+            AgentKind::Greedy => write!(f, "greedy"),
+        }
+    }
+}
+
+impl AgentKind {
+    // Agents keep state between moves, so every game gets fresh ones
+    pub fn build(self, seed: u64) -> Box<dyn Agent> {
+        match self {
+            AgentKind::Random => Box::new(RandomAgent::from_seed(seed)),
+            AgentKind::Greedy => Box::new(GreedyAgent),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Entrant {
+    pub name: String,
+    pub kind: AgentKind,
+}
+
+// Creates an entrant for every agent given. Agents entered more than once are numbered so
+// that they can be told apart in the results.
+pub fn entrants(kinds: &[AgentKind]) -> Vec<Entrant> {
+    kinds
+        .iter()
+        .enumerate()
+        .map(|(i, kind)| {
+            let copies = kinds.iter().filter(|other| *other == kind).count();
+            let name = if copies > 1 {
+                let number = kinds[..=i].iter().filter(|other| *other == kind).count();
+                format!("{}#{}", kind, number)
+            } else {
+                kind.to_string()
+            };
+            Entrant { name, kind: *kind }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entrant_names() {
+        let names: Vec<String> =
+            entrants(&[AgentKind::Greedy, AgentKind::Random, AgentKind::Greedy])
+                .into_iter()
+                .map(|entrant| entrant.name)
+                .collect();
+        assert_eq!(names, ["greedy#1", "random", "greedy#2"]);
+    }
+
+    #[test]
+    fn test_load_defaults() {
+        let stage = Stage::load(DEFAULT).unwrap();
+        assert_eq!(stage.board, default_board());
+        let deck = DeckList::load(DEFAULT).unwrap();
+        assert_eq!(deck.cards, default_deck());
+        assert!(matches!(
+            Stage::load("missing.json"),
+            Err(SetupError::Read { .. })
+        ));
+    }
+}
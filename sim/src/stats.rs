@@ -0,0 +1,135 @@
+// Two-sided 95% confidence
+const Z: f64 = 1.96;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameOutcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+// Results of a set of games from one side's point of view
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    // Sums of the differences between our ink and the opponent's, for the mean and variance
+    margin_sum: f64,
+    margin_sq_sum: f64,
+}
+
+impl Record {
+    pub fn add(&mut self, outcome: GameOutcome, margin: i32) {
+        match outcome {
+            GameOutcome::Win => self.wins += 1,
+            GameOutcome::Draw => self.draws += 1,
+            GameOutcome::Loss => self.losses += 1,
+        }
+        let margin = f64::from(margin);
+        self.margin_sum += margin;
+        self.margin_sq_sum += margin * margin;
+    }
+
+    pub fn merge(&mut self, other: &Record) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+        self.margin_sum += other.margin_sum;
+        self.margin_sq_sum += other.margin_sq_sum;
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // Share of games won, counting draws as half a win
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games())
+    }
+
+    // Wilson score interval for the score. Unlike the normal approximation, it stays
+    // within [0, 1] and is still meaningful for small samples and lopsided results.
+    pub fn score_interval(&self) -> (f64, f64) {
+        let n = f64::from(self.games());
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let p = self.score();
+        let z2 = Z * Z;
+        let denominator = 1.0 + z2 / n;
+        let center = (p + z2 / (2.0 * n)) / denominator;
+        let half_width = Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+        (
+            (center - half_width).max(0.0),
+            (center + half_width).min(1.0),
+        )
+    }
+
+    pub fn mean_margin(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        self.margin_sum / f64::from(self.games())
+    }
+
+    // Half the width of the confidence interval for the mean ink margin
+    pub fn margin_error(&self) -> f64 {
+        let n = f64::from(self.games());
+        if n < 2.0 {
+            return f64::INFINITY;
+        }
+        let mean = self.mean_margin();
+        let variance = ((self.margin_sq_sum - n * mean * mean) / (n - 1.0)).max(0.0);
+        Z * (variance / n).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_interval() {
+        let mut record = Record::default();
+        for _ in 0..8 {
+            record.add(GameOutcome::Win, 10);
+        }
+        record.add(GameOutcome::Draw, 0);
+        record.add(GameOutcome::Loss, -5);
+        assert_eq!(record.games(), 10);
+        assert!((record.score() - 0.85).abs() < 1e-9);
+        let (low, high) = record.score_interval();
+        assert!(low < 0.85 && 0.85 < high);
+        assert!((low - 0.5411).abs() < 1e-3, "{}", low);
+        assert!((high - 0.9646).abs() < 1e-3, "{}", high);
+
+        // A clean sweep still has some uncertainty
+        let mut sweep = Record::default();
+        sweep.add(GameOutcome::Win, 1);
+        let (low, high) = sweep.score_interval();
+        assert!(low > 0.0 && low < 0.5);
+        assert_eq!(high, 1.0);
+    }
+
+    #[test]
+    fn test_margin() {
+        let mut record = Record::default();
+        for margin in [2, 4, 4, 4, 5, 5, 7, 9] {
+            record.add(GameOutcome::Win, margin);
+        }
+        assert_eq!(record.mean_margin(), 5.0);
+        // The sample standard deviation is sqrt(32 / 7)
+        let expected = Z * (32.0 / 7.0 / 8.0_f64).sqrt();
+        assert!((record.margin_error() - expected).abs() < 1e-9);
+
+        let mut merged = Record::default();
+        merged.merge(&record);
+        merged.merge(&record);
+        assert_eq!(merged.games(), 16);
+        assert_eq!(merged.mean_margin(), 5.0);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::game::{mix_seed, play_game, GameResult, GameSpec};
+use crate::setup::{DeckList, Entrant, Stage};
+use crate::stats::{GameOutcome, Record};
+use common::Outcome;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    // Every entrant plays every other entrant once
+    RoundRobin,
+    // Entrants with similar standings are paired each round, without rematches where possible
+    Swiss { rounds: usize },
+}
+
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    pub entrants: Vec<Entrant>,
+    pub stages: Vec<Stage>,
+    pub decks: Vec<DeckList>,
+    // Games each pairing plays on every combination of stage and deck
+    pub games: usize,
+    pub turns: u32,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Standing {
+    pub record: Record,
+    // Two points for each match won and one for each match drawn or bye,
+    // so that standings don't need fractions
+    pub match_points: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Results {
+    pub standings: Vec<Standing>,
+    // Record of the row entrant against the column entrant
+    pub head_to_head: Vec<Vec<Record>>,
+    pub games: Vec<GameResult>,
+}
+
+impl Results {
+    fn new(entrants: usize) -> Self {
+        Results {
+            standings: vec![Standing::default(); entrants],
+            head_to_head: vec![vec![Record::default(); entrants]; entrants],
+            games: vec![],
+        }
+    }
+
+    fn add_match(&mut self, a: usize, b: usize, games: Vec<GameResult>) {
+        let mut record = Record::default();
+        for game in &games {
+            let seat = if game.spec.seats[0] == a { 0 } else { 1 };
+            let outcome = match (game.outcome, seat) {
+                (Outcome::Draw, _) => GameOutcome::Draw,
+                (Outcome::P1Win, 0) | (Outcome::P2Win, 1) => GameOutcome::Win,
+                _ => GameOutcome::Loss,
+            };
+            let margin = game.ink[seat] as i32 - game.ink[1 - seat] as i32;
+            record.add(outcome, margin);
+            let opponent_outcome = match outcome {
+                GameOutcome::Win => GameOutcome::Loss,
+                GameOutcome::Draw => GameOutcome::Draw,
+                GameOutcome::Loss => GameOutcome::Win,
+            };
+            self.head_to_head[b][a].add(opponent_outcome, -margin);
+        }
+        match record.wins.cmp(&record.losses) {
+            Ordering::Greater => self.standings[a].match_points += 2,
+            Ordering::Less => self.standings[b].match_points += 2,
+            Ordering::Equal => {
+                self.standings[a].match_points += 1;
+                self.standings[b].match_points += 1;
+            }
+        }
+        self.head_to_head[a][b].merge(&record);
+        self.recount(a);
+        self.recount(b);
+        self.games.extend(games);
+    }
+
+    // Rebuilds an entrant's overall record from its head-to-head records
+    fn recount(&mut self, entrant: usize) {
+        let mut record = Record::default();
+        for other in &self.head_to_head[entrant] {
+            record.merge(other);
+        }
+        self.standings[entrant].record = record;
+    }
+
+    // Entrants from best to worst, by match points and then by score
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.standings.len()).collect();
+        ranking.sort_by(|&a, &b| {
+            let (a, b) = (&self.standings[a], &self.standings[b]);
+            b.match_points
+                .cmp(&a.match_points)
+                .then(b.record.score().total_cmp(&a.record.score()))
+                .then(b.record.mean_margin().total_cmp(&a.record.mean_margin()))
+        });
+        ranking
+    }
+}
+
+impl Tournament {
+    pub fn run(&self, format: Format) -> Results {
+        let mut results = Results::new(self.entrants.len());
+        match format {
+            Format::RoundRobin => {
+                let pairings: Vec<(usize, usize)> = (0..self.entrants.len())
+                    .flat_map(|a| (a + 1..self.entrants.len()).map(move |b| (a, b)))
+                    .collect();
+                self.play_round(&pairings, 0, &mut results);
+            }
+            Format::Swiss { rounds } => {
+                let mut byes = vec![false; self.entrants.len()];
+                for round in 0..rounds {
+                    let (pairings, bye) = swiss_pairings(&results, &byes);
+                    if let Some(bye) = bye {
+                        byes[bye] = true;
+                        results.standings[bye].match_points += 2;
+                    }
+                    self.play_round(&pairings, round, &mut results);
+                }
+            }
+        }
+        results
+    }
+
+    // Plays every game of every match in the round in parallel
+    fn play_round(&self, pairings: &[(usize, usize)], round: usize, results: &mut Results) {
+        let specs: Vec<(usize, GameSpec)> = pairings
+            .iter()
+            .enumerate()
+            .flat_map(|(pairing, &(a, b))| {
+                self.match_specs(a, b, round, pairing)
+                    .into_iter()
+                    .map(move |spec| (pairing, spec))
+            })
+            .collect();
+        let played: Vec<(usize, GameResult)> = specs
+            .into_par_iter()
+            .map(|(pairing, spec)| {
+                let result = play_game(spec, &self.entrants, &self.stages, &self.decks, self.turns);
+                (pairing, result)
+            })
+            .collect();
+        let mut by_pairing: Vec<Vec<GameResult>> = vec![vec![]; pairings.len()];
+        for (pairing, result) in played {
+            by_pairing[pairing].push(result);
+        }
+        for (&(a, b), games) in pairings.iter().zip(by_pairing) {
+            results.add_match(a, b, games);
+        }
+    }
+
+    // Lists the games of a match. Games come in pairs with the same seed and the seats
+    // swapped, so both entrants are dealt the same cards and luck evens out.
+    pub fn match_specs(&self, a: usize, b: usize, round: usize, pairing: usize) -> Vec<GameSpec> {
+        let mut specs = vec![];
+        for stage in 0..self.stages.len() {
+            for deck in 0..self.decks.len() {
+                for game in 0..self.games {
+                    let seats = if game % 2 == 0 { [a, b] } else { [b, a] };
+                    let index = [round, pairing, stage, deck, game / 2]
+                        .iter()
+                        .fold(self.seed, |seed, &part| mix_seed(seed ^ part as u64));
+                    specs.push(GameSpec {
+                        seats,
+                        stage,
+                        deck,
+                        seed: index,
+                    });
+                }
+            }
+        }
+        specs
+    }
+}
+
+// Pairs entrants in order of their standings, each with the next entrant they haven't
+// played yet. With an odd number of entrants, the lowest ranked entrant that hasn't had
+// a bye yet sits out.
+fn swiss_pairings(results: &Results, byes: &[bool]) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut unpaired = results.ranking();
+    let mut bye = None;
+    if unpaired.len() % 2 == 1 {
+        let position = unpaired
+            .iter()
+            .rposition(|&entrant| !byes[entrant])
+            .unwrap_or(unpaired.len() - 1);
+        bye = Some(unpaired.remove(position));
+    }
+    let mut pairings = vec![];
+    while !unpaired.is_empty() {
+        let a = unpaired.remove(0);
+        // Fall back to a rematch when everyone left has been played already
+        let position = unpaired
+            .iter()
+            .position(|&b| results.head_to_head[a][b].games() == 0)
+            .unwrap_or(0);
+        let b = unpaired.remove(position);
+        pairings.push((a, b));
+    }
+    (pairings, bye)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{entrants, AgentKind, DEFAULT};
+
+    fn tournament(kinds: &[AgentKind], games: usize) -> Tournament {
+        Tournament {
+            entrants: entrants(kinds),
+            stages: vec![Stage::load(DEFAULT).unwrap()],
+            decks: vec![DeckList::load(DEFAULT).unwrap()],
+            games,
+            turns: 4,
+            seed: 3,
+        }
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let tournament = tournament(
+            &[AgentKind::Greedy, AgentKind::Random, AgentKind::Random],
+            4,
+        );
+        let results = tournament.run(Format::RoundRobin);
+        assert_eq!(results.games.len(), 3 * 4);
+        for (entrant, standing) in results.standings.iter().enumerate() {
+            assert_eq!(standing.record.games(), 8);
+            assert_eq!(results.head_to_head[entrant][entrant].games(), 0);
+        }
+        // Each pair of games swaps the seats
+        let seats = |i: usize| results.games[i].spec.seats;
+        assert_eq!(seats(0), [seats(1)[1], seats(1)[0]]);
+        assert_eq!(results.games[0].spec.seed, results.games[1].spec.seed);
+        assert_ne!(results.games[0].spec.seed, results.games[2].spec.seed);
+        // Head-to-head records mirror each other
+        let (a, b) = (&results.head_to_head[0][1], &results.head_to_head[1][0]);
+        assert_eq!((a.wins, a.draws, a.losses), (b.losses, b.draws, b.wins));
+        assert_eq!(a.mean_margin(), -b.mean_margin());
+    }
+
+    #[test]
+    fn test_results_are_reproducible() {
+        let tournament = tournament(&[AgentKind::Greedy, AgentKind::Random], 4);
+        let results1 = tournament.run(Format::RoundRobin);
+        let results2 = tournament.run(Format::RoundRobin);
+        let inks = |results: &Results| {
+            results
+                .games
+                .iter()
+                .map(|game| game.ink)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(inks(&results1), inks(&results2));
+    }
+
+    #[test]
+    fn test_swiss() {
+        let kinds = [AgentKind::Greedy, AgentKind::Random, AgentKind::Random];
+        let results = tournament(&kinds, 2).run(Format::Swiss { rounds: 3 });
+        // One entrant sits out each round, and no one sits out twice
+        assert_eq!(results.games.len(), 3 * 2);
+        for standing in &results.standings {
+            assert_eq!(standing.record.games(), 4);
+        }
+        // No rematches were needed, so everyone played each other once
+        for a in 0..kinds.len() {
+            for b in 0..kinds.len() {
+                if a != b {
+                    assert_eq!(results.head_to_head[a][b].games(), 2);
+                }
+            }
+        }
+        let points: u32 = results.standings.iter().map(|s| s.match_points).sum();
+        assert_eq!(points, 3 * 2 + 3 * 2);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{from_js, js_error, player_num, space_at, to_js, validate};
+use common::{
+    Board, Card, DeckRng, GameState, HandIndex, InputError, PlayerNum, RawInput, TurnReport,
+    ValidInput, DECK_SIZE, DEFAULT_TURNS,
+};
+use wasm_bindgen::prelude::*;
+
+// Decks are given as two lists of cards, for player 1 and player 2
+fn decks(decks: Option<[Vec<Card>; 2]>) -> Result<[[Card; DECK_SIZE]; 2], String> {
+    let Some(decks) = decks else {
+        return Ok([common::default_deck(), common::default_deck()]);
+    };
+    let [deck1, deck2] = decks.map(|deck| {
+        let len = deck.len();
+        deck.try_into()
+            .map_err(|_| format!("Decks need {} cards but one has {}", DECK_SIZE, len))
+    });
+    Ok([deck1?, deck2?])
+}
+
+// A game played entirely in the browser, e.g. to replay a match or try out a deck
+#[wasm_bindgen]
+pub struct Game(GameState<DeckRng>);
+
+impl Game {
+    fn create(
+        turns: Option<u32>,
+        seed: Option<u32>,
+        board: Option<Board>,
+        decks: Option<[Vec<Card>; 2]>,
+    ) -> Result<Self, String> {
+        let rng = seed.map_or_else(DeckRng::default, |seed| DeckRng::from_seed(seed.into()));
+        Ok(Game(GameState::with_stage(
+            board.unwrap_or_else(common::default_board),
+            self::decks(decks)?,
+            turns.unwrap_or(DEFAULT_TURNS),
+            rng,
+        )))
+    }
+
+    fn apply(&mut self, input1: RawInput, input2: RawInput) -> Result<TurnReport, InputError> {
+        let board = self.0.board();
+        let input1 = ValidInput::new(input1, board, self.0.player(PlayerNum::P1))?;
+        let input2 = ValidInput::new(input2, board, self.0.player(PlayerNum::P2))?;
+        Ok(self.0.update(input1, input2))
+    }
+}
+
+#[wasm_bindgen]
+impl Game {
+    // Starts a game, on the default board with the default decks unless others are given.
+    // Without a seed, cards are drawn at random.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        turns: Option<u32>,
+        seed: Option<u32>,
+        board: JsValue,
+        decks: JsValue,
+    ) -> Result<Game, JsError> {
+        Game::create(turns, seed, from_js(board)?, from_js(decks)?).map_err(js_error)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn board(&self) -> Result<JsValue, JsError> {
+        to_js(self.0.board())
+    }
+
+    #[wasm_bindgen(getter, js_name = turnsLeft)]
+    pub fn turns_left(&self) -> u32 {
+        self.0.turns_left()
+    }
+
+    // The player's hand, deck and special gauge, as the server sends it to that player
+    pub fn player(&self, player: u8) -> Result<JsValue, JsError> {
+        to_js(self.0.player(player_num(player).map_err(js_error)?))
+    }
+
+    // The cards in the player's hand, in hand order
+    pub fn hand(&self, player: u8) -> Result<JsValue, JsError> {
+        let player = self.0.player(player_num(player).map_err(js_error)?);
+        let hand: Vec<&Card> = HandIndex::ALL
+            .into_iter()
+            .map(|hand_idx| player.get_card(hand_idx))
+            .collect();
+        to_js(&hand)
+    }
+
+    #[wasm_bindgen(js_name = spaceAt)]
+    pub fn space_at(&self, x: usize, y: usize) -> Result<String, JsError> {
+        Ok(space_at(self.0.board(), x, y)
+            .map_err(js_error)?
+            .to_string())
+    }
+
+    #[wasm_bindgen(js_name = inkCount)]
+    pub fn ink_count(&self, player: u8) -> Result<u32, JsError> {
+        Ok(self
+            .0
+            .board()
+            .count_inked_spaces(player_num(player).map_err(js_error)?))
+    }
+
+    // Returns the spaces the input would ink, or throws with the reason it's illegal
+    #[wasm_bindgen(js_name = validatePlacement)]
+    pub fn validate_placement(&self, player: u8, input: JsValue) -> Result<JsValue, JsError> {
+        let player = self.0.player(player_num(player).map_err(js_error)?);
+        let spaces = validate(self.0.board(), player, from_js(input)?).map_err(js_error)?;
+        to_js(&spaces)
+    }
+
+    // Plays both players' inputs and describes what happened. Throws without changing the
+    // game if either input is illegal.
+    #[wasm_bindgen(js_name = applyTurn)]
+    pub fn apply_turn(&mut self, input1: JsValue, input2: JsValue) -> Result<JsValue, JsError> {
+        let report = self
+            .apply(from_js(input1)?, from_js(input2)?)
+            .map_err(js_error)?;
+        to_js(&report)
+    }
+
+    // "P1Win", "P2Win" or "Draw", going by the ink on the board now
+    pub fn outcome(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.check_winner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{ai::legal_inputs, Action};
+
+    #[test]
+    fn test_create() {
+        let game = Game::create(Some(3), Some(1), None, None).unwrap();
+        assert_eq!(game.0.turns_left(), 3);
+        assert_eq!(game.0.board(), &common::default_board());
+
+        let short = vec![common::default_deck()[0].clone()];
+        let err = Game::create(None, None, None, Some([short, Vec::new()]))
+            .err()
+            .unwrap();
+        assert_eq!(err, "Decks need 15 cards but one has 1");
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut game = Game::create(Some(2), Some(1), None, None).unwrap();
+        let pass = RawInput {
+            hand_idx: HandIndex::H1,
+            action: Action::Pass,
+        };
+        let (placement, _) = legal_inputs(game.0.board(), game.0.player(PlayerNum::P1))
+            .into_iter()
+            .find(|(input, _)| matches!(input.action, Action::Place(_)))
+            .unwrap();
+
+        // Player 2 can't make player 1's placement, and the game is left as it was
+        assert!(game.apply(pass.clone(), placement.clone()).is_err());
+        assert_eq!(game.0.turns_left(), 2);
+
+        let report = game.apply(placement, pass).unwrap();
+        assert!(report.plays[0].placed && !report.plays[1].placed);
+        assert_eq!(
+            report.plays[0].inked,
+            game.0.board().count_inked_spaces(PlayerNum::P1) - 1
+        );
+        assert_eq!(game.0.turns_left(), 1);
+    }
+}
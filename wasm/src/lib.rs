@@ -0,0 +1,203 @@
+// JavaScript bindings for the rules engine, for web tools that need the rules without the
+// Yew frontend. Boards, players, cards and inputs are passed as plain objects in the same
+// shape the server sends them over the websocket.
+mod game;
+
+pub use game::Game;
+
+use common::{
+    rotate_input, rotate_pivot, Board, BoardSpace, Card, Grid, InkSpace, Input, InputError,
+    Placement, Player, PlayerNum, RawInput, Rotation, ValidInput,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::prelude::*;
+
+// A space a placement would ink, in board coordinates
+#[derive(Serialize, Debug, PartialEq)]
+struct InkedSpace {
+    x: usize,
+    y: usize,
+    special: bool,
+}
+
+fn inked_spaces(placement: &Placement) -> Vec<InkedSpace> {
+    placement
+        .ink_spaces()
+        .0
+        .iter()
+        .map(|(position, space)| InkedSpace {
+            x: position.x(),
+            y: position.y(),
+            special: matches!(space, InkSpace::Special),
+        })
+        .collect()
+}
+
+// Runs the same checks as the server. Passing is always legal and inks nothing.
+fn validate(
+    board: &Board,
+    player: &Player,
+    input: RawInput,
+) -> Result<Vec<InkedSpace>, InputError> {
+    Ok(match ValidInput::new(input, board, player)?.get() {
+        Input::Place(placement) => inked_spaces(&placement),
+        Input::Pass => Vec::new(),
+    })
+}
+
+fn player_num(player: u8) -> Result<PlayerNum, String> {
+    match player {
+        1 => Ok(PlayerNum::P1),
+        2 => Ok(PlayerNum::P2),
+        _ => Err(format!("Unknown player {}. Expected 1 or 2", player)),
+    }
+}
+
+fn rotation(rotation: u8) -> Result<Rotation, String> {
+    Rotation::ALL
+        .get(usize::from(rotation))
+        .copied()
+        .ok_or_else(|| format!("Unknown rotation {}. Expected 0 to 3", rotation))
+}
+
+// Spaces are written the same way the board prints them, e.g. "I1" for player 1's ink
+fn space_at(board: &Board, x: usize, y: usize) -> Result<BoardSpace, String> {
+    if x >= board.width() || y >= board.height() {
+        return Err(format!(
+            "Space ({}, {}) is outside the {}x{} board",
+            x,
+            y,
+            board.width(),
+            board.height()
+        ));
+    }
+    Ok(board.get_space(x, y))
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    // Maps become plain objects rather than Map instances
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn js_error(err: impl ToString) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+// Returns the spaces the input would ink, or throws with the reason it's illegal
+#[wasm_bindgen(js_name = validatePlacement)]
+pub fn validate_placement(
+    board: JsValue,
+    player: JsValue,
+    input: JsValue,
+) -> Result<JsValue, JsError> {
+    let board: Board = from_js(board)?;
+    let player: Player = from_js(player)?;
+    let spaces = validate(&board, &player, from_js(input)?).map_err(js_error)?;
+    to_js(&spaces)
+}
+
+// The card's 8x8 grid after the given number of counter-clockwise quarter turns
+#[wasm_bindgen(js_name = rotateCard)]
+pub fn rotate_card(card: JsValue, rotation: u8) -> Result<JsValue, JsError> {
+    let card: Card = from_js(card)?;
+    let grid: Grid = rotate_input(&card, self::rotation(rotation).map_err(js_error)?);
+    to_js(&grid)
+}
+
+// Where the card's pivot is in the grid returned by rotateCard, as [x, y]
+#[wasm_bindgen(js_name = cardPivot)]
+pub fn card_pivot(card: JsValue, rotation: u8) -> Result<JsValue, JsError> {
+    let card: Card = from_js(card)?;
+    let (x, y) = rotate_pivot(&card, self::rotation(rotation).map_err(js_error)?);
+    to_js(&[x, y])
+}
+
+#[wasm_bindgen(js_name = spaceAt)]
+pub fn space_at_js(board: JsValue, x: usize, y: usize) -> Result<String, JsError> {
+    let board: Board = from_js(board)?;
+    Ok(space_at(&board, x, y).map_err(js_error)?.to_string())
+}
+
+#[wasm_bindgen(js_name = inkCount)]
+pub fn ink_count(board: JsValue, player: u8) -> Result<u32, JsError> {
+    let board: Board = from_js(board)?;
+    Ok(board.count_inked_spaces(player_num(player).map_err(js_error)?))
+}
+
+#[wasm_bindgen(js_name = defaultBoard)]
+pub fn default_board() -> Result<JsValue, JsError> {
+    to_js(&common::default_board())
+}
+
+#[wasm_bindgen(js_name = defaultDeck)]
+pub fn default_deck() -> Result<JsValue, JsError> {
+    to_js(&common::default_deck())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Action, DeckRng, GameState, HandIndex, RawPlacement};
+
+    fn place(x: i32, y: i32, special_activated: bool) -> RawInput {
+        RawInput {
+            hand_idx: HandIndex::H1,
+            action: Action::Place(RawPlacement {
+                x,
+                y,
+                special_activated,
+                rotation: Rotation::Zero,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let game_state = GameState::with_rng(12, DeckRng::from_seed(1));
+        let board = game_state.board();
+        let player = game_state.player(PlayerNum::P1);
+        let pass = RawInput {
+            hand_idx: HandIndex::H2,
+            action: Action::Pass,
+        };
+        assert_eq!(validate(board, player, pass).unwrap(), Vec::new());
+
+        assert!(matches!(
+            validate(board, player, place(-7, -7, false)),
+            Err(InputError::InvalidPosition(..))
+        ));
+        assert!(matches!(
+            validate(board, player, place(4, 4, true)),
+            Err(InputError::InsufficientSpecial { .. })
+        ));
+
+        let (input, _) = common::ai::legal_inputs(board, player)
+            .into_iter()
+            .find(|(input, _)| matches!(input.action, Action::Place(_)))
+            .unwrap();
+        let spaces = validate(board, player, input).unwrap();
+        assert!(!spaces.is_empty());
+        assert!(spaces.iter().all(|space| space_at(board, space.x, space.y)
+            .unwrap()
+            .eq(&BoardSpace::Empty)));
+    }
+
+    #[test]
+    fn test_arguments() {
+        assert_eq!(player_num(2), Ok(PlayerNum::P2));
+        assert!(player_num(0).is_err());
+        assert_eq!(rotation(3), Ok(Rotation::Three));
+        assert!(rotation(4).is_err());
+
+        let board = common::default_board();
+        assert_eq!(space_at(&board, 0, 0), Ok(BoardSpace::Empty));
+        assert!(space_at(&board, board.width(), 0).is_err());
+    }
+}